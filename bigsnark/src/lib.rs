@@ -3,4 +3,4 @@ extern crate serde_derive;
 
 mod rocks;
 
-pub use self::rocks::{RockConstraints, Rocks, RocksSignals};
+pub use self::rocks::{Manifest, RockConstraints, Rocks, RocksSignals};
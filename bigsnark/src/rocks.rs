@@ -1,21 +1,29 @@
 use circom2_parser::ast::SignalType;
 use circom2_compiler::algebra;
-use circom2_compiler::algebra::{SignalId, QEQ};
+use circom2_compiler::algebra::{Field, SignalId, QEQ};
 use circom2_compiler::storage;
-use circom2_compiler::storage::{StorageFactory,Constraints, Signal, SignalName, Signals};
+use circom2_compiler::storage::{StorageFactory,Constraints, ConstraintIter, Signal, SignalIter, SignalName, Signals};
 
-use rocksdb::{DB};
+use num_bigint::BigUint;
+use rocksdb::{Direction, IteratorMode, WriteBatch, DB};
 
-use std::rc::Rc;
+use std::ops::Range;
+use std::sync::Arc;
 
 use serde_cbor::{from_slice, to_vec};
+use std::fs::File;
 use std::path::PathBuf;
 
+const MANIFEST_FILE: &str = "manifest.json";
+
 #[derive(Debug)]
 pub enum Error {
     NotFound(String),
     RocksDb(rocksdb::Error),
     Cbor(serde_cbor::error::Error),
+    Json(serde_json::Error),
+    Io(std::io::Error),
+    Mismatch(String),
 }
 
 impl From<rocksdb::Error> for Error {
@@ -30,6 +38,18 @@ impl From<serde_cbor::error::Error> for Error {
     }
 }
 
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[macro_export]
@@ -48,81 +68,159 @@ struct SignalEntry {
     pub value: Option<algebra::Value>,
 }
 
+impl From<SignalEntry> for Signal {
+    fn from(entry: SignalEntry) -> Self {
+        Signal {
+            id: entry.id as usize,
+            xtype: entry.xtype,
+            full_name: SignalName::new(entry.full_name),
+            value: entry.value,
+        }
+    }
+}
+
+/// describes a compiled circuit stored in a Rocks database
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub circuit_hash: String,
+    pub field: String,
+    pub signals: usize,
+    pub constraints: usize,
+    pub signals_db: String,
+    pub constraints_db: String,
+}
+
 pub struct Rocks {
     base_path: String,
+    field: Field,
     count: usize,
 }
 
 impl Rocks {
-    pub fn new(base_path: String) -> Rocks {
+    pub fn new(base_path: String, field: Field) -> Rocks {
         Rocks {
             base_path,
+            field,
             count: 0,
         }
     }
+
+    /// open a database previously compiled and saved with `save_manifest`
+    pub fn open(base_path: &str) -> storage::Result<(Manifest, RocksSignals, RockConstraints)> {
+        let manifest = map_err!{{
+            let mut path = PathBuf::from(base_path);
+            path.push(MANIFEST_FILE);
+            serde_json::from_reader::<_, Manifest>(File::open(path)?)?
+        }}?;
+
+        let field = BigUint::parse_bytes(manifest.field.as_bytes(), 10)
+            .and_then(|prime| Field::from_prime(&prime))
+            .ok_or_else(|| storage::Error::Inner(format!(
+                "{:?}",
+                Error::Mismatch(format!("field {}", manifest.field))
+            )))?;
+
+        let signals = RocksSignals::new(&db_path(base_path, &manifest.signals_db), field)?;
+        let constraints = RockConstraints::new(&db_path(base_path, &manifest.constraints_db))?;
+
+        if signals.len()? != manifest.signals || constraints.len()? != manifest.constraints {
+            return Err(storage::Error::Inner(format!(
+                "{:?}",
+                Error::Mismatch("signal or constraint count".to_string())
+            )));
+        }
+
+        Ok((manifest, signals, constraints))
+    }
+
+    /// write the manifest that allows to `open` the database later
+    pub fn save_manifest(
+        &self,
+        circuit_hash: &str,
+        signals: &RocksSignals,
+        constraints: &RockConstraints,
+    ) -> storage::Result<Manifest> {
+        let manifest = Manifest {
+            circuit_hash: circuit_hash.to_string(),
+            field: signals.field().prime().to_string(),
+            signals: signals.len()?,
+            constraints: constraints.len()?,
+            signals_db: signals.name.clone(),
+            constraints_db: constraints.name.clone(),
+        };
+        map_err!{{
+            let mut path = PathBuf::from(&self.base_path);
+            path.push(MANIFEST_FILE);
+            serde_json::to_writer_pretty(File::create(path)?, &manifest)?
+        }}?;
+        Ok(manifest)
+    }
+
+    fn next_name(&mut self, kind: &str) -> String {
+        let name = format!("_{}_{}", kind, self.count);
+        self.count += 1;
+        name
+    }
 }
 
 impl StorageFactory<RocksSignals, RockConstraints> for Rocks {
     fn new_signals(&mut self) -> storage::Result<RocksSignals> {
-        let mut full_path = PathBuf::new();
-        full_path.push(&self.base_path);
-        full_path.push(format!("_signals_{}", self.count));
-        self.count += 1;
-        RocksSignals::new(full_path.as_os_str().to_str().unwrap())
+        let name = self.next_name("signals");
+        let mut signals = RocksSignals::new(&db_path(&self.base_path, &name), self.field)?;
+        signals.name = name;
+        Ok(signals)
     }
     fn new_constraints(&mut self) -> storage::Result<RockConstraints> {
-        let mut full_path = PathBuf::new();
-        full_path.push(&self.base_path);
-        full_path.push(format!("_constraints_{}", self.count));
-        self.count += 1;
-        RockConstraints::new(full_path.as_os_str().to_str().unwrap())
+        let name = self.next_name("constraints");
+        let mut constraints = RockConstraints::new(&db_path(&self.base_path, &name))?;
+        constraints.name = name;
+        Ok(constraints)
     }
 }
 
 pub struct RocksSignals {
     db: DB,
+    name: String,
+    field: Field,
 }
 
 impl RocksSignals {
-    pub fn new(path: &str) -> storage::Result<Self> {
-        (||{
-            Ok(DB::open_default(path).map(|x| RocksSignals { db: x })?)
-        })().map_err(|err:Error| storage::Error::Inner(format!("{:?})",err)))
+    pub fn new(path: &str, field: Field) -> storage::Result<Self> {
+        let mut signals = (||{
+            Ok(DB::open_default(path).map(|x| RocksSignals { db: x, name: path.to_string(), field })?)
+        })().map_err(|err:Error| storage::Error::Inner(format!("{:?})",err)))?;
+        if signals.is_empty()? {
+            signals.insert("one".to_string(), SignalType::PublicInput, None)?;
+        }
+        Ok(signals)
     }
 }
 
 pub struct RockConstraints {
     db: DB,
+    name: String,
 }
 
 impl RockConstraints {
     pub fn new(path: &str) -> storage::Result<Self> {
         map_err!{{
-            DB::open_default(path).map(|x| RockConstraints { db: x })?
+            DB::open_default(path).map(|x| RockConstraints { db: x, name: path.to_string() })?
         }}
     }               
 }
 
 impl RocksSignals {
     fn load(&self, id: SignalId) -> Result<Option<(Vec<u8>, SignalEntry)>> {
-        let index_bytes = u64_to_le(id as u64);
-
-        let mut key: Vec<u8> = vec![1];
-        key.extend_from_slice(&index_bytes);
+        let key = key_of(1, id as u64);
 
         match self.db.get(&key)? {
             None => Ok(None),
             Some(v) => Ok(Some((key, from_slice::<SignalEntry>(&v)?))),
         }
     }
-    fn _get_by_id(&self, id: SignalId) -> Result<Option<Rc<Signal>>> {
+    fn _get_by_id(&self, id: SignalId) -> Result<Option<Arc<Signal>>> {
         if let Some((_, entry)) = self.load(id)? {
-            Ok(Some(Rc::new(Signal {
-                id: entry.id as usize,
-                xtype: entry.xtype,
-                full_name: SignalName::new(entry.full_name),
-                value: entry.value,
-            })))
+            Ok(Some(Arc::new(Signal::from(entry))))
         } else {
             Ok(None)
         }
@@ -130,6 +228,9 @@ impl RocksSignals {
 }
 
 impl<'a> Signals for RocksSignals {
+    fn field(&self) -> Field {
+        self.field
+    }
     fn is_empty(&self) -> storage::Result<bool> {
         Ok(self.len()? == 0)
     }
@@ -155,8 +256,7 @@ impl<'a> Signals for RocksSignals {
                 value,
             };
 
-            let mut key: Vec<u8> = vec![1];
-            key.extend_from_slice(&index_bytes);
+            let key = key_of(1, index);
             self.db
                 .put(&key.to_owned(), to_vec(&entry).unwrap().as_slice())?;
 
@@ -181,13 +281,13 @@ impl<'a> Signals for RocksSignals {
         }}
     }
 
-    fn get_by_id(&self, id: SignalId) -> storage::Result<Option<Rc<Signal>>> {
+    fn get_by_id(&self, id: SignalId) -> storage::Result<Option<Arc<Signal>>> {
         map_err!{{
             self._get_by_id(id)?
         }}
     }
 
-    fn get_by_name(&self, full_name: &str) -> storage::Result<Option<Rc<Signal>>> {
+    fn get_by_name(&self, full_name: &str) -> storage::Result<Option<Arc<Signal>>> {
         map_err!{{
             let mut key: Vec<u8> = vec![2];
             key.extend_from_slice(full_name.as_bytes());
@@ -204,6 +304,40 @@ impl<'a> Signals for RocksSignals {
             format!("{:?}:{:?}:{:?}", s.full_name, s.xtype, s.value)
         }}
     }
+
+    fn iter_range(&self, range: Range<SignalId>) -> SignalIter {
+        let from = key_of(1, range.start as u64);
+        let end = range.end as u64;
+        Box::new(
+            self.db
+                .iterator(IteratorMode::From(&from, Direction::Forward))
+                .take_while(move |(key, _)| key[0] == 1 && u64_from_slice(&key[1..]) < end)
+                .map(|(_, value)| {
+                    map_err!{{
+                        Arc::new(Signal::from(from_slice::<SignalEntry>(&value)?))
+                    }}
+                }),
+        )
+    }
+
+    fn get_by_prefix(&self, component_name: &str) -> storage::Result<Vec<Arc<Signal>>> {
+        map_err!{{
+            let mut prefix: Vec<u8> = vec![2];
+            prefix.extend_from_slice(format!("{}.", component_name).as_bytes());
+
+            let mut signals = Vec::new();
+            for (key, value) in self.db.iterator(IteratorMode::From(&prefix, Direction::Forward)) {
+                if !key.starts_with(&prefix) {
+                    break;
+                }
+                if let Some(signal) = self._get_by_id(u64_from_slice(&value) as usize)? {
+                    signals.push(signal);
+                }
+            }
+            signals.sort_by_key(|s| s.id);
+            signals
+        }}
+    }
 }
 
 impl<'a> Constraints for RockConstraints {
@@ -217,27 +351,76 @@ impl<'a> Constraints for RockConstraints {
     }
     fn get(&self, i: usize) -> storage::Result<QEQ> {
         map_err!{{
-            let mut key: Vec<u8> = vec![1];
-            key.extend_from_slice(&u64_to_le(i as u64));
+            let key = key_of(1, i as u64);
             match self.db.get(&key)? {
                 None => Err(Error::NotFound(format!("Constraint at index {}", i))),
                 Some(v) => Ok(from_slice::<QEQ>(&v)?),
             }?
         }}
     }
-    fn get_debug(&self, _i: usize) -> Option<String> {
-        None
+    fn get_debug(&self, i: usize) -> Option<String> {
+        match self.db.get(&key_of(3, i as u64)) {
+            Ok(Some(v)) => Some(String::from_utf8_lossy(&v).to_string()),
+            _ => None,
+        }
     }
-    fn push(&mut self, qeq: QEQ, _debug: Option<String>) -> storage::Result<usize> {
+    fn push(&mut self, qeq: QEQ, debug: Option<String>) -> storage::Result<usize> {
         map_err!{{
             let index = inc_u64(&mut self.db, &[0])? - 1;
-            let mut key: Vec<u8> = vec![1];
-            key.extend_from_slice(&u64_to_le(index as u64));
+            let key = key_of(1, index);
             self.db
                 .put(&key.to_owned(), to_vec(&qeq).unwrap().as_slice())?;
+            if let Some(debug) = debug {
+                self.db.put(&key_of(3, index), debug.as_bytes())?;
+            }
             index as usize
         }}
     }
+    fn push_batch(&mut self, batch: Vec<(QEQ, Option<String>)>) -> storage::Result<usize> {
+        map_err!{{
+            let first = get_u64(&self.db, &[0])?.unwrap_or(0);
+            let mut write_batch = WriteBatch::default();
+            for (n, (qeq, debug)) in batch.iter().enumerate() {
+                let key = key_of(1, first + n as u64);
+                write_batch.put(&key, to_vec(qeq)?.as_slice())?;
+                if let Some(debug) = debug {
+                    write_batch.put(&key_of(3, first + n as u64), debug.as_bytes())?;
+                }
+            }
+            write_batch.put(&[0], &u64_to_le(first + batch.len() as u64))?;
+            self.db.write(write_batch)?;
+            first as usize
+        }}
+    }
+    fn iter_range(&self, range: Range<usize>) -> ConstraintIter {
+        let from = key_of(1, range.start as u64);
+        let end = range.end as u64;
+        Box::new(
+            self.db
+                .iterator(IteratorMode::From(&from, Direction::Forward))
+                .take_while(move |(key, _)| key[0] == 1 && u64_from_slice(&key[1..]) < end)
+                .map(|(_, value)| {
+                    map_err!{{
+                        from_slice::<QEQ>(&value)?
+                    }}
+                }),
+        )
+    }
+}
+
+/// path of a database inside the base directory
+fn db_path(base_path: &str, name: &str) -> String {
+    let mut full_path = PathBuf::new();
+    full_path.push(base_path);
+    full_path.push(name);
+    full_path.as_os_str().to_str().unwrap().to_string()
+}
+
+/// build a key as a one byte table tag followed by the index
+fn key_of(tag: u8, index: u64) -> Vec<u8> {
+    let mut key: Vec<u8> = vec![tag];
+    key.extend_from_slice(&u64_to_le(index));
+    key
 }
 
 /// increment an u64 counter
@@ -316,18 +499,18 @@ mod test {
 
         let tmpfile = tmpfile.as_os_str().to_str().expect("bad OS filename");
 
-        Rocks::new(tmpfile.to_string())
+        Rocks::new(tmpfile.to_string(), Field::Bn254)
     }
 
     #[test]
     fn test_rocks_signals() -> storage::Result<()> {
         let one = FS::one();
-        let two = &one + &one;
-        let three = &one + &two;
+        let two = FS::from(2);
+        let three = FS::from(3);
 
         let mut rocks = init();
         let mut signals = rocks.new_signals()?;
-        assert_eq!(0, signals.len()?);
+        assert_eq!(1, signals.len()?);
 
         signals.insert(
             "s1".to_string(),
@@ -340,7 +523,7 @@ mod test {
             Some(Value::from(two)),
         )?;
         signals.insert("s3".to_string(), SignalType::Internal, None)?;
-        assert_eq!(3, signals.len()?);
+        assert_eq!(4, signals.len()?);
 
         let s1 = &*signals.get_by_name("s1")?.unwrap();
         assert_eq!("Some(1)", format!("{:?}", s1.value));
@@ -353,7 +536,7 @@ mod test {
         assert_eq!(true, s3.value.is_none());
 
         signals.update(s3.id, Value::from(three))?;
-        assert_eq!(3, signals.len()?);
+        assert_eq!(4, signals.len()?);
 
         let s3 = &*signals.get_by_name("s3")?.unwrap();
         assert_eq!("Some(3)", format!("{:?}", s3.value));
@@ -361,21 +544,122 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_rocks_signals_parallel_reads() -> storage::Result<()> {
+        let mut rocks = init();
+        let mut signals = rocks.new_signals()?;
+        for n in 0..16u64 {
+            signals.insert(format!("s{}", n), SignalType::Internal, Some(Value::from(n)))?;
+        }
+
+        let signals = std::sync::Arc::new(signals);
+        let handles = (0..4)
+            .map(|t| {
+                let signals = signals.clone();
+                std::thread::spawn(move || {
+                    for n in (t..16).step_by(4) {
+                        let s = signals.get_by_id(n + 1).unwrap().unwrap();
+                        assert_eq!(format!("Some({})", n), format!("{:?}", s.value));
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_rocks_signals_iter() -> storage::Result<()> {
+        let mut rocks = init();
+        let mut signals = rocks.new_signals()?;
+        signals.insert("main.a".to_string(), SignalType::PublicInput, None)?;
+        signals.insert("main.c.b".to_string(), SignalType::Internal, None)?;
+        signals.insert("main.cc".to_string(), SignalType::Internal, None)?;
+        signals.insert("main.c.d".to_string(), SignalType::Internal, None)?;
+
+        let names = |v: Vec<Arc<Signal>>| {
+            v.iter()
+                .map(|s| s.full_name.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+
+        let all = signals.iter().collect::<storage::Result<Vec<_>>>()?;
+        assert_eq!("one,main.a,main.c.b,main.cc,main.c.d", names(all));
+
+        let range = signals.iter_range(2..4).collect::<storage::Result<Vec<_>>>()?;
+        assert_eq!("main.c.b,main.cc", names(range));
+
+        assert_eq!("main.c.b,main.c.d", names(signals.get_by_prefix("main.c")?));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rocks_constraints_batch() -> storage::Result<()> {
+        let one = QEQ::from(&FS::one());
+        let two = QEQ::from(&FS::from(2));
+
+        let mut rocks = init();
+        let mut constraints = rocks.new_constraints()?;
+        constraints.push(one.clone(), None)?;
+        let first = constraints.push_batch(vec![(two, None), (one, None)])?;
+
+        assert_eq!(1, first);
+        assert_eq!(3, constraints.len()?);
+
+        let all = constraints
+            .iter()
+            .map(|qeq| qeq.map(|qeq| format!("{:?}", qeq)))
+            .collect::<storage::Result<Vec<_>>>()?;
+        assert_eq!(vec!["[ ]*[ ]+[1s0]", "[ ]*[ ]+[2s0]", "[ ]*[ ]+[1s0]"], all);
+
+        Ok(())
+    }
+
     #[test]
     fn test_rocks_constraints() -> storage::Result<()> {
         let one = QEQ::from(&FS::one());
-        let two = QEQ::from(&(&FS::one() + &FS::one()));
+        let two = QEQ::from(&FS::from(2));
 
         let mut rocks = init();
         let mut constraints = rocks.new_constraints()?;
         assert_eq!(0, constraints.len()?);
 
         let c1 = constraints.push(one, None)?;
-        let c2 = constraints.push(two, None)?;
+        let c2 = constraints.push(two, Some("file:10".to_string()))?;
 
         assert_eq!(2, constraints.len()?);
         assert_eq!("[ ]*[ ]+[1s0]", format!("{:?}", constraints.get(c1)?));
         assert_eq!("[ ]*[ ]+[2s0]", format!("{:?}", constraints.get(c2)?));
+        assert_eq!(None, constraints.get_debug(c1));
+        assert_eq!(Some("file:10".to_string()), constraints.get_debug(c2));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rocks_reopen() -> storage::Result<()> {
+        let mut rocks = init();
+        let base_path = rocks.base_path.clone();
+        {
+            let mut signals = rocks.new_signals()?;
+            let mut constraints = rocks.new_constraints()?;
+            signals.insert("main.a".to_string(), SignalType::PublicInput, None)?;
+            constraints.push(QEQ::from(&FS::one()), Some("file:1".to_string()))?;
+            rocks.save_manifest("cafe", &signals, &constraints)?;
+        }
+
+        let (manifest, signals, constraints) = Rocks::open(&base_path)?;
+        assert_eq!("cafe", manifest.circuit_hash);
+        assert_eq!(Field::Bn254, signals.field());
+        assert_eq!(2, manifest.signals);
+        assert_eq!(1, manifest.constraints);
+        assert_eq!("main.a", signals.get_by_id(1)?.unwrap().full_name.to_string());
+        assert_eq!(Some("file:1".to_string()), constraints.get_debug(0));
 
         Ok(())
     }
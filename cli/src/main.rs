@@ -11,68 +11,170 @@ extern crate structopt;
 extern crate log;
 
 use circom2_compiler::{
+    algebra::{Field, FS},
     evaluator::{Evaluator,Mode},
+    format,
     tester
 };
 
 use std::time::{SystemTime, UNIX_EPOCH};
 use std::fs::File;
 use std::io::prelude::*;
+use std::io::{BufReader, BufWriter};
+use std::path::PathBuf;
+use std::sync::Arc;
 
 use circom2_compiler::storage::{Constraints, Signals};
-use circom2_compiler::storage::{Ram, StorageFactory};
+use circom2_compiler::storage::{build_witness, Mmap, Ram, RamConstraints, RamSignals, StorageFactory};
+use circom2_compiler::progress::{Phase, Progress};
 use circom2_compiler::tester::dump_error;
 use circom2_compiler::evaluator::{print_info};
 
-use circom2_bigsnark::Rocks;
+use circom2_bigsnark::{Manifest, RockConstraints, Rocks, RocksSignals};
+use circom2_prover::{groth16, plonk};
 
 
 const DEFAULT_CIRCUIT : &str = "circuit.circom";
 const DEFAULT_PROVING_KEY : &str = "proving.key";
 const DEFAULT_INPUT : &str = "input.json";
 const DEFAULT_PROOF : &str = "proof.json";
+const DEFAULT_PROOFS_DIR : &str = "proofs";
 const DEFAULT_SOLIDITY_VERIFIER : &str = "verifier.sol";
+const DEFAULT_VERIFICATION_KEY : &str = "verification_key.json";
+const DEFAULT_CEREMONY : &str = "ceremony.params";
+const DEFAULT_WITNESS : &str = "witness.wtns";
+const DEFAULT_WITNESS_JSON : &str = "witness.json";
+
+fn read_inputs(input_path: &str) -> Vec<(String,FS)> {
+    let mut inputs_json = String::new();
+    File::open(input_path)
+        .expect("cannot open inputs file")
+        .read_to_string(&mut inputs_json)
+        .expect("cannot read inputs file");
+
+    circom2_prover::groth16::flatten_json("main", &inputs_json)
+        .expect("cannot parse inputs file")
+}
+
+/// width of the progress bar, in characters
+const PROGRESS_BAR_WIDTH: usize = 30;
+
+/// draws the progress of the long running phases on stderr
+#[derive(Default)]
+struct ProgressBar {
+    total: Option<usize>,
+    drawn: bool,
+}
 
-fn generate_cuda<S:Signals,C:Constraints>(eval : &Evaluator<S,C>, cuda_file : Option<String>) {
-    if let Some(cuda_file) = cuda_file {
-        circom2_prover::cuda::export_r1cs(&cuda_file, &eval.constraints, &eval.signals).unwrap();
+impl Progress for ProgressBar {
+    fn start(&mut self, phase: Phase, total: Option<usize>) {
+        self.total = total;
+        info!("Running {}...", phase);
+    }
+
+    fn update(&mut self, phase: Phase, done: usize, rate: f64) {
+        let count = match self.total {
+            Some(total) if total > 0 => {
+                let filled = std::cmp::min(done * PROGRESS_BAR_WIDTH / total, PROGRESS_BAR_WIDTH);
+                format!("[{}{}] {}/{}", "#".repeat(filled), " ".repeat(PROGRESS_BAR_WIDTH - filled), done, total)
+            }
+            _ => done.to_string(),
+        };
+        eprint!("\r{} {} @ {:.0}/s", phase, count, rate);
+        self.drawn = true;
+    }
+
+    fn finish(&mut self, phase: Phase, done: usize) {
+        if self.drawn {
+            eprintln!();
+            self.drawn = false;
+        }
+        info!("Finished {} ({})", phase, done);
     }
 }
 
-fn compile_rocks(filename: &str, print_all: bool, cuda_file: Option<String>) {
-    let start = SystemTime::now();
-    let since_the_epoch = start.duration_since(UNIX_EPOCH).unwrap().as_secs();
-    let mut storage = Rocks::new(format!("db_{}_{}", filename, since_the_epoch));
+/// files to export once the circuit is compiled
+struct Exports {
+    cuda: Option<String>,
+    r1cs: Option<String>,
+    sym: Option<String>,
+}
+
+impl Exports {
+    fn generate<S:Signals,C:Constraints>(&self, signals: &S, constraints: &C) {
+        if let Some(cuda_file) = &self.cuda {
+            circom2_prover::cuda::export_r1cs(&cuda_file, constraints, signals)
+                .expect("cannot write cuda file");
+        }
+        if let Some(r1cs_file) = &self.r1cs {
+            let file = File::create(r1cs_file).expect("cannot create r1cs file");
+            format::write_r1cs(BufWriter::new(file), signals, constraints)
+                .expect("cannot write r1cs file");
+        }
+        if let Some(sym_file) = &self.sym {
+            let file = File::create(sym_file).expect("cannot create sym file");
+            format::write_sym(BufWriter::new(file), signals)
+                .expect("cannot write sym file");
+        }
+    }
+}
 
+fn compile<S,C,F>(storage: &mut F, filename: &str, print_all: bool, exports: &Exports) -> Option<Evaluator<'static,S,C>>
+where
+    S: Signals,
+    C: Constraints,
+    F: StorageFactory<S,C>,
+{
     let mut eval = Evaluator::new(
         Mode::GenConstraints,
         storage.new_signals().unwrap(),
         storage.new_constraints().unwrap(),
     );
+    eval.progress = Box::new(ProgressBar::default());
     if let Err(err) = eval.eval_file(".", &filename) {
         dump_error(&eval, &format!("{:?}", err));
+        None
     } else {
-        generate_cuda(&eval,cuda_file);
+        exports.generate(&eval.signals, &eval.constraints);
         print_info(&eval, print_all);
+        Some(eval)
     }
 }
 
-fn compile_ram(filename: &str, print_all: bool, cuda_file: Option<String>) {
-    let mut storage = Ram::default();
+fn compile_rocks(filename: &str, field: Field, print_all: bool, exports: &Exports) {
+    let db_path = storage_path("db", filename);
+    let mut storage = Rocks::new(db_path.clone(), field);
+    if let Some(eval) = compile(&mut storage, filename, print_all, exports) {
+        storage.save_manifest(&eval.circuit_hash(), &eval.signals, &eval.constraints)
+            .expect("cannot write database manifest");
+        info!("Compiled circuit stored in {}", db_path);
+    }
+}
 
-    let mut eval = Evaluator::new(
-        Mode::GenConstraints,
-        storage.new_signals().unwrap(),
-        storage.new_constraints().unwrap(),
-    );
-    if let Err(err) = eval.eval_file(".", &filename) {
-        dump_error(&eval, &format!("{:?}", err));
-    } else {
-        generate_cuda(&eval,cuda_file);
-        print_info(&eval, print_all);
+fn compile_mmap(filename: &str, field: Field, print_all: bool, exports: &Exports) {
+    let mmap_path = storage_path("mmap", filename);
+    let mut storage = Mmap::new(mmap_path.clone(), field);
+    if let Some(mut eval) = compile(&mut storage, filename, print_all, exports) {
+        eval.signals.finish().expect("cannot write the mapped signals");
+        info!("Compiled circuit stored in {}", mmap_path);
     }
 }
 
+/// reopen a circuit compiled with --storage rocks
+fn open_db(db: &str) -> (Manifest, RocksSignals, RockConstraints) {
+    let (manifest, signals, constraints) = Rocks::open(db)
+        .expect("cannot open compiled circuit database");
+    info!("Loaded circuit {} ({} signals, {} constraints)",
+        manifest.circuit_hash, manifest.signals, manifest.constraints);
+    (manifest, signals, constraints)
+}
+
+fn storage_path(prefix: &str, filename: &str) -> String {
+    let start = SystemTime::now();
+    let since_the_epoch = start.duration_since(UNIX_EPOCH).unwrap().as_secs();
+    format!("{}_{}_{}", prefix, filename, since_the_epoch)
+}
+
 use structopt::StructOpt;
 
 /// A StructOpt example
@@ -105,6 +207,14 @@ enum Command {
         /// Use RAM (default) or local storage
         use_ram: Option<bool>,
 
+        #[structopt(long = "storage")]
+        /// Storage backend: ram (default), rocks or mmap
+        storage: Option<String>,
+
+        #[structopt(long = "db")]
+        /// Do not compile, export a circuit already compiled with --storage rocks
+        db: Option<String>,
+
         #[structopt(long = "print")]
         /// Print constaints and signals
         print: Option<bool>,
@@ -112,6 +222,18 @@ enum Command {
         #[structopt(long = "cuda")]
         /// Export cuda format
         cuda: Option<String>,
+
+        #[structopt(long = "r1cs")]
+        /// Export iden3 binary r1cs format
+        r1cs: Option<String>,
+
+        #[structopt(long = "sym")]
+        /// Export signal id to name map
+        sym: Option<String>,
+
+        #[structopt(long = "curve")]
+        /// Curve whose scalar field is used: bn254 (default) or bls12-381
+        curve: Option<String>,
     },
     #[structopt(name = "setup")]
     /// Compile & generate trusted setup
@@ -127,6 +249,41 @@ enum Command {
         #[structopt(long = "verifier")]
         /// Solidity verifier
         verifier: Option<String>,
+
+        #[structopt(long = "vk")]
+        /// snarkjs verification key, defaults to verification_key.json
+        vk: Option<String>,
+
+        #[structopt(long = "db")]
+        /// Use a circuit already compiled with --storage rocks
+        db: Option<String>,
+
+        #[structopt(long = "r1cs")]
+        /// Use a circuit compiled elsewhere, as .r1cs or circom circuit.json
+        r1cs: Option<String>,
+
+        #[structopt(long = "sym")]
+        /// Signal names of the --r1cs circuit
+        sym: Option<String>,
+
+        #[structopt(long = "seed")]
+        /// INSECURE, only for tests: derive all randomness from this seed
+        seed: Option<u64>,
+
+        #[structopt(long = "ptau")]
+        /// Derive the keys from a powers of tau, in snarkjs .ptau or bellman response format
+        ptau: Option<String>,
+
+        #[structopt(long = "curve")]
+        /// Curve of the keys: bn254 (default) or bls12-381
+        curve: Option<String>,
+
+        #[structopt(long = "protocol")]
+        /// Proving system: groth16 (default) or plonk, plonk keys need --ptau
+        protocol: Option<String>,
+
+        #[structopt(subcommand)]
+        ceremony: Option<Ceremony>,
     },
     #[structopt(name = "prove")]
     /// Compile & generate trusted setup
@@ -146,6 +303,138 @@ enum Command {
         #[structopt(long = "proof")]
         /// Proof file, defaults to proof.json
         proof: Option<String>,
+
+        #[structopt(long = "witness")]
+        /// Prove from a witness file instead of the circuit and inputs
+        witness: Option<String>,
+
+        #[structopt(long = "db")]
+        /// Prove with the constraints of a circuit already compiled with --storage rocks
+        db: Option<String>,
+
+        #[structopt(long = "inputs-dir")]
+        /// Prove each .json inputs file of this folder, loading the proving key once
+        inputs_dir: Option<String>,
+
+        #[structopt(long = "out-dir")]
+        /// Folder for the proofs of --inputs-dir, defaults to proofs
+        out_dir: Option<String>,
+
+        #[structopt(long = "threads")]
+        /// Number of proofs of --inputs-dir created at the same time, defaults to 1
+        threads: Option<usize>,
+
+        #[structopt(long = "mmap")]
+        /// Map the groth16 proving key and stream it while proving, slower but for keys larger than the memory
+        mmap: bool,
+
+        #[structopt(long = "seed")]
+        /// INSECURE, only for tests: derive all randomness from this seed
+        seed: Option<u64>,
+
+        #[structopt(long = "curve")]
+        /// Curve of the keys: bn254 (default) or bls12-381
+        curve: Option<String>,
+
+        #[structopt(long = "protocol")]
+        /// Proving system of the keys: groth16 (default) or plonk
+        protocol: Option<String>,
+    },
+    #[structopt(name = "verify")]
+    /// Verify a proof, exits with an error if it is not valid
+    Verify {
+        #[structopt(long = "vk")]
+        /// Verification key, .json in snarkjs format or a proving key, defaults to verification_key.json
+        vk: Option<String>,
+
+        #[structopt(long = "proof")]
+        /// Proof file, defaults to proof.json
+        proof: Option<String>,
+
+        #[structopt(long = "public")]
+        /// Public inputs json array, defaults to the ones in the proof file
+        public: Option<String>,
+
+        #[structopt(long = "curve")]
+        /// Curve of the keys: bn254 (default) or bls12-381
+        curve: Option<String>,
+
+        #[structopt(long = "protocol")]
+        /// Proving system of the keys: groth16 (default) or plonk
+        protocol: Option<String>,
+    },
+    #[structopt(name = "calldata")]
+    /// Print the arguments of the verifier's verifyTx for a proof
+    Calldata {
+        #[structopt(long = "proof")]
+        /// Proof file, defaults to proof.json
+        proof: Option<String>,
+
+        #[structopt(long = "public")]
+        /// Public inputs json array, defaults to the ones in the proof file
+        public: Option<String>,
+
+        #[structopt(long = "abi")]
+        /// Print the ABI encoded arguments as hex instead
+        abi: bool,
+    },
+    #[structopt(name = "verifier")]
+    /// Generate the solidity verifier for a verification key
+    Verifier {
+        #[structopt(long = "vk")]
+        /// Verification key, .json in snarkjs format or a proving key, defaults to verification_key.json
+        vk: Option<String>,
+
+        #[structopt(long = "out")]
+        /// Solidity verifier, defaults to verifier.sol
+        out: Option<String>,
+
+        #[structopt(long = "pragma")]
+        /// Solidity version: 0.5 (default), 0.6 or 0.8
+        pragma: Option<String>,
+
+        #[structopt(long = "name")]
+        /// Contract name, defaults to Verifier
+        name: Option<String>,
+
+        #[structopt(long = "view")]
+        /// Make verifyTx a view function that does not emit events
+        view: bool,
+
+        #[structopt(long = "calldata")]
+        /// Make verifyTx external with calldata parameters
+        calldata: bool,
+
+        #[structopt(long = "batch")]
+        /// Also add verifyBatch, to verify many proofs at once
+        batch: bool,
+    },
+    #[structopt(name = "witness")]
+    /// Compute the witness for the given inputs
+    Witness {
+        #[structopt(long = "circuit")]
+        /// Circuit, defaults to circuit.circom
+        circuit: Option<String>,
+
+        #[structopt(long = "input")]
+        /// Inputs file, defaults to input.json
+        input: Option<String>,
+
+        #[structopt(long = "out")]
+        /// Witness file, defaults to witness.wtns or witness.json
+        out: Option<String>,
+
+        #[structopt(long = "json")]
+        /// Write a json array of decimal values ordered by signal id
+        json: bool,
+
+        #[structopt(long = "cuda")]
+        /// Also export the witness in cuda format, for the constraints of compile --cuda
+        cuda: Option<String>,
+
+        #[structopt(long = "curve")]
+        /// Curve whose scalar field is used: bn254 (default) or bls12-381
+        curve: Option<String>,
     },
     #[structopt(name = "test")]
     /// Run embeeded circuit tests
@@ -160,6 +449,160 @@ enum Command {
     },
 }
 
+#[derive(StructOpt, Debug)]
+enum Ceremony {
+    #[structopt(name = "init")]
+    /// Start a phase-2 ceremony for the circuit from a powers of tau file
+    Init {
+        #[structopt(long = "ptau")]
+        /// Powers of tau, in snarkjs .ptau or bellman response format
+        ptau: String,
+
+        #[structopt(long = "params")]
+        /// Ceremony parameters, defaults to ceremony.params
+        params: Option<String>,
+    },
+    #[structopt(name = "contribute")]
+    /// Add a contribution to the ceremony parameters
+    Contribute {
+        #[structopt(long = "params")]
+        /// Ceremony parameters, defaults to ceremony.params
+        params: Option<String>,
+
+        #[structopt(long = "out")]
+        /// Parameters with the contribution, defaults to overwrite --params
+        out: Option<String>,
+
+        #[structopt(long = "seed")]
+        /// INSECURE, only for tests: derive the contribution from this seed
+        seed: Option<u64>,
+    },
+    #[structopt(name = "verify-contributions")]
+    /// Check the ceremony parameters against the circuit and the powers of tau
+    VerifyContributions {
+        #[structopt(long = "ptau")]
+        /// Powers of tau, in snarkjs .ptau or bellman response format
+        ptau: String,
+
+        #[structopt(long = "params")]
+        /// Ceremony parameters, defaults to ceremony.params
+        params: Option<String>,
+    },
+    #[structopt(name = "finalize")]
+    /// Verify the ceremony and write the proving key and verifiers
+    Finalize {
+        #[structopt(long = "ptau")]
+        /// Powers of tau, in snarkjs .ptau or bellman response format
+        ptau: String,
+
+        #[structopt(long = "params")]
+        /// Ceremony parameters, defaults to ceremony.params
+        params: Option<String>,
+    },
+}
+
+/// circuit in `field` compiled from source or imported with --r1cs, with its `circuit_hash` if compiled
+fn load_circuit(circuit: &str, field: Field, r1cs: Option<String>, sym: Option<String>) -> (RamSignals, RamConstraints, Option<String>) {
+    if let Some(r1cs) = r1cs {
+        let input = BufReader::new(File::open(&r1cs).expect("cannot open circuit file"));
+        let imported = if r1cs.ends_with(".json") {
+            format::import_circuit_json(input, field)
+        } else {
+            let sym = sym.map(|sym| BufReader::new(File::open(sym).expect("cannot open sym file")));
+            format::import_r1cs(input, sym)
+        };
+        let (signals, constraints) = imported.expect("cannot import circuit");
+        (signals, constraints, None)
+    } else {
+        let exports = Exports { cuda: None, r1cs: None, sym: None };
+        match compile(&mut Ram::new(field), circuit, false, &exports) {
+            Some(eval) => {
+                let hash = eval.circuit_hash();
+                (eval.signals, eval.constraints, Some(hash))
+            }
+            None => std::process::exit(1),
+        }
+    }
+}
+
+fn read_phase1<S: Signals, C: Constraints>(ptau: &str, signals: &S, constraints: &C) -> groth16::Phase1 {
+    let size = groth16::circuit_size(signals, constraints).expect("cannot compute circuit size");
+    groth16::Phase1::open(ptau, size).expect("cannot read powers of tau")
+}
+
+fn read_ceremony(params: &str) -> groth16::MpcParameters {
+    let file = BufReader::new(File::open(params).expect("cannot open ceremony parameters"));
+    groth16::MpcParameters::read(file).expect("cannot read ceremony parameters")
+}
+
+fn write_ceremony(mpc: &groth16::MpcParameters, params: &str) {
+    let file = BufWriter::new(File::create(params).expect("cannot create ceremony parameters"));
+    mpc.write(file).expect("cannot write ceremony parameters");
+}
+
+/// parse --curve, the circuit is computed in its scalar field
+fn select_curve(curve: Option<String>) -> groth16::CurveName {
+    curve.map_or(groth16::CurveName::Bn254, |curve| curve.parse().expect("unknown curve"))
+}
+
+/// evaluate `$body` with `$E` as the pairing engine of the curve
+macro_rules! with_curve {
+    ($curve:expr, $E:ident => $body:expr) => {
+        match $curve {
+            groth16::CurveName::Bn254 => {
+                type $E = groth16::Bn256;
+                $body
+            }
+            groth16::CurveName::Bls12_381 => {
+                type $E = groth16::Bls12;
+                $body
+            }
+        }
+    };
+}
+
+/// parse --protocol, plonk keys are only available for bn254
+fn use_plonk(protocol: Option<String>, curve: groth16::CurveName) -> bool {
+    match protocol.as_ref().map(String::as_str) {
+        None | Some("groth16") => false,
+        Some("plonk") if curve == groth16::CurveName::Bn254 => true,
+        Some("plonk") => {
+            error!("plonk is only available for bn254");
+            std::process::exit(1);
+        }
+        Some(protocol) => {
+            error!("unknown protocol '{}', use groth16 or plonk", protocol);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// plonk keys from the powers of tau, or from insecure local powers with --seed
+fn plonk_setup<S: Signals, C: Constraints>(signals: &S, constraints: &C, source_hash: Option<&str>, pk: &str, vk: &str, ptau: Option<String>, seed: Option<u64>) {
+    let size = plonk::circuit_size(signals, constraints).expect("cannot compute circuit size");
+    let phase1 = match (ptau, seed) {
+        (Some(ptau), _) => groth16::Phase1::open(&ptau, size).expect("cannot read powers of tau"),
+        (None, Some(seed)) => groth16::Phase1::insecure(size, &mut groth16::new_rng(Some(seed))),
+        (None, None) => {
+            error!("plonk keys need a powers of tau, use --ptau");
+            std::process::exit(1);
+        }
+    };
+    plonk::setup(
+        &phase1,
+        signals,
+        constraints,
+        source_hash,
+        BufWriter::new(File::create(pk).expect("cannot create proving key file")),
+        File::create(vk).expect("cannot create verification key file"),
+        &mut ProgressBar::default(),
+    ).expect("unable to create keys");
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
 fn main() {
     stderrlog::new()
         .verbosity(2)
@@ -167,26 +610,180 @@ fn main() {
         .init()
         .unwrap();
 
-    circom2_prover::groth16::bellman_verbose(true);
-
     let cmd = Command::from_args();
     match cmd {
-        Command::Compile { circuit, use_ram, print, cuda } => {
+        Command::Compile { circuit, use_ram, storage, db, print, cuda, r1cs, sym, curve } => {
+            let field = select_curve(curve).field();
             let circuit = circuit.unwrap_or(DEFAULT_CIRCUIT.to_string());
-            let use_ram = use_ram.unwrap_or(true);
             let print_all = print.unwrap_or(false);
-            if use_ram {
-                compile_ram(&circuit,print_all,cuda)
-            } else {
-                compile_rocks(&circuit,print_all, cuda)
+            let exports = Exports { cuda, r1cs, sym };
+            if let Some(db) = db {
+                let (_, signals, constraints) = open_db(&db);
+                exports.generate(&signals, &constraints);
+                return;
+            }
+            let storage = storage.unwrap_or_else(|| {
+                if use_ram.unwrap_or(true) { "ram" } else { "rocks" }.to_string()
+            });
+            match storage.as_str() {
+                "ram" => { compile(&mut Ram::new(field), &circuit, print_all, &exports); }
+                "rocks" => compile_rocks(&circuit, field, print_all, &exports),
+                "mmap" => compile_mmap(&circuit, field, print_all, &exports),
+                _ => error!("unknown storage '{}', use ram, rocks or mmap", storage),
             }
         }
-        Command::Setup { circuit, pk, verifier } => {
+        Command::Setup { circuit, pk, verifier, vk, db, r1cs, sym, seed, ptau, curve, protocol, ceremony: Some(ceremony) } => {
+            if db.is_some() || seed.is_some() || ptau.is_some() {
+                warn!("--db, --seed and --ptau are not used in the ceremony");
+            }
+            if select_curve(curve) != groth16::CurveName::Bn254 {
+                error!("The ceremony is only available for bn254");
+                std::process::exit(1);
+            }
+            if use_plonk(protocol, groth16::CurveName::Bn254) {
+                error!("plonk keys do not need a ceremony, use --ptau instead");
+                std::process::exit(1);
+            }
+            let circuit = circuit.unwrap_or(DEFAULT_CIRCUIT.to_string());
+            match ceremony {
+                Ceremony::Init { ptau, params } => {
+                    let (signals, constraints, _) = load_circuit(&circuit, Field::Bn254, r1cs, sym);
+                    let phase1 = read_phase1(&ptau, &signals, &constraints);
+                    let mpc = groth16::MpcParameters::new(&phase1, &signals, &constraints)
+                        .expect("cannot create ceremony parameters");
+                    write_ceremony(&mpc, &params.unwrap_or(DEFAULT_CEREMONY.to_string()));
+                }
+                Ceremony::Contribute { params, out, seed } => {
+                    if seed.is_some() {
+                        warn!("Using a seeded contribution, it does not add any security");
+                    }
+                    let params = params.unwrap_or(DEFAULT_CEREMONY.to_string());
+                    let mut mpc = read_ceremony(&params);
+                    let hash = mpc.contribute(&mut groth16::new_rng(seed))
+                        .expect("cannot contribute");
+                    write_ceremony(&mpc, &out.unwrap_or(params));
+                    info!("Contribution #{} hash {}", mpc.contributions.len(), to_hex(&hash));
+                }
+                Ceremony::VerifyContributions { ptau, params } => {
+                    let (signals, constraints, _) = load_circuit(&circuit, Field::Bn254, r1cs, sym);
+                    let phase1 = read_phase1(&ptau, &signals, &constraints);
+                    let mpc = read_ceremony(&params.unwrap_or(DEFAULT_CEREMONY.to_string()));
+                    match mpc.verify(&phase1, &signals, &constraints) {
+                        Ok(hashes) => for (n, hash) in hashes.iter().enumerate() {
+                            info!("Contribution #{} hash {}", n + 1, to_hex(hash));
+                        },
+                        Err(err) => {
+                            error!("Ceremony is not valid: {:?}", err);
+                            std::process::exit(1);
+                        }
+                    }
+                }
+                Ceremony::Finalize { ptau, params } => {
+                    let (signals, constraints, source_hash) = load_circuit(&circuit, Field::Bn254, r1cs, sym);
+                    let phase1 = read_phase1(&ptau, &signals, &constraints);
+                    let mpc = read_ceremony(&params.unwrap_or(DEFAULT_CEREMONY.to_string()));
+                    mpc.verify(&phase1, &signals, &constraints).expect("ceremony is not valid");
+                    groth16::write_keys(
+                        &signals,
+                        &constraints,
+                        source_hash.as_ref().map(String::as_str),
+                        &mpc.params,
+                        File::create(pk.unwrap_or(DEFAULT_PROVING_KEY.to_string())).expect("cannot create proving key file"),
+                        Some(File::create(verifier.unwrap_or(DEFAULT_SOLIDITY_VERIFIER.to_string())).expect("cannot create verifier file")),
+                        File::create(vk.unwrap_or(DEFAULT_VERIFICATION_KEY.to_string())).expect("cannot create verification key file"),
+                    ).expect("cannot write keys");
+                }
+            }
+        }
+        Command::Setup { circuit, pk, verifier, vk, db, r1cs, sym, seed, ptau, curve, protocol, ceremony: None } => {
+            if seed.is_some() {
+                warn!("Using a seeded setup, the keys are not secure");
+            }
+            let curve = select_curve(curve);
             let circuit = circuit.unwrap_or(DEFAULT_CIRCUIT.to_string());
             let pk = pk.unwrap_or(DEFAULT_PROVING_KEY.to_string());
-            let verifier = verifier.unwrap_or(DEFAULT_SOLIDITY_VERIFIER.to_string());
-            circom2_prover::groth16::setup_ram(&circuit,&pk,&verifier)
-                .expect("unable to create proof");
+            if use_plonk(protocol, curve) {
+                if verifier.is_some() {
+                    warn!("--verifier is not used with plonk");
+                }
+                let vk = vk.unwrap_or(DEFAULT_VERIFICATION_KEY.to_string());
+                if let Some(db) = db {
+                    let (manifest, signals, constraints) = open_db(&db);
+                    plonk_setup(&signals, &constraints, Some(&manifest.circuit_hash), &pk, &vk, ptau, seed);
+                } else {
+                    let (signals, constraints, source_hash) = load_circuit(&circuit, curve.field(), r1cs, sym);
+                    plonk_setup(&signals, &constraints, source_hash.as_ref().map(String::as_str), &pk, &vk, ptau, seed);
+                }
+                return;
+            }
+            // only bn254 has a solidity verifier by default, other curves fail if asked for one
+            let verifier = match curve {
+                groth16::CurveName::Bn254 => Some(verifier.unwrap_or(DEFAULT_SOLIDITY_VERIFIER.to_string())),
+                _ => verifier,
+            };
+            let vk = vk.unwrap_or(DEFAULT_VERIFICATION_KEY.to_string());
+            let create = |pk: &str, verifier: Option<&str>, vk: &str| (
+                File::create(pk).expect("cannot create proving key file"),
+                verifier.map(|verifier| File::create(verifier).expect("cannot create verifier file")),
+                File::create(vk).expect("cannot create verification key file"),
+            );
+            if let Some(ptau) = ptau {
+                if curve != groth16::CurveName::Bn254 {
+                    error!("--ptau is only available for bn254");
+                    std::process::exit(1);
+                }
+                let (pk, verifier, vk) = create(&pk, verifier.as_ref().map(String::as_str), &vk);
+                let mut rng = groth16::new_rng(seed);
+                if let Some(db) = db {
+                    let (manifest, signals, constraints) = open_db(&db);
+                    let phase1 = read_phase1(&ptau, &signals, &constraints);
+                    groth16::setup_phase1(&phase1, &signals, &constraints, Some(&manifest.circuit_hash), pk, verifier, vk, &mut rng, &mut ProgressBar::default())
+                } else {
+                    let (signals, constraints, source_hash) = load_circuit(&circuit, curve.field(), r1cs, sym);
+                    let phase1 = read_phase1(&ptau, &signals, &constraints);
+                    groth16::setup_phase1(&phase1, &signals, &constraints, source_hash.as_ref().map(String::as_str), pk, verifier, vk, &mut rng, &mut ProgressBar::default())
+                }.expect("unable to create proof");
+            } else if let Some(db) = db {
+                let (manifest, signals, constraints) = open_db(&db);
+                let (pk, verifier, vk) = create(&pk, verifier.as_ref().map(String::as_str), &vk);
+                with_curve!(curve, E => groth16::setup::<E, _, _, _, _, _, _>(&signals, &constraints, Some(&manifest.circuit_hash), pk, verifier, vk, &mut groth16::new_rng(seed), &mut ProgressBar::default()))
+                    .expect("unable to create proof");
+            } else if r1cs.is_some() {
+                let (signals, constraints, source_hash) = load_circuit(&circuit, curve.field(), r1cs, sym);
+                let (pk, verifier, vk) = create(&pk, verifier.as_ref().map(String::as_str), &vk);
+                let source_hash = source_hash.as_ref().map(String::as_str);
+                with_curve!(curve, E => groth16::setup::<E, _, _, _, _, _, _>(&signals, &constraints, source_hash, pk, verifier, vk, &mut groth16::new_rng(seed), &mut ProgressBar::default()))
+                    .expect("unable to create proof");
+            } else {
+                let verifier = verifier.as_ref().map(String::as_str);
+                with_curve!(curve, E => groth16::setup_ram::<E>(&circuit, &pk, verifier, &vk, seed, &mut ProgressBar::default()))
+                    .expect("unable to create proof");
+            }
+        }
+        Command::Witness { circuit, input, out, json, cuda, curve } => {
+            let field = select_curve(curve).field();
+            let circuit_path = circuit.unwrap_or(DEFAULT_CIRCUIT.to_string());
+            let input_path = input.unwrap_or(DEFAULT_INPUT.to_string());
+            let default_out = if json { DEFAULT_WITNESS_JSON } else { DEFAULT_WITNESS };
+            let out_path = out.unwrap_or(default_out.to_string());
+
+            let inputs = read_inputs(&input_path);
+            let (signals, _) = circom2_prover::groth16::witness_ram(&circuit_path, field, inputs, &mut ProgressBar::default())
+                .expect("cannot generate witness");
+            let witness = build_witness(&signals)
+                .expect("cannot build witness");
+            if let Some(cuda_file) = &cuda {
+                circom2_prover::cuda::export_witness(cuda_file, &signals)
+                    .expect("cannot write cuda witness file");
+            }
+
+            let out = BufWriter::new(File::create(out_path).expect("cannot create witness file"));
+            if json {
+                format::write_witness_json(out, &witness)
+            } else {
+                let wires = format::WireMap::new(&signals).expect("cannot map signals to wires");
+                format::write_wtns(out, field, &wires.to_wires(&witness))
+            }.expect("cannot write witness file");
         }
         Command::Test { circuit, debug } => {
             let circuit = circuit.unwrap_or(DEFAULT_CIRCUIT.to_string());
@@ -198,28 +795,191 @@ fn main() {
                 _ => {}
             }
         }
-        Command::Prove { circuit, pk, input, proof } => {
+        Command::Prove { circuit, pk, input, proof, witness, db, inputs_dir: Some(inputs_dir), out_dir, threads, mmap, seed, curve, protocol } => {
+            if seed.is_some() {
+                warn!("Using a seeded prover, the proofs do not hide the witness");
+            }
+            if input.is_some() || proof.is_some() || witness.is_some() || db.is_some() {
+                warn!("--input, --proof, --witness and --db are not used with --inputs-dir");
+            }
+            let curve = select_curve(curve);
+            if use_plonk(protocol, curve) {
+                error!("--inputs-dir is only available for groth16");
+                std::process::exit(1);
+            }
+            let circuit_path = circuit.unwrap_or(DEFAULT_CIRCUIT.to_string());
+            let pk_path = pk.unwrap_or(DEFAULT_PROVING_KEY.to_string());
+            let out_dir = PathBuf::from(out_dir.unwrap_or(DEFAULT_PROOFS_DIR.to_string()));
+            std::fs::create_dir_all(&out_dir).expect("cannot create proofs folder");
+
+            let mut paths = std::fs::read_dir(&inputs_dir)
+                .expect("cannot read inputs folder")
+                .map(|entry| entry.expect("cannot read inputs folder").path())
+                .filter(|path| path.extension().map_or(false, |ext| ext == "json"))
+                .collect::<Vec<_>>();
+            paths.sort();
+            let inputs = paths.iter()
+                .map(|path| std::fs::read_to_string(path).expect("cannot read inputs file"))
+                .collect::<Vec<_>>();
+
+            let mut failed = 0;
+            let done = |n: usize, proof: groth16::Result<String>| {
+                let name = paths[n].file_name().expect("bad inputs file name");
+                match proof {
+                    Ok(proof) => {
+                        File::create(out_dir.join(name))
+                            .expect("cannot create proof file")
+                            .write_all(proof.as_bytes())
+                            .expect("cannot write proof file");
+                        info!("Proof of {:?} generated", name);
+                    }
+                    Err(err) => {
+                        error!("Cannot generate proof of {:?}: {:?}", name, err);
+                        failed += 1;
+                    }
+                }
+            };
+            with_curve!(curve, E => {
+                let prover = if mmap {
+                    groth16::Prover::<E>::open_mapped(&circuit_path, &pk_path, &mut ProgressBar::default())
+                } else {
+                    groth16::Prover::<E>::open(&circuit_path, &pk_path, &mut ProgressBar::default())
+                }.expect("cannot load proving key");
+                Arc::new(prover).prove_stream(inputs.into_iter(), threads.unwrap_or(1), seed, done)
+            }).expect("cannot generate proofs");
+            if failed > 0 {
+                error!("{} of {} proofs failed", failed, paths.len());
+                std::process::exit(1);
+            }
+        }
+        Command::Prove { circuit, pk, input, proof, witness, db, inputs_dir: None, out_dir: _, threads: _, mmap, seed, curve, protocol } => {
+            if seed.is_some() {
+                warn!("Using a seeded prover, the proof does not hide the witness");
+            }
+            let curve = select_curve(curve);
             let circuit_path = circuit.unwrap_or(DEFAULT_CIRCUIT.to_string());
             let pk_path = pk.unwrap_or(DEFAULT_PROVING_KEY.to_string());
             let input_path = input.unwrap_or(DEFAULT_INPUT.to_string());
             let proof_path = proof.unwrap_or(DEFAULT_PROOF.to_string());
 
-            let mut inputs_json = String::new();
-            File::open(input_path)
-                .expect("cannot open inputs file")
-                .read_to_string(&mut inputs_json)
-                .expect("cannot read inputs file");
-            
-            let inputs = circom2_prover::groth16::flatten_json("main", &inputs_json)
-                .expect("cannot parse inputs file");
+            let plonk = use_plonk(protocol, curve);
+            if db.is_some() && (plonk || witness.is_some()) {
+                warn!("--db is only used to prove groth16 keys from the circuit and inputs");
+            }
+            if mmap && (plonk || db.is_some()) {
+                warn!("--mmap is only used to prove groth16 keys, without --db");
+            }
 
-            let proof = circom2_prover::groth16::prove_ram(&circuit_path,&pk_path,inputs)
-                .expect("cannot generate proof");
+            let proof = if let Some(witness_path) = witness {
+                let witness = format::read_wtns(BufReader::new(
+                    File::open(witness_path).expect("cannot open witness file")
+                ), curve.field()).expect("cannot read witness file");
+                let mut proof = Vec::new();
+                if plonk {
+                    let pk = BufReader::new(File::open(pk_path).expect("cannot open proving key file"));
+                    plonk::prove_witness(&witness, pk, &mut proof, &mut groth16::new_rng(seed), &mut ProgressBar::default())
+                } else if mmap {
+                    // the key is mapped, its points are decoded in chunks while proving
+                    with_curve!(curve, E => groth16::MappedKey::<E>::open(&pk_path, &mut ProgressBar::default())
+                        .and_then(|key| groth16::prove_witness_mapped(&witness, &key, &mut proof, &mut groth16::new_rng(seed), &mut ProgressBar::default())))
+                } else {
+                    let pk = BufReader::new(File::open(pk_path).expect("cannot open proving key file"));
+                    with_curve!(curve, E => groth16::prove_witness::<E, _, _, _>(&witness, pk, &mut proof, &mut groth16::new_rng(seed), &mut ProgressBar::default()))
+                }.expect("cannot generate proof");
+                String::from_utf8_lossy(&proof).to_string()
+            } else if plonk {
+                let (signals, source_hash) = groth16::witness_ram(&circuit_path, curve.field(), read_inputs(&input_path), &mut ProgressBar::default())
+                    .expect("cannot generate witness");
+                let pk = BufReader::new(File::open(pk_path).expect("cannot open proving key file"));
+                let mut proof = Vec::new();
+                plonk::generate_verified_proof(signals, Some(&source_hash), pk, &mut proof, &mut groth16::new_rng(seed), &mut ProgressBar::default())
+                    .expect("cannot generate proof");
+                String::from_utf8_lossy(&proof).to_string()
+            } else if let Some(db) = db {
+                let (_, _, constraints) = open_db(&db);
+                let (signals, source_hash) = groth16::witness_ram(&circuit_path, curve.field(), read_inputs(&input_path), &mut ProgressBar::default())
+                    .expect("cannot generate witness");
+                let pk = BufReader::new(File::open(pk_path).expect("cannot open proving key file"));
+                let mut proof = Vec::new();
+                with_curve!(curve, E => groth16::generate_verified_proof_with::<E, _, _, _, _, _>(signals, Some(&source_hash), &constraints, pk, &mut proof, &mut groth16::new_rng(seed), &mut ProgressBar::default()))
+                    .expect("cannot generate proof");
+                String::from_utf8_lossy(&proof).to_string()
+            } else if mmap {
+                let inputs = read_inputs(&input_path);
+                let mut progress = ProgressBar::default();
+                with_curve!(curve, E => groth16::Prover::<E>::open_mapped(&circuit_path, &pk_path, &mut progress)
+                    .and_then(|prover| prover.prove(inputs, &mut groth16::new_rng(seed), &mut progress)))
+                    .expect("cannot generate proof")
+            } else {
+                let inputs = read_inputs(&input_path);
+                with_curve!(curve, E => groth16::prove_ram::<E>(&circuit_path, &pk_path, inputs, seed, &mut ProgressBar::default()))
+                    .expect("cannot generate proof")
+            };
 
             File::create(proof_path)
                 .expect("cannot create proof file")
                 .write_all(proof.as_bytes())
                 .expect("cannot write proof file");
         }
+        Command::Verify { vk, proof, public, curve, protocol } => {
+            let curve = select_curve(curve);
+            let vk_path = vk.unwrap_or(DEFAULT_VERIFICATION_KEY.to_string());
+            let proof_path = proof.unwrap_or(DEFAULT_PROOF.to_string());
+            let public_inputs = public.map(|path| {
+                read_inputs(&path).into_iter().map(|(_, v)| v).collect::<Vec<_>>()
+            });
+
+            let result = if use_plonk(protocol, curve) {
+                plonk::verify_files(&vk_path, &proof_path, public_inputs)
+            } else {
+                with_curve!(curve, E => groth16::verify_files::<E>(&vk_path, &proof_path, public_inputs))
+            };
+            match result {
+                Ok(true) => info!("Proof is valid"),
+                Ok(false) => {
+                    error!("Proof is not valid");
+                    std::process::exit(1);
+                }
+                Err(err) => {
+                    error!("Cannot verify proof: {:?}", err);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Command::Calldata { proof, public, abi } => {
+            let proof_path = proof.unwrap_or(DEFAULT_PROOF.to_string());
+            let (proof_inputs, proof) = groth16::read_input_and_proof(BufReader::new(
+                File::open(proof_path).expect("cannot open proof file")
+            )).expect("cannot read proof file");
+            let public_inputs = public.map(|path| {
+                read_inputs(&path).into_iter().map(|(_, v)| v).collect::<Vec<_>>()
+            });
+
+            let calldata = groth16::Calldata::new(&proof, &public_inputs.unwrap_or(proof_inputs));
+            if abi {
+                println!("{}", calldata.to_abi_hex());
+            } else {
+                println!("{}", calldata.to_args());
+            }
+        }
+        Command::Verifier { vk, out, pragma, name, view, calldata, batch } => {
+            let vk_path = vk.unwrap_or(DEFAULT_VERIFICATION_KEY.to_string());
+            let out_path = out.unwrap_or(DEFAULT_SOLIDITY_VERIFIER.to_string());
+            let mut options = groth16::SolidityOptions::default();
+            if let Some(pragma) = pragma {
+                options.pragma = pragma.parse().expect("unknown solidity version");
+            }
+            if let Some(name) = name {
+                options.contract_name = name;
+            }
+            options.view = view;
+            options.calldata = calldata;
+            options.batch = batch;
+
+            let vk = groth16::read_vk_file::<groth16::Bn256>(&vk_path).expect("cannot read verification key");
+            let mut out = BufWriter::new(File::create(out_path).expect("cannot create verifier file"));
+            groth16::generate_solidity(&vk, vk.ic.len() - 1, &options, &mut out)
+                .expect("cannot generate verifier");
+        }
     }
 }
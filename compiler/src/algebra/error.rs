@@ -1,7 +1,10 @@
+use super::types::SignalId;
+
 #[derive(Debug, Clone)]
 pub enum Error {
     InvalidOperation(String),
     InvalidFormat(String),
+    UnknownSignal(SignalId),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -5,7 +5,7 @@ use num_traits::cast::ToPrimitive;
 use num_traits::identities::{One, Zero};
 use std::cmp::Ordering;
 use std::fmt;
-use std::ops::{Add, AddAssign, BitAnd, BitOr, BitXor, Div, Mul, Neg, Rem, Shl, Shr};
+use std::ops::{BitAnd, Rem};
 
 use super::error::{Error, Result};
 use super::traits::AlgZero;
@@ -27,19 +27,67 @@ lazy_static! {
         10
     )
     .unwrap();
+    pub static ref BLS12_381_FIELD_UINT: BigUint = BigUint::parse_bytes(
+        b"52435875175126190479447740508185965837690552500527637822603658699938581184513",
+        10
+    )
+    .unwrap();
+    static ref BLS12_381_FIELD_UINT_NEG: BigUint = &*BLS12_381_FIELD_UINT >> 1;
+    static ref BLS12_381_FIELD_INT: BigInt = BigInt::from(BLS12_381_FIELD_UINT.clone());
     pub static ref ONE: BigUint = BigUint::parse_bytes(b"1", 10).unwrap();
     pub static ref ZERO: BigUint = BigUint::parse_bytes(b"0", 10).unwrap();
 }
 
+/// prime field of the signals, it has to be the scalar field of the curve
+///   used to prove the circuit
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Field {
+    Bn254,
+    Bls12_381,
+}
+
+impl Field {
+    /// the field with `prime` as modulus
+    pub fn from_prime(prime: &BigUint) -> Option<Field> {
+        [Field::Bn254, Field::Bls12_381]
+            .iter()
+            .cloned()
+            .find(|field| field.prime() == prime)
+    }
+
+    pub fn prime(self) -> &'static BigUint {
+        match self {
+            Field::Bn254 => &BABYJUB_FIELD_UINT,
+            Field::Bls12_381 => &BLS12_381_FIELD_UINT,
+        }
+    }
+
+    fn prime_int(self) -> &'static BigInt {
+        match self {
+            Field::Bn254 => &BABYJUB_FIELD_INT,
+            Field::Bls12_381 => &BLS12_381_FIELD_INT,
+        }
+    }
+
+    /// values over this one are shown as negative
+    fn half(self) -> &'static BigUint {
+        match self {
+            Field::Bn254 => &BABYJUB_FIELD_UINT_NEG,
+            Field::Bls12_381 => &BLS12_381_FIELD_UINT_NEG,
+        }
+    }
+}
 
 // Field Scalar  ------------------------------------------------
 
 impl FS {
-    fn field() -> &'static BigUint {
-        &BABYJUB_FIELD_UINT as &BigUint
+    /// `n` reduced into `field`
+    pub fn reduce(n: &BigUint, field: Field) -> Self {
+        FS(n % field.prime())
     }
-    fn field_int() -> &'static BigInt {
-        &BABYJUB_FIELD_INT as &BigInt
+    /// `n` reduced into `field`, negative values are counted back from the prime
+    pub fn from_bigint(n: &BigInt, field: Field) -> Self {
+        FS(normalize(n, field.prime_int()).to_biguint().unwrap())
     }
     pub fn parse(expr: &str) -> Result<Self> {
         if expr.starts_with("0x") {
@@ -66,46 +114,77 @@ impl FS {
     pub fn is_one(&self) -> bool {
         self.0.cmp(&ONE) == Ordering::Equal
     }
-    pub fn is_neg(&self) -> bool {
-        self.0.cmp(&BABYJUB_FIELD_UINT_NEG as &BigUint) == Ordering::Greater
+    pub fn is_neg(&self, field: Field) -> bool {
+        self.0.cmp(field.half()) == Ordering::Greater
     }
-    pub fn format(&self, plus_sign_at_start: bool) -> String {
-        if self.is_neg() {
-            format!("-{}", (-self).0.to_str_radix(10))
+    /// decimal value, the ones over half of `field` are shown as negative
+    pub fn format(&self, plus_sign_at_start: bool, field: Field) -> String {
+        if self.is_neg(field) {
+            format!("-{}", self.neg(field).0.to_str_radix(10))
         } else if plus_sign_at_start {
             format!("+{}", self.0.to_str_radix(10))
         } else {
             self.0.to_str_radix(10)
         }
     }
-    pub fn shl(&self, rhs: &FS) -> Result<FS> {
-        if let Some(self_u64) = self.0.to_u64() {
-            if let Some(rhs_u64) = rhs.0.to_u64() {
-                let v = BigUint::from_u64(self_u64 << rhs_u64).unwrap();
-                return Ok(FS(v));
-            }
+
+    pub fn neg(&self, field: Field) -> FS {
+        FS::reduce(&(field.prime() - &self.0), field)
+    }
+    pub fn add(&self, rhs: &FS, field: Field) -> FS {
+        FS::reduce(&(&self.0 + &rhs.0), field)
+    }
+    pub fn sub(&self, rhs: &FS, field: Field) -> FS {
+        self.add(&rhs.neg(field), field)
+    }
+    pub fn mul(&self, rhs: &FS, field: Field) -> FS {
+        FS::reduce(&(&self.0 * &rhs.0), field)
+    }
+    pub fn div(&self, rhs: &FS, field: Field) -> Result<FS> {
+        let GcdResult { gcd, c1: c, .. } = extended_gcd(
+            BigInt::from_biguint(num_bigint::Sign::Plus, rhs.0.clone()),
+            field.prime_int().clone(),
+        );
+
+        if gcd == BigInt::one() {
+            let rhs_inv = normalize(&c, field.prime_int()).to_biguint().unwrap();
+            Ok(FS::reduce(&(&self.0 * rhs_inv), field))
+        } else {
+            Err(Error::InvalidOperation(format!(
+                "Cannot find inv gcd={}",
+                gcd.to_str_radix(10)
+            )))
+        }
+    }
+    pub fn pow(&self, rhs: &FS, field: Field) -> FS {
+        FS(self.0.modpow(&rhs.0, field.prime()))
+    }
+    pub fn intdiv(&self, rhs: &FS) -> FS {
+        FS(&self.0 / &rhs.0)
+    }
+    pub fn shl(&self, rhs: &FS, field: Field) -> Result<FS> {
+        if let Some(rhs_usize) = rhs.0.to_usize() {
+            Ok(FS::reduce(&(&self.0 << rhs_usize), field))
+        } else {
+            Err(Error::InvalidOperation(
+                "Only can shl on 64 bit values".to_string(),
+            ))
         }
-        Err(Error::InvalidOperation(
-            "Only can shl on 64 bit values".to_string(),
-        ))
     }
     pub fn shr(&self, rhs: &FS) -> Result<FS> {
-        if let Some(self_u64) = self.0.to_u64() {
-            if let Some(rhs_u64) = rhs.0.to_u64() {
-                let v = BigUint::from_u64(self_u64 >> rhs_u64).unwrap();
-                return Ok(FS(v));
-            }
+        if let Some(rhs_usize) = rhs.0.to_usize() {
+            Ok(FS(&self.0 >> rhs_usize))
+        } else {
+            Err(Error::InvalidOperation(
+                "Only can shr on 64 bit values".to_string(),
+            ))
         }
-        Err(Error::InvalidOperation(
-            "Only can shr on 64 bit values".to_string(),
-        ))
     }
-    pub fn pow(&self, rhs: &FS) -> FS {
-        FS::from(self.0.modpow(&rhs.0, FS::field()))
+    pub fn bitor(&self, rhs: &FS, field: Field) -> FS {
+        FS::reduce(&(&self.0 | &rhs.0), field)
     }
-
-    pub fn intdiv(&self, rhs: &FS) -> FS {
-        FS::from(&self.0 / &rhs.0)
+    pub fn bitxor(&self, rhs: &FS, field: Field) -> FS {
+        FS::reduce(&(&self.0 ^ &rhs.0), field)
     }
 }
 
@@ -129,35 +208,10 @@ impl Ord for FS {
     }
 }
 
-impl From<&BigUint> for FS {
-    fn from(n: &BigUint) -> Self {
-        FS(n % FS::field())
-    }
-}
-
-impl From<BigUint> for FS {
-    fn from(n: BigUint) -> Self {
-        FS::from(&n)
-    }
-}
-
+/// u64 values are below the prime of every field
 impl From<u64> for FS {
     fn from(n: u64) -> Self {
-        FS::from(BigUint::from_u64(n).unwrap())
-    }
-}
-
-
-impl From<BigInt> for FS {
-    fn from(n: BigInt) -> Self {
-        FS::from(&n)
-    }
-}
-
-impl From<&BigInt> for FS {
-    fn from(n: &BigInt) -> Self {
-        let v = n % (&BABYJUB_FIELD_INT as &BigInt);
-        FS(v.to_biguint().unwrap())
+        FS(BigUint::from_u64(n).unwrap())
     }
 }
 
@@ -170,67 +224,10 @@ impl AlgZero for FS {
     }
 }
 
+/// without a field the value is shown unsigned, see `format`
 impl fmt::Debug for FS {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> std::result::Result<(), fmt::Error> {
-        write!(fmt, "{}", self.format(false))
-    }
-}
-
-// -&FS -> FS
-impl<'a> Neg for &'a FS {
-    type Output = FS;
-
-    fn neg(self) -> FS {
-        FS::from(FS::field() - &self.0)
-    }
-}
-
-// &FS + &FS -> FS
-impl<'a> Add<&'a FS> for &'a FS {
-    type Output = FS;
-
-    fn add(self, rhs: &'a FS) -> FS {
-        FS::from(&(self.0) + &rhs.0)
-    }
-}
-
-// &FS * &FS -> FS
-impl<'a> Mul<&'a FS> for &'a FS {
-    type Output = FS;
-
-    fn mul(self, rhs: &'a FS) -> FS {
-        FS::from(&(self.0) * &rhs.0)
-    }
-}
-
-// &FS / &FS -> FS
-impl<'a> Div<&'a FS> for &'a FS {
-    type Output = Result<FS>;
-
-    #[allow(clippy::suspicious_arithmetic_impl)]
-    fn div(self, rhs: &'a FS) -> Result<FS> {
-        let GcdResult { gcd, c1: c, .. } = extended_gcd(
-            BigInt::from_biguint(num_bigint::Sign::Plus, rhs.0.clone()),
-            FS::field_int().clone(),
-        );
-
-        if gcd == BigInt::one() {
-            let rhs_inv = normalize(&c, FS::field_int()).to_biguint().unwrap();
-            Ok(FS::from(&self.0 * rhs_inv))
-        } else {
-            Err(Error::InvalidOperation(format!(
-                "Cannot find inv gcd={}",
-                gcd.to_str_radix(10)
-            )))
-        }
-    }
-}
-
-// FS += &FSs
-impl<'a> AddAssign<&'a FS> for FS {
-    // addLCNum
-    fn add_assign(&mut self, rhs: &'a FS) {
-        *self = (self as &Self) + rhs;
+        write!(fmt, "{}", self.0.to_str_radix(10))
     }
 }
 
@@ -246,34 +243,6 @@ impl<'a> Rem<&'a FS> for &'a FS {
     }
 }
 
-// &FS << &FS
-impl<'a> Shl<&'a FS> for &'a FS {
-    type Output = Result<FS>;
-    fn shl(self, rhs: &'a FS) -> Result<FS> {
-        if let Some(rhs_usize) = rhs.0.to_usize() {
-            return Ok(FS::from(&self.0 << rhs_usize));
-        } else {
-            Err(Error::InvalidOperation(
-                "Only can shl on 64 bit values".to_string(),
-            ))
-        }
-    }
-}
-
-// &FS >> &FS
-impl<'a> Shr<&'a FS> for &'a FS {
-    type Output = Result<FS>;
-    fn shr(self, rhs: &'a FS) -> Result<FS> {
-        if let Some(rhs_usize) = rhs.0.to_usize() {
-            return Ok(FS::from(&self.0 >> rhs_usize));
-        } else {
-            Err(Error::InvalidOperation(
-                "Only can shr on 64 bit values".to_string(),
-            ))
-        }
-    }
-}
-
 // &FS & &FS
 impl<'a> BitAnd<&'a FS> for &'a FS {
     type Output = FS;
@@ -282,22 +251,6 @@ impl<'a> BitAnd<&'a FS> for &'a FS {
     }
 }
 
-// &FS | &FS
-impl<'a> BitOr<&'a FS> for &'a FS {
-    type Output = FS;
-    fn bitor(self, rhs: &'a FS) -> FS {
-        FS::from(&self.0 | &rhs.0)
-    }
-}
-
-// &FS ^ &FS
-impl<'a> BitXor<&'a FS> for &'a FS {
-    type Output = FS;
-    fn bitxor(self, rhs: &'a FS) -> FS {
-        FS::from(&self.0 ^ &rhs.0)
-    }
-}
-
 // helpers --------------------------------------------------------------------
 
 pub struct GcdResult {
@@ -351,18 +304,19 @@ pub fn normalize(a: &BigInt, n: &BigInt) -> BigInt {
 mod test {
     use super::super::Result;
     use super::*;
-    use num_bigint::ToBigUint;
+
+    const BN: Field = Field::Bn254;
 
     fn u32_to_fs(n: u32) -> FS {
-        FS::from(n.to_biguint().unwrap())
+        FS::from(n as u64)
     }
 
     #[test]
     fn test_fs_fs_add_mul() {
         let one = FS::one();
-        let two = &one + &one;
-        let three = &(&one + &one) + &one;
-        let six = &three * &two;
+        let two = one.add(&one, BN);
+        let three = one.add(&one, BN).add(&one, BN);
+        let six = three.mul(&two, BN);
 
         assert_eq!("1", format!("{:?}", one));
         assert_eq!("2", format!("{:?}", two));
@@ -373,19 +327,21 @@ mod test {
     #[test]
     fn test_fs_neg() {
         let one = &FS::one();
-        let minus_one = &-one;
-        assert_eq!("-1", format!("{:?}", minus_one));
-        let minus_two = &(minus_one + minus_one);
-        assert_eq!("2", format!("{:?}", -minus_two));
+        let minus_one = &one.neg(BN);
+        assert_eq!("-1", minus_one.format(false, BN));
+        assert_eq!("+2", one.add(one, BN).format(true, BN));
+        let minus_two = &minus_one.add(minus_one, BN);
+        assert_eq!("2", format!("{:?}", minus_two.neg(BN)));
+        assert_eq!("0", format!("{:?}", FS::zero().neg(BN)));
     }
 
     #[test]
-    fn test_fs_addassig() {
+    fn test_fs_sub() {
         let one = &FS::one();
-        let mut three = one + one;
-        three += one;
+        let three = u32_to_fs(3);
 
-        assert_eq!("3", format!("{:?}", three));
+        assert_eq!("2", format!("{:?}", three.sub(one, BN)));
+        assert_eq!("-2", one.sub(&three, BN).format(false, BN));
     }
 
     #[test]
@@ -397,7 +353,7 @@ mod test {
 
     #[test]
     fn test_fs_shl() -> Result<()> {
-        let forty = &u32_to_fs(10) << &u32_to_fs(2);
+        let forty = u32_to_fs(10).shl(&u32_to_fs(2), BN);
         assert_eq!("40", format!("{:?}", forty?));
 
         Ok(())
@@ -405,7 +361,7 @@ mod test {
 
     #[test]
     fn test_fs_shr() -> Result<()> {
-        let twenty = &u32_to_fs(40) >> &u32_to_fs(1);
+        let twenty = u32_to_fs(40).shr(&u32_to_fs(1));
         assert_eq!("20", format!("{:?}", twenty?));
 
         Ok(())
@@ -413,11 +369,26 @@ mod test {
 
     #[test]
     fn test_div() -> Result<()> {
-        let div = &u32_to_fs(1) / &u32_to_fs(2);
-        let mul = &u32_to_fs(6) * &div?;
+        let div = u32_to_fs(1).div(&u32_to_fs(2), BN);
+        let mul = u32_to_fs(6).mul(&div?, BN);
         assert_eq!("3", format!("{:?}", mul));
 
         Ok(())
     }
 
+    #[test]
+    fn test_fields() {
+        let minus_one = |field| FS::one().neg(field).0.to_string();
+        assert_eq!((&*BABYJUB_FIELD_UINT - &*ONE).to_string(), minus_one(Field::Bn254));
+        assert_eq!((&*BLS12_381_FIELD_UINT - &*ONE).to_string(), minus_one(Field::Bls12_381));
+
+        // the bn254 prime is only reduced to zero in bn254
+        let p = FS(BABYJUB_FIELD_UINT.clone());
+        assert!(FS::reduce(&p.0, Field::Bn254).is_zero());
+        assert!(!FS::reduce(&p.0, Field::Bls12_381).is_zero());
+        assert_eq!("-1", FS::from_bigint(&BigInt::from(-1), Field::Bls12_381).format(false, Field::Bls12_381));
+
+        assert_eq!(Some(Field::Bls12_381), Field::from_prime(&BLS12_381_FIELD_UINT));
+        assert_eq!(None, Field::from_prime(&BigUint::from(7u32)));
+    }
 }
@@ -1,8 +1,9 @@
 use std::fmt;
 use std::iter;
-use std::ops::{Add, Mul, Neg};
+use std::ops::Mul;
 
-use super::SIGNAL_ONE;
+use super::{Field, SIGNAL_ONE};
+use super::error::{Error, Result};
 use super::traits::AlgZero;
 use super::types::*;
 
@@ -33,97 +34,66 @@ impl LC {
     pub fn rm(&mut self, signal: SignalId) {
         self.0.retain(|(s, _)| *s != signal);
     }
-    pub fn format<F>(&self, func: F) -> String
+    /// value of the combination with the `witness` values, that are in `field`
+    pub fn eval(&self, witness: &Witness, field: Field) -> Result<FS> {
+        self.0.iter().try_fold(FS::zero(), |acc, (s, v)| {
+            let value = witness.get(*s).ok_or(Error::UnknownSignal(*s))?;
+            Ok(acc.add(&v.mul(value, field), field))
+        })
+    }
+    /// with the coefficients over half of `field` shown as negative
+    pub fn format<F>(&self, field: Field, func: F) -> String
+    where
+        F: Fn(SignalId) -> String,
+    {
+        self.format_with(|v, plus| v.format(plus, field), func)
+    }
+    fn format_with<V, F>(&self, value: V, func: F) -> String
     where
+        V: Fn(&FS, bool) -> String,
         F: Fn(SignalId) -> String,
     {
         if let Some((head, tail)) = self.0.split_first() {
-            let head = format!("{}{}", head.1.format(false), func(head.0));
+            let head = format!("{}{}", value(&head.1, false), func(head.0));
             let tail = tail
                 .iter()
-                .map(|(s, v)| format!("{}{}", v.format(true), func(*s)));
+                .map(|(s, v)| format!("{}{}", value(v, true), func(*s)));
             iter::once(head).chain(tail).collect::<Vec<_>>().join("")
         } else {
             "0".to_string()
         }
     }
-}
 
-impl Default for LC {
-    fn default() -> Self {
-        LC::new()
+    pub fn neg(&self, field: Field) -> LC {
+        LC(self.0.iter().map(|(s, v)| (*s, v.neg(field))).collect())
     }
-}
-
-impl AlgZero for LC {
-    fn zero() -> Self {
-        LC(vec![])
-    }
-    fn is_zero(&self) -> bool {
-        !self.0.iter().any(|(_, e)| !e.is_zero())
-    }
-}
 
-impl<'a> From<&'a FS> for LC {
-    fn from(fs: &'a FS) -> Self {
-        LC(vec![(SIGNAL_ONE, fs.clone())])
-    }
-}
-
-impl fmt::Debug for LC {
-    fn fmt(&self, fmt: &mut fmt::Formatter) -> std::result::Result<(), fmt::Error> {
-        write!(fmt, "{}", self.format(|s| format!("s{}", s)))
-    }
-}
-
-// -&LC -> LC
-impl<'a> Neg for &'a LC {
-    type Output = LC;
-
-    fn neg(self) -> LC {
-        LC(self.0.iter().map(|(s, v)| (*s, -v)).collect())
-    }
-}
-
-// &LC + &FS -> LC
-impl<'a> Add<&'a FS> for &'a LC {
-    type Output = LC;
-
-    fn add(self, rhs: &'a FS) -> LC {
+    /// adds `rhs` times the signal one
+    pub fn add_fs(&self, rhs: &FS, field: Field) -> LC {
         let mut v = self.0.clone();
 
         if let Some(i) = v.iter().position(|(s, _)| *s == SIGNAL_ONE) {
-            v[i].1 += rhs;
+            v[i].1 = v[i].1.add(rhs, field);
         } else {
             v.push((SIGNAL_ONE, rhs.clone()))
         }
         v.retain(|v| !v.1.is_zero());
         LC(v)
     }
-}
-
-// &LC * &FS -> LC
-impl<'a> Mul<&'a FS> for &'a LC {
-    type Output = LC;
 
-    fn mul(self, rhs: &'a FS) -> LC {
+    pub fn mul_fs(&self, rhs: &FS, field: Field) -> LC {
         if rhs.is_zero() {
             LC::zero()
         } else {
-            LC(self.0.iter().map(|(s, e)| (*s, e * rhs)).collect())
+            LC(self.0.iter().map(|(s, e)| (*s, e.mul(rhs, field))).collect())
         }
     }
-}
-
-// &LC + &LC -> LC
-impl<'a> Add<&'a LC> for &'a LC {
-    type Output = LC;
 
-    fn add(self, rhs: &'a LC) -> LC {
+    pub fn add(&self, rhs: &LC, field: Field) -> LC {
         let mut v = self.0.clone();
         for (signal, e) in &rhs.0 {
             if let Some(i) = v.iter().position(|(s, _)| s == signal) {
-                v[i].1 += e;
+                v[i].1 = v[i].1.add(e, field);
             } else {
                 v.push((*signal, e.clone()));
             }
@@ -133,6 +103,35 @@ impl<'a> Add<&'a LC> for &'a LC {
     }
 }
 
+impl Default for LC {
+    fn default() -> Self {
+        LC::new()
+    }
+}
+
+impl AlgZero for LC {
+    fn zero() -> Self {
+        LC(vec![])
+    }
+    fn is_zero(&self) -> bool {
+        !self.0.iter().any(|(_, e)| !e.is_zero())
+    }
+}
+
+impl<'a> From<&'a FS> for LC {
+    fn from(fs: &'a FS) -> Self {
+        LC(vec![(SIGNAL_ONE, fs.clone())])
+    }
+}
+
+/// without a field the coefficients are shown unsigned, see `format`
+impl fmt::Debug for LC {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> std::result::Result<(), fmt::Error> {
+        let value = |v: &FS, plus| if plus { format!("+{:?}", v) } else { format!("{:?}", v) };
+        write!(fmt, "{}", self.format_with(value, |s| format!("s{}", s)))
+    }
+}
+
 // &LC * &LC -> QEQ
 impl<'a> Mul<&'a LC> for &'a LC {
     type Output = QEQ;
@@ -149,10 +148,12 @@ impl<'a> Mul<&'a LC> for &'a LC {
 #[cfg(test)]
 mod test {
     use super::*;
-    use num_bigint::ToBigUint;
+    use crate::algebra::BABYJUB_FIELD_UINT;
+
+    const BN: Field = Field::Bn254;
 
     fn u32_to_fs(n: u32) -> FS {
-        FS::from(n.to_biguint().unwrap())
+        FS::from(n as u64)
     }
 
     #[test]
@@ -186,14 +187,14 @@ mod test {
     #[test]
     fn test_lc_fs_add_mul() {
         let one = &FS::one();
-        let two = &(one + one);
+        let two = &one.add(one, BN);
         let s1 = 1 as SignalId;
 
         let lc_1s1 = &LC::from_signal(s1, FS::one());
-        assert_eq!("1s1+2s0", format!("{:?}", &(lc_1s1 + one) + one));
+        assert_eq!("1s1+2s0", format!("{:?}", lc_1s1.add_fs(one, BN).add_fs(one, BN)));
 
-        let lc_1s1_4one = &(lc_1s1 + two);
-        assert_eq!("2s1+4s0", format!("{:?}", lc_1s1_4one * two));
+        let lc_1s1_4one = &lc_1s1.add_fs(two, BN);
+        assert_eq!("2s1+4s0", format!("{:?}", lc_1s1_4one.mul_fs(two, BN)));
     }
 
     #[test]
@@ -202,13 +203,18 @@ mod test {
         let s2 = 2 as SignalId;
         let lc_1s1 = &LC::from_signal(s1, FS::one());
         let lc_1s2 = &LC::from_signal(s2, FS::one());
-
-        let lc_n1s1_1s2 = &(&(-lc_1s1) + lc_1s2);
-        assert_eq!("-1s1+1s2", format!("{:?}", lc_n1s1_1s2));
-        let lc_1s1_n1s2 = &-lc_n1s1_1s2;
-        assert_eq!("1s1-1s2", format!("{:?}", lc_1s1_n1s2));
-
-        let lc_zero = lc_n1s1_1s2 + lc_1s1_n1s2;
+        let format = |lc: &LC| lc.format(BN, |s| format!("s{}", s));
+
+        let lc_n1s1_1s2 = &lc_1s1.neg(BN).add(lc_1s2, BN);
+        assert_eq!("-1s1+1s2", format(lc_n1s1_1s2));
+        let lc_1s1_n1s2 = &lc_n1s1_1s2.neg(BN);
+        assert_eq!("1s1-1s2", format(lc_1s1_n1s2));
+        assert_eq!(
+            format!("1s1+{}s2", &*BABYJUB_FIELD_UINT - 1u32),
+            format!("{:?}", lc_1s1_n1s2)
+        );
+
+        let lc_zero = lc_n1s1_1s2.add(lc_1s1_n1s2, BN);
         assert_eq!("0", format!("{:?}", lc_zero));
     }
 
@@ -220,11 +226,25 @@ mod test {
         let lc_1s2 = &LC::from_signal(s2, FS::one());
 
         assert_eq!("1s1", format!("{:?}", lc_1s1));
-        assert_eq!("2s1", format!("{:?}", lc_1s1 + lc_1s1));
-        let lc_2s1_1s2 = &(lc_1s1 + lc_1s1) + lc_1s2;
+        assert_eq!("2s1", format!("{:?}", lc_1s1.add(lc_1s1, BN)));
+        let lc_2s1_1s2 = lc_1s1.add(lc_1s1, BN).add(lc_1s2, BN);
 
         assert_eq!("2s1+1s2", format!("{:?}", &lc_2s1_1s2));
         assert_eq!("[2s1+1s2]*[1s2]+[ ]", format!("{:?}", &lc_2s1_1s2 * lc_1s2));
     }
 
+    #[test]
+    fn test_lc_eval() {
+        let s1 = 1 as SignalId;
+        let s2 = 2 as SignalId;
+        let witness = Witness::from(vec![FS::one(), u32_to_fs(3), u32_to_fs(5)]);
+
+        let lc_2s1_1s2 = LC::from_signal(s1, u32_to_fs(2)).add(&LC::from_signal(s2, FS::one()), BN);
+        let lc = lc_2s1_1s2.add_fs(&FS::one(), BN);
+        assert_eq!("12", format!("{:?}", lc.eval(&witness, BN).unwrap()));
+        assert_eq!("0", format!("{:?}", LC::zero().eval(&witness, BN).unwrap()));
+
+        let unknown = LC::from_signal(3, FS::one());
+        assert!(unknown.eval(&witness, BN).is_err());
+    }
 }
@@ -4,6 +4,7 @@ mod types;
 mod fs;
 mod lc;
 mod qeq;
+mod witness;
 
 mod error;
 mod value;
@@ -11,6 +12,7 @@ mod value;
 pub const SIGNAL_ONE: SignalId = 0;
 
 pub use self::error::*;
+pub use self::fs::{Field, BABYJUB_FIELD_UINT, BLS12_381_FIELD_UINT};
 pub use self::traits::AlgZero;
-pub use self::types::{SignalId, FS, LC, QEQ};
+pub use self::types::{SignalId, Witness, FS, LC, QEQ};
 pub use self::value::{eval_infix, eval_prefix, Value};
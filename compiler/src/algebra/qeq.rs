@@ -1,93 +1,86 @@
 use std::fmt;
-use std::ops::{Add, Mul, Neg};
 
+use super::error::Result;
+use super::Field;
 use super::traits::AlgZero;
 use super::types::*;
 
 impl QEQ {
-    pub fn format<F>(&self, func: F) -> String
+    /// value of `a * b + c` with the `witness` values, that are in `field`
+    pub fn eval(&self, witness: &Witness, field: Field) -> Result<FS> {
+        let a = self.a.eval(witness, field)?;
+        let b = self.b.eval(witness, field)?;
+        let c = self.c.eval(witness, field)?;
+        Ok(a.mul(&b, field).add(&c, field))
+    }
+    /// with the coefficients over half of `field` shown as negative
+    pub fn format<F>(&self, field: Field, func: F) -> String
     where
         F: Fn(SignalId) -> String,
     {
+        self.format_with(|v| v.format(field, &func))
+    }
+    fn format_with<F: Fn(&LC) -> String>(&self, func: F) -> String {
         let f = |v: &LC| {
             if !v.0.is_empty() {
-                v.format(&func)
+                func(v)
             } else {
                 " ".to_string()
             }
         };
         format!("[{}]*[{}]+[{}]", f(&self.a), f(&self.b), f(&self.c))
     }
-}
 
-impl AlgZero for QEQ {
-    fn zero() -> Self {
+    pub fn add_fs(&self, rhs: &FS, field: Field) -> QEQ {
         QEQ {
-            a: LC::zero(),
-            b: LC::zero(),
-            c: LC::zero(),
+            a: self.a.clone(),
+            b: self.b.clone(),
+            c: self.c.add_fs(rhs, field),
         }
     }
-    fn is_zero(&self) -> bool {
-        (self.a.is_zero() || self.b.is_zero()) && self.c.is_zero()
-    }
-}
 
-impl fmt::Debug for QEQ {
-    fn fmt(&self, fmt: &mut fmt::Formatter) -> std::result::Result<(), fmt::Error> {
-        write!(fmt, "{}", self.format(|s| format!("s{}", s)))
+    pub fn mul_fs(&self, rhs: &FS, field: Field) -> QEQ {
+        QEQ {
+            a: self.a.mul_fs(rhs, field),
+            b: self.b.clone(),
+            c: self.c.mul_fs(rhs, field),
+        }
     }
-}
-
-// &QEQ + &FS -> QEQ
-impl<'a> Add<&'a FS> for &'a QEQ {
-    type Output = QEQ;
 
-    fn add(self, rhs: &'a FS) -> QEQ {
+    pub fn add_lc(&self, rhs: &LC, field: Field) -> QEQ {
         QEQ {
             a: self.a.clone(),
             b: self.b.clone(),
-            c: &self.c + rhs,
+            c: self.c.add(rhs, field),
         }
     }
-}
 
-// &QEQ * &FS -> QEQ
-impl<'a> Mul<&'a FS> for &'a QEQ {
-    type Output = QEQ;
-
-    fn mul(self, rhs: &'a FS) -> QEQ {
+    pub fn neg(&self, field: Field) -> QEQ {
         QEQ {
-            a: &self.a * rhs,
+            a: self.a.neg(field),
             b: self.b.clone(),
-            c: &self.c * rhs,
+            c: self.c.neg(field),
         }
     }
 }
 
-// &QEQ + &LC -> QEQ
-impl<'a> Add<&'a LC> for &'a QEQ {
-    type Output = QEQ;
-
-    fn add(self, rhs: &'a LC) -> QEQ {
+impl AlgZero for QEQ {
+    fn zero() -> Self {
         QEQ {
-            a: self.a.clone(),
-            b: self.b.clone(),
-            c: &self.c + rhs,
+            a: LC::zero(),
+            b: LC::zero(),
+            c: LC::zero(),
         }
     }
+    fn is_zero(&self) -> bool {
+        (self.a.is_zero() || self.b.is_zero()) && self.c.is_zero()
+    }
 }
 
-// -&QEQ -> QEQ
-impl<'a> Neg for &'a QEQ {
-    type Output = QEQ;
-
-    fn neg(self) -> QEQ {
-        QEQ {
-            a: -&self.a,
-            b: self.b.clone(),
-            c: -&self.c,
-        }
+/// without a field the coefficients are shown unsigned, see `format`
+impl fmt::Debug for QEQ {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> std::result::Result<(), fmt::Error> {
+        write!(fmt, "{}", self.format_with(|v| format!("{:?}", v)))
     }
 }
 
@@ -115,28 +108,49 @@ impl<'a> From<&'a LC> for QEQ {
 mod test {
     use super::*;
 
+    const BN: Field = Field::Bn254;
+
     #[test]
     fn test_qeq_fs_add_mul() {
         let one = &FS::one();
-        let two = &(one + one);
+        let two = &one.add(one, BN);
         let s1 = 1 as SignalId;
         let s2 = 2 as SignalId;
 
         let lc_1s1 = &LC::from_signal(s1, FS::one());
         let lc_1s2 = &LC::from_signal(s2, FS::one());
-        let lc_1s1_1s2_one = &(lc_1s1 * lc_1s2) + one;
+        let lc_1s1_1s2_one = &(lc_1s1 * lc_1s2).add_fs(one, BN);
 
         assert_eq!("[1s1]*[1s2]+[1s0]", format!("{:?}", lc_1s1_1s2_one));
-        assert_eq!("[2s1]*[1s2]+[2s0]", format!("{:?}", &lc_1s1_1s2_one * two));
+        assert_eq!("[2s1]*[1s2]+[2s0]", format!("{:?}", lc_1s1_1s2_one.mul_fs(two, BN)));
     }
 
     #[test]
     fn test_qeq_neg() {
         let s1 = 1 as SignalId;
         let lc_1s1 = &LC::from_signal(s1, FS::one());
-        let qeq = &(&(&(lc_1s1 + lc_1s1) * lc_1s1) + lc_1s1);
-        let neq_qeq = &-qeq;
-        assert_eq!("[2s1]*[1s1]+[1s1]", format!("{:?}", -neq_qeq));
+        let qeq = &(&lc_1s1.add(lc_1s1, BN) * lc_1s1).add_lc(lc_1s1, BN);
+        let neq_qeq = &qeq.neg(BN);
+        assert_eq!("[-2s1]*[1s1]+[-1s1]", neq_qeq.format(BN, |s| format!("s{}", s)));
+        assert_eq!("[2s1]*[1s1]+[1s1]", format!("{:?}", neq_qeq.neg(BN)));
     }
 
+    #[test]
+    fn test_qeq_eval() {
+        let one = &FS::one();
+        let two = &one.add(one, BN);
+        let s1 = 1 as SignalId;
+        let s2 = 2 as SignalId;
+        let witness = Witness::from(vec![FS::one(), two.clone(), two.add(one, BN)]);
+
+        // s1 * s2 - 6 = 0
+        let lc_1s1 = &LC::from_signal(s1, FS::one());
+        let lc_1s2 = &LC::from_signal(s2, FS::one());
+        let six = two.add(one, BN).mul(two, BN);
+        let qeq = (lc_1s1 * lc_1s2).add_fs(&six.neg(BN), BN);
+        assert!(qeq.eval(&witness, BN).unwrap().is_zero());
+
+        let qeq = (lc_1s1 * lc_1s2).add_fs(one, BN);
+        assert_eq!("7", format!("{:?}", qeq.eval(&witness, BN).unwrap()));
+    }
 }
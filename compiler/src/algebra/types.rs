@@ -14,3 +14,6 @@ pub struct QEQ {
     pub b: LC,
     pub c: LC,
 }
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Witness(pub Vec<FS>);
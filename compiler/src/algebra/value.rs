@@ -1,10 +1,9 @@
 use std::fmt;
 
 use circom2_parser::ast;
-use num_bigint::{BigInt, BigUint};
-use num_traits::identities::Zero;
 
 use super::error::{Error, Result};
+use super::Field;
 use super::types::*;
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -45,19 +44,7 @@ impl Value {
 
 impl Default for Value {
     fn default() -> Self {
-        Value::FieldScalar(FS::from(BigUint::zero()))
-    }
-}
-
-impl From<&BigInt> for Value {
-    fn from(n: &BigInt) -> Self {
-        Value::FieldScalar(FS::from(n))
-    }
-}
-
-impl From<&BigUint> for Value {
-    fn from(n: &BigUint) -> Self {
-        Value::FieldScalar(FS::from(n))
+        Value::FieldScalar(FS::zero())
     }
 }
 
@@ -103,48 +90,49 @@ impl fmt::Debug for Value {
     }
 }
 
-pub fn eval_infix(lhv: &Value, op: ast::Opcode, rhv: &Value) -> Result<Value> {
+/// apply `op` in `field`
+pub fn eval_infix(lhv: &Value, op: ast::Opcode, rhv: &Value, field: Field) -> Result<Value> {
     use ast::Opcode::*;
     use Value::*;
     match (op, lhv, rhv) {
         // add
-        (Add, FieldScalar(lhv), FieldScalar(rhv)) => Ok(FieldScalar(lhv + rhv)),
-        (Add, LinearCombination(lhv), LinearCombination(rhv)) => Ok(LinearCombination(lhv + rhv)),
+        (Add, FieldScalar(lhv), FieldScalar(rhv)) => Ok(FieldScalar(lhv.add(rhv, field))),
+        (Add, LinearCombination(lhv), LinearCombination(rhv)) => Ok(LinearCombination(lhv.add(rhv, field))),
 
-        (Add, FieldScalar(lhv), LinearCombination(rhv)) => Ok(LinearCombination(rhv + lhv)),
-        (Add, LinearCombination(lhv), FieldScalar(rhv)) => Ok(LinearCombination(lhv + rhv)),
+        (Add, FieldScalar(lhv), LinearCombination(rhv)) => Ok(LinearCombination(rhv.add_fs(lhv, field))),
+        (Add, LinearCombination(lhv), FieldScalar(rhv)) => Ok(LinearCombination(lhv.add_fs(rhv, field))),
 
-        (Add, FieldScalar(lhv), QuadraticEquation(rhv)) => Ok(QuadraticEquation(rhv + lhv)),
-        (Add, QuadraticEquation(lhv), FieldScalar(rhv)) => Ok(QuadraticEquation(lhv + rhv)),
+        (Add, FieldScalar(lhv), QuadraticEquation(rhv)) => Ok(QuadraticEquation(rhv.add_fs(lhv, field))),
+        (Add, QuadraticEquation(lhv), FieldScalar(rhv)) => Ok(QuadraticEquation(lhv.add_fs(rhv, field))),
 
-        (Add, LinearCombination(lhv), QuadraticEquation(rhv)) => Ok(QuadraticEquation(rhv + lhv)),
-        (Add, QuadraticEquation(lhv), LinearCombination(rhv)) => Ok(QuadraticEquation(lhv + rhv)),
+        (Add, LinearCombination(lhv), QuadraticEquation(rhv)) => Ok(QuadraticEquation(rhv.add_lc(lhv, field))),
+        (Add, QuadraticEquation(lhv), LinearCombination(rhv)) => Ok(QuadraticEquation(lhv.add_lc(rhv, field))),
 
         // sub
-        (Sub, FieldScalar(lhv), FieldScalar(rhv)) => Ok(FieldScalar(lhv + &-rhv)),
-        (Sub, LinearCombination(lhv), LinearCombination(rhv)) => Ok(LinearCombination(lhv + &-rhv)),
+        (Sub, FieldScalar(lhv), FieldScalar(rhv)) => Ok(FieldScalar(lhv.sub(rhv, field))),
+        (Sub, LinearCombination(lhv), LinearCombination(rhv)) => Ok(LinearCombination(lhv.add(&rhv.neg(field), field))),
 
-        (Sub, FieldScalar(lhv), LinearCombination(rhv)) => Ok(LinearCombination(&-rhv + lhv)),
-        (Sub, LinearCombination(lhv), FieldScalar(rhv)) => Ok(LinearCombination(lhv + &-rhv)),
+        (Sub, FieldScalar(lhv), LinearCombination(rhv)) => Ok(LinearCombination(rhv.neg(field).add_fs(lhv, field))),
+        (Sub, LinearCombination(lhv), FieldScalar(rhv)) => Ok(LinearCombination(lhv.add_fs(&rhv.neg(field), field))),
 
-        (Sub, FieldScalar(lhv), QuadraticEquation(rhv)) => Ok(QuadraticEquation(&-rhv + lhv)),
-        (Sub, QuadraticEquation(lhv), FieldScalar(rhv)) => Ok(QuadraticEquation(lhv + &-rhv)),
+        (Sub, FieldScalar(lhv), QuadraticEquation(rhv)) => Ok(QuadraticEquation(rhv.neg(field).add_fs(lhv, field))),
+        (Sub, QuadraticEquation(lhv), FieldScalar(rhv)) => Ok(QuadraticEquation(lhv.add_fs(&rhv.neg(field), field))),
 
-        (Sub, LinearCombination(lhv), QuadraticEquation(rhv)) => Ok(QuadraticEquation(&-rhv + lhv)),
-        (Sub, QuadraticEquation(lhv), LinearCombination(rhv)) => Ok(QuadraticEquation(lhv + &-rhv)),
+        (Sub, LinearCombination(lhv), QuadraticEquation(rhv)) => Ok(QuadraticEquation(rhv.neg(field).add_lc(lhv, field))),
+        (Sub, QuadraticEquation(lhv), LinearCombination(rhv)) => Ok(QuadraticEquation(lhv.add_lc(&rhv.neg(field), field))),
 
         // mul
-        (Mul, FieldScalar(lhv), FieldScalar(rhv)) => Ok(FieldScalar(lhv * rhv)),
+        (Mul, FieldScalar(lhv), FieldScalar(rhv)) => Ok(FieldScalar(lhv.mul(rhv, field))),
         (Mul, LinearCombination(lhv), LinearCombination(rhv)) => Ok(QuadraticEquation(lhv * rhv)),
 
-        (Mul, LinearCombination(lhv), FieldScalar(rhv)) => Ok(LinearCombination(lhv * rhv)),
-        (Mul, FieldScalar(lhv), LinearCombination(rhv)) => Ok(LinearCombination(rhv * lhv)),
+        (Mul, LinearCombination(lhv), FieldScalar(rhv)) => Ok(LinearCombination(lhv.mul_fs(rhv, field))),
+        (Mul, FieldScalar(lhv), LinearCombination(rhv)) => Ok(LinearCombination(rhv.mul_fs(lhv, field))),
 
-        (Mul, QuadraticEquation(lhv), FieldScalar(rhv)) => Ok(QuadraticEquation(lhv * rhv)),
-        (Mul, FieldScalar(lhv), QuadraticEquation(rhv)) => Ok(QuadraticEquation(rhv * lhv)),
+        (Mul, QuadraticEquation(lhv), FieldScalar(rhv)) => Ok(QuadraticEquation(lhv.mul_fs(rhv, field))),
+        (Mul, FieldScalar(lhv), QuadraticEquation(rhv)) => Ok(QuadraticEquation(rhv.mul_fs(lhv, field))),
 
         // div
-        (Div, FieldScalar(lhv), FieldScalar(rhv)) => Ok(FieldScalar((lhv / rhv)?)),
+        (Div, FieldScalar(lhv), FieldScalar(rhv)) => Ok(FieldScalar(lhv.div(rhv, field)?)),
 
         // intdiv
         (IntDiv, FieldScalar(lhv), FieldScalar(rhv)) => Ok(FieldScalar(lhv.intdiv(rhv))),
@@ -153,22 +141,22 @@ pub fn eval_infix(lhv: &Value, op: ast::Opcode, rhv: &Value) -> Result<Value> {
         (Mod, FieldScalar(lhv), FieldScalar(rhv)) => Ok(FieldScalar((lhv % rhv)?)),
 
         // <<
-        (ShiftL, FieldScalar(lhv), FieldScalar(rhv)) => Ok(FieldScalar((lhv << rhv)?)),
+        (ShiftL, FieldScalar(lhv), FieldScalar(rhv)) => Ok(FieldScalar(lhv.shl(rhv, field)?)),
 
         // >>
-        (ShiftR, FieldScalar(lhv), FieldScalar(rhv)) => Ok(FieldScalar((lhv >> rhv)?)),
+        (ShiftR, FieldScalar(lhv), FieldScalar(rhv)) => Ok(FieldScalar(lhv.shr(rhv)?)),
 
         // and
         (BitAnd, FieldScalar(lhv), FieldScalar(rhv)) => Ok(FieldScalar(lhv & rhv)),
 
         // or
-        (BitOr, FieldScalar(lhv), FieldScalar(rhv)) => Ok(FieldScalar(lhv | rhv)),
+        (BitOr, FieldScalar(lhv), FieldScalar(rhv)) => Ok(FieldScalar(lhv.bitor(rhv, field))),
 
         // xor
-        (BitXor, FieldScalar(lhv), FieldScalar(rhv)) => Ok(FieldScalar(lhv ^ rhv)),
+        (BitXor, FieldScalar(lhv), FieldScalar(rhv)) => Ok(FieldScalar(lhv.bitxor(rhv, field))),
 
         // powmod
-        (Pow, FieldScalar(lhv), FieldScalar(rhv)) => Ok(FieldScalar(lhv.pow(rhv))),
+        (Pow, FieldScalar(lhv), FieldScalar(rhv)) => Ok(FieldScalar(lhv.pow(rhv, field))),
 
         _ => Err(Error::InvalidOperation(format!(
             "Cannot apply operator {:?} on {:?} over {:?}",
@@ -177,14 +165,15 @@ pub fn eval_infix(lhv: &Value, op: ast::Opcode, rhv: &Value) -> Result<Value> {
     }
 }
 
-pub fn eval_prefix(op: ast::Opcode, rhv: &Value) -> Result<Value> {
+/// apply `op` in `field`
+pub fn eval_prefix(op: ast::Opcode, rhv: &Value, field: Field) -> Result<Value> {
     use ast::Opcode::Sub;
     use Value::*;
     match (op, rhv) {
         // negate
-        (Sub, FieldScalar(rhv)) => Ok(FieldScalar(-rhv)),
-        (Sub, LinearCombination(rhv)) => Ok(LinearCombination(-rhv)),
-        (Sub, QuadraticEquation(rhv)) => Ok(QuadraticEquation(-rhv)),
+        (Sub, FieldScalar(rhv)) => Ok(FieldScalar(rhv.neg(field))),
+        (Sub, LinearCombination(rhv)) => Ok(LinearCombination(rhv.neg(field))),
+        (Sub, QuadraticEquation(rhv)) => Ok(QuadraticEquation(rhv.neg(field))),
 
         _ => Err(Error::InvalidOperation(format!(
             "Cannot apply operator {:?} on {:?}",
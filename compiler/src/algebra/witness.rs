@@ -0,0 +1,40 @@
+use std::fmt;
+
+use super::types::*;
+
+impl Witness {
+    pub fn new() -> Self {
+        Witness(vec![FS::one()])
+    }
+    pub fn get(&self, signal: SignalId) -> Option<&FS> {
+        self.0.get(signal)
+    }
+    pub fn push(&mut self, fs: FS) -> SignalId {
+        self.0.push(fs);
+        self.0.len() - 1
+    }
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Default for Witness {
+    fn default() -> Self {
+        Witness::new()
+    }
+}
+
+impl From<Vec<FS>> for Witness {
+    fn from(values: Vec<FS>) -> Self {
+        Witness(values)
+    }
+}
+
+impl fmt::Debug for Witness {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> std::result::Result<(), fmt::Error> {
+        write!(fmt, "{:?}", self.0)
+    }
+}
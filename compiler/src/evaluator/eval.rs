@@ -15,12 +15,13 @@ use itertools::Itertools;
 use num_bigint::BigInt;
 
 use super::algebra;
-use super::algebra::{AlgZero, SignalId};
+use super::algebra::{AlgZero, Field, SignalId};
 use super::error::*;
 use super::retval::*;
 use super::scope::*;
 use super::types::*;
 use super::utils::*;
+use crate::progress::{Counter, NoProgress, Phase, Progress};
 use crate::storage::{Constraints, Signals};
 
 #[derive(Debug)]
@@ -49,7 +50,7 @@ impl Mode {
 }
 
 #[derive(Debug)]
-pub struct Evaluator<S, C>
+pub struct Evaluator<'a, S, C>
 where
     S: Signals,
     C: Constraints,
@@ -58,7 +59,6 @@ where
     pub current_file: String,
     pub current_component: String,
     pub current_function: Option<String>,
-    pub debug_last_constraint: std::time::Instant,
 
     // collected signals, constraints and components
     pub signals: S,
@@ -81,33 +81,44 @@ where
 
     // turn on debugging
     pub debug: bool,
+
+    // field of the signals, the arithmetic of the circuit is done in it
+    pub field: Field,
+
+    // receives the generated constraints count, it can borrow the progress
+    //   of the caller
+    pub progress: Box<dyn Progress + 'a>,
+    constraints_counter: Counter,
 }
 
-impl<S, C> Evaluator<S, C>
+impl<'a, S, C> Evaluator<'a, S, C>
 where
     S: Signals,
     C: Constraints,
 {
     pub fn new(mode: Mode, signals: S, constraints: C) -> Self {
         Self {
+            field: signals.field(),
             signals,
             constraints,
             mode,
             current_file: "".to_string(),
             current_component: "".to_string(),
             current_function: None,
-            debug_last_constraint: std::time::Instant::now(),
             processed_files: Vec::new(),
             last_error: None,
             path: PathBuf::from("."),
             deferred_signal_values: HashMap::new(),
             debug: false,
+            progress: Box::new(NoProgress),
+            constraints_counter: Counter::new(Phase::Constraints, 100_000),
         }
     }
 
     // public interface ---------------------------------------------------------------------------
 
     pub fn eval_inline(&mut self, scope: &mut Scope, code: &str) -> Result<()> {
+        self.register_source(code);
         match circom2_parser::parse(&code) {
             Ok(elements) => self.eval_body_elements_p(&Meta::new(0, 0, None), scope, &elements)?,
 
@@ -129,12 +140,47 @@ where
     }
 
     pub fn eval_file(&mut self, path: &str, filename: &str) -> Result<Scope> {
+        let phase = if self.mode == Mode::GenWitness {
+            Phase::Witness
+        } else {
+            Phase::Constraints
+        };
+        self.progress.start(phase, None);
         self.path = PathBuf::from(path);
         let mut scope = Scope::new(true, None, filename.to_string());
         self.eval_include(&Meta::new(0, 0, None), &mut scope, filename)?;
+        let done = if phase == Phase::Witness {
+            self.signals.len()?
+        } else {
+            self.constraints.len()?
+        };
+        self.progress.finish(phase, done);
         Ok(scope)
     }
 
+    /// blake2b hash of all processed source files, in include order
+    pub fn circuit_hash(&self) -> String {
+        let mut hasher = Blake2b::new(64);
+        for file_hash in &self.processed_files {
+            hasher.update(file_hash.as_bytes());
+        }
+        hex::encode(hasher.finalize().as_bytes())
+    }
+
+    /// add the hash of `code` to the processed files, false if it was already there
+    fn register_source(&mut self, code: &str) -> bool {
+        let mut hasher = Blake2b::new(64);
+        hasher.update(code.as_bytes());
+        let hash_hex = hex::encode(hasher.finalize().as_bytes());
+
+        if self.processed_files.iter().any(|h| h == &hash_hex) {
+            false
+        } else {
+            self.processed_files.push(hash_hex);
+            true
+        }
+    }
+
     pub fn set_deferred_value(&mut self, signal_full_name: String, value: algebra::Value) {
         self.deferred_signal_values.insert(signal_full_name, value);
     }
@@ -161,7 +207,7 @@ where
         op: circom2_parser::ast::Opcode,
         rhv: &algebra::Value,
     ) -> Result<algebra::Value> {
-        match algebra::eval_prefix(op, rhv) {
+        match algebra::eval_prefix(op, rhv, self.field) {
             Err(err) => self.register_error(meta, scope, Err(Error::Algebra(err))),
             Ok(v) => Ok(v),
         }
@@ -175,7 +221,7 @@ where
         op: circom2_parser::ast::Opcode,
         rhv: &algebra::Value,
     ) -> Result<algebra::Value> {
-        match algebra::eval_infix(lhv, op, rhv) {
+        match algebra::eval_infix(lhv, op, rhv, self.field) {
             Err(err) => self.register_error(meta, scope, Err(Error::Algebra(err))),
             Ok(v) => Ok(v),
         }
@@ -274,11 +320,11 @@ where
             match name {
                 "dbg_signals" => Ok(self.dbg_dump_signals()?),
                 "dbg" => {
-                    print!("DBG ");
+                    let mut line = "DBG ".to_string();
                     for param in params {
-                        self.dbg_print(&scope, param)?;
+                        line.push_str(&self.dbg_print(&scope, param)?);
                     }
-                    println!();
+                    info!("{}", line);
                     Ok(())
                 },
                 _ => Err(Error::NotFound(format!("internal funcion {}!", name)))
@@ -591,7 +637,7 @@ where
     }
 
     fn eval_number(&mut self, meta: &Meta, scope: &Scope, n: &BigInt) -> Result<ReturnValue> {
-        let internal = || Ok(ReturnValue::Algebra(algebra::Value::from(n)));
+        let internal = || Ok(ReturnValue::Algebra(algebra::Value::from(algebra::FS::from_bigint(n, self.field))));
         let res = internal();
         self.register_error(meta, scope, res)
     }
@@ -1210,16 +1256,7 @@ where
                     self.constraints.push(qeq, None)?
                 };
 
-                if count > 0 && count % 100_000 == 0 {
-                    let now = std::time::Instant::now();
-                    let diff = now.duration_since(self.debug_last_constraint);
-                    println!(
-                        "Generated {} constrains, @ {} c/s",
-                        count,
-                        100_000_000 / diff.as_millis()
-                    );
-                    self.debug_last_constraint = now;
-                }
+                self.constraints_counter.set(count, &mut *self.progress);
             }
 
             Ok(())
@@ -1241,15 +1278,7 @@ where
                 return Err(Error::Io(format!("{:?}", full_path), ioerr.to_string()));
             }
 
-            let mut hasher = Blake2b::new(64);
-            hasher.update(&code.as_bytes());
-
-            let hash = hasher.finalize();
-            let hash_hex = hex::encode(hash.as_bytes());
-
-            if !self.processed_files.iter().any(|h| h == &hash_hex) {
-                self.processed_files.push(hash_hex);
-
+            if self.register_source(&code) {
                 let mut new_current_file = full_path.to_str().unwrap().to_string();
                 let mut new_path = full_path.parent().unwrap().to_path_buf();
                 std::mem::swap(&mut new_current_file, &mut self.current_file);
@@ -1346,7 +1375,7 @@ where
         F: FnOnce() -> String,
     {
         if self.debug {
-            println!("*trace {} {}:{:?}", f(), self.current_file, meta.start);
+            debug!("*trace {} {}:{:?}", f(), self.current_file, meta.start);
         }
     }
 
@@ -1473,14 +1502,14 @@ where
     }
 
     fn dbg_dump_signals(&self) -> Result<()> {
-        for n in 0..self.signals.len()? {
-            println!("{:?}", self.signals.get_by_id(n).unwrap().unwrap());
+        for signal in self.signals.iter() {
+            info!("{:?}", signal?);
         }
         Ok(())
     }
 
-    fn dbg_print(&mut self, scope: &Scope, expr: &ExpressionP) -> Result<()> {
-        let mut processed = false;
+    fn dbg_print(&mut self, scope: &Scope, expr: &ExpressionP) -> Result<String> {
+        let mut processed = None;
 
         if let ExpressionP::Variable { name: var, .. } = &expr {
             let full_name = self.expand_selectors(scope, var, None)?;
@@ -1497,24 +1526,22 @@ where
                         })
                         .collect::<Vec<_>>()
                         .join(",");
-                    println!(
+                    processed = Some(format!(
                         "{} ⇨ pending_inputs {{{}}} ",
                         &full_name, pending_inputs_str
-                    );
-                    processed = true;
+                    ));
                 }
                 _ => {}
             });
         }
 
-        if !processed {
-            let value = self.eval_expression_p(scope, expr)?;
-            print!("{:?} ⇨ ", expr);
-            match value {
-                ReturnValue::Algebra(value) => print!("{} ", format_algebra(&self.signals, &value)),
-                _ => print!("{:?} ", value),
-            }
+        if let Some(processed) = processed {
+            return Ok(processed);
         }
-        Ok(())
+        let value = self.eval_expression_p(scope, expr)?;
+        Ok(match value {
+            ReturnValue::Algebra(value) => format!("{:?} ⇨ {} ", expr, format_algebra(&self.signals, &value)),
+            _ => format!("{:?} ⇨ {:?} ", expr, value),
+        })
     }
 }
@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod test {
-    use super::super::error::Result;
+    use super::super::error::{Error, Result};
     use super::super::scope::Scope;
     use crate::algebra;
     use crate::evaluator::eval::{Evaluator, Mode};
@@ -9,7 +9,7 @@ mod test {
     use crate::evaluator::check_constrains_eval_zero;
 
     fn constrain_eq<'a, S: Signals, C: Constraints>(
-        eval: &Evaluator<S, C>,
+        eval: &Evaluator<'_, S, C>,
         index: usize,
         value: &str,
     ) {
@@ -23,11 +23,11 @@ mod test {
             .constraints
             .get(index)
             .unwrap()
-            .format(|id| format!("{:?}", name_of(id)));
+            .format(eval.field, |id| format!("{:?}", name_of(id)));
 
         assert_eq!(formatted, value);
     }
-    fn signal_eq<'a, S: Signals, C: Constraints>(eval: &Evaluator<S, C>, name: &str, value: &str) {
+    fn signal_eq<'a, S: Signals, C: Constraints>(eval: &Evaluator<'_, S, C>, name: &str, value: &str) {
         if let Some(signal) = eval.signals.get_by_name(name).unwrap() {
             assert_eq!(eval.signals.to_string(signal.id).unwrap(), value);
         } else {
@@ -43,7 +43,7 @@ mod test {
         s: &str,
         deferred_values: Vec<(String, u64)>,
         mut factory: F,
-    ) -> Result<(Evaluator<S, C>, Scope)>
+    ) -> Result<(Evaluator<'static, S, C>, Scope)>
     where
         F: StorageFactory<S, C>,
         S: Signals,
@@ -60,12 +60,12 @@ mod test {
         Ok((evaluator, scope))
     }
 
-    fn eval_constraint(s: &str) -> Result<(Evaluator<RamSignals, RamConstraints>, Scope)> {
+    fn eval_constraint(s: &str) -> Result<(Evaluator<'static, RamSignals, RamConstraints>, Scope)> {
         let (eval, scope) = eval_generic(Mode::GenConstraints, s, vec![], Ram::default())?;
         Ok((eval, scope))
     }
 
-    fn eval_witness(s: &str) -> Result<(Evaluator<RamSignals, RamConstraints>, Scope)> {
+    fn eval_witness(s: &str) -> Result<(Evaluator<'static, RamSignals, RamConstraints>, Scope)> {
         let (eval_witness, scope_witness) = eval_generic(Mode::GenWitness, s, vec![], Ram::default())?;
         assert_eq!(eval_witness.constraints.len()?, 0);
             
@@ -75,7 +75,7 @@ mod test {
     fn eval_witness_with_defer(
         s: &str,
         deferred_values: Vec<(String, u64)>,
-    ) -> Result<(Evaluator<RamSignals, RamConstraints>, Scope)> {
+    ) -> Result<(Evaluator<'static, RamSignals, RamConstraints>, Scope)> {
         let (eval, scope) = eval_generic(Mode::GenWitness, s, deferred_values, Ram::default())?;
         assert_eq!(eval.constraints.len()?, 0);
 
@@ -735,6 +735,102 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_check_constrains_reports_all() -> Result<()> {
+        let (eval_constraint, _) = eval_constraint(
+            "
+            template t() {
+                signal input a;
+                signal input b;
+                a === 2;
+                b === 3;
+            }
+            component main = t();
+        ",
+        )?;
+        let (eval_witness, _) = eval_generic(
+            Mode::GenWitness,
+            "
+            template t() {
+                signal input a;
+                signal input b;
+            }
+            component main = t();
+        ",
+            vec![("main.a".to_string(), 5), ("main.b".to_string(), 7)],
+            Ram::default(),
+        )?;
+
+        match check_constrains_eval_zero(&eval_constraint.constraints, &eval_witness.signals) {
+            Err(Error::CannotCheckConstrain(msg)) => {
+                assert!(msg.contains("constrain #0"));
+                assert!(msg.contains("constrain #1"));
+            }
+            _ => panic!("expected both constraints to fail"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_ignores_unreferenced_signals() -> Result<()> {
+        let (eval_constraint, _) = eval_constraint(
+            "
+            template t() {
+                signal input a;
+                signal input unused;
+                a === 2;
+            }
+            component main = t();
+        ",
+        )?;
+        let (eval_witness, _) = eval_generic(
+            Mode::GenWitness,
+            "
+            template t() {
+                signal input a;
+                signal input unused;
+            }
+            component main = t();
+        ",
+            vec![("main.a".to_string(), 2)],
+            Ram::default(),
+        )?;
+
+        check_constrains_eval_zero(&eval_constraint.constraints, &eval_witness.signals)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_signals_shared_across_threads() -> Result<()> {
+        let (eval, _) = eval_witness_with_defer(
+            "
+            template t() {
+                signal input a;
+                signal output b;
+                b <== a * 3;
+            }
+            component main = t();
+        ",
+            vec![("main.a".to_string(), 2)],
+        )?;
+
+        let signals = std::sync::Arc::new(eval.signals);
+        let handles = (0..4)
+            .map(|_| {
+                let signals = signals.clone();
+                std::thread::spawn(move || {
+                    let b = signals.get_by_name("main.b").unwrap().unwrap();
+                    format!("{:?}", b.value)
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            assert_eq!("Some(6)", handle.join().unwrap());
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_signal_ordering() -> Result<()> {
         let (eval, _) = eval_constraint(
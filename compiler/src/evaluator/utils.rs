@@ -3,57 +3,74 @@ use super::algebra::{AlgZero, Value};
 use super::error::*;
 use crate::evaluator::Evaluator;
 use crate::storage::{Constraints, Signals, Signal};
-use std::rc::Rc;
+use std::collections::HashSet;
+use std::sync::Arc;
+use rayon::prelude::*;
 
 pub fn check_constrains_eval_zero<C:Constraints,S:Signals>(constraints: &C, signals: &S) -> Result<()> { 
-    let eval_lc = |lc: &algebra::LC| lc.0
-        .iter()
-        .fold(Ok(algebra::FS::zero()),|acc,(s,v)| {
-            let s_val = if *s == 0 {
-                algebra::FS::one()
-            } else {
-                let s_val = &*signals.get_by_id(*s).unwrap().unwrap();
-                match &s_val.value {
-                    Some(algebra::Value::FieldScalar(fs)) => fs.clone(),
-                    _=> return Err(Error::CannotCheckConstrain(format!("signal bad value {:?}",s_val)))
-                }
-            };
-            Ok(&acc? + &(v * &s_val))
-        });
-
-    for n in 0..constraints.len().unwrap() {
-        let qeq = constraints.get(n).unwrap();
-        let a = eval_lc(&qeq.a)?;
-        let b = eval_lc(&qeq.b)?;
-        let c = eval_lc(&qeq.c)?;
+    // only signals referenced by a constraint need a value, the missing ones
+    //   are kept apart and reported when a constraint uses them
+    let mut witness = algebra::Witness::new();
+    let mut missing = HashSet::new();
+    for signal in signals.iter_range(1..signals.len()?) {
+        let signal = signal?;
+        match &signal.value {
+            Some(Value::FieldScalar(fs)) => {
+                witness.push(fs.clone());
+            }
+            _ => {
+                missing.insert(witness.push(algebra::FS::zero()));
+            }
+        };
+    }
 
-        let zero = &(&a * &b) + &c;
+    let field = signals.field();
+    let failed = (0..constraints.len()?)
+        .into_par_iter()
+        .map(|n| {
+            let qeq = constraints.get(n)?;
+            let unknown = qeq.a.0.iter().chain(qeq.b.0.iter()).chain(qeq.c.0.iter())
+                .find(|(s, _)| missing.contains(s));
+            if let Some((s, _)) = unknown {
+                let s_val = signals.get_by_id(*s)?;
+                return Err(Error::CannotCheckConstrain(format!("signal bad value {:?}",s_val)));
+            }
+            let zero = qeq.eval(&witness, field).map_err(Error::Algebra)?;
 
-        if !zero.is_zero() {
+            if zero.is_zero() {
+                return Ok(None);
+            }
             let nonzero_value = algebra::Value::QuadraticEquation(qeq);
             let debug = constraints.get_debug(n).unwrap_or("".to_string());
-            let msg = format!("constrain '{}' ({}) evals to non-zero ({:?})",format_algebra(signals,&nonzero_value),debug,zero);
-            return Err(Error::CannotCheckConstrain(msg));
-        }
+            Ok(Some(format!("constrain #{} '{}' ({}) evals to non-zero ({})",n,format_algebra(signals,&nonzero_value),debug,zero.format(false, field))))
+        })
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .filter_map(|msg| msg)
+        .collect::<Vec<_>>();
+
+    if !failed.is_empty() {
+        return Err(Error::CannotCheckConstrain(failed.join("\n")));
     }
 
     Ok(())
 }
 
 pub fn format_algebra<S:Signals>(signals: &S, a: &algebra::Value) -> String {
-    let qname = |s: Option<Rc<Signal>>| {
+    let qname = |s: Option<Arc<Signal>>| {
         Ok(s.map_or("unknown".to_string(), |s| s.full_name.to_string()))
     };
     let sname = |id| signals.get_by_id(id).and_then(qname).unwrap();
+    let field = signals.field();
 
     match a {
-        algebra::Value::FieldScalar(fe) => format!("{:?}", fe),
-        algebra::Value::LinearCombination(lc) => lc.format(sname),
-        algebra::Value::QuadraticEquation(qeq) => qeq.format(sname),
+        algebra::Value::FieldScalar(fe) => fe.format(false, field),
+        algebra::Value::LinearCombination(lc) => lc.format(field, sname),
+        algebra::Value::QuadraticEquation(qeq) => qeq.format(field, sname),
     }
 }
 
-pub fn print_info<S:Signals,C:Constraints>(eval : &Evaluator<S,C>, print_all: bool) {
+pub fn print_info<S:Signals,C:Constraints>(eval : &Evaluator<'_,S,C>, print_all: bool) {
     info!(
         "{} signals, {} constraints",
         eval.signals.len().unwrap(),
@@ -61,12 +78,12 @@ pub fn print_info<S:Signals,C:Constraints>(eval : &Evaluator<S,C>, print_all: bo
     );
     if print_all {
         info!("signals -------------------------");
-        for n in 0..eval.signals.len().unwrap() {
-            info!("{}: {:?}",n,eval.signals.get_by_id(n).unwrap());
+        for (n, signal) in eval.signals.iter().enumerate() {
+            info!("{}: {:?}",n,signal.unwrap());
         }
         info!("constrains ----------------------");
-        for n in 0..eval.constraints.len().unwrap() {
-            let constrain = Value::QuadraticEquation(eval.constraints.get(n).unwrap());
+        for (n, constrain) in eval.constraints.iter().enumerate() {
+            let constrain = Value::QuadraticEquation(constrain.unwrap());
             info!("{}:  {}=0",n,format_algebra(&eval.signals,&constrain));
         }
     }
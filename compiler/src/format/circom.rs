@@ -0,0 +1,242 @@
+use circom2_parser::ast::SignalType;
+use std::collections::HashMap;
+use std::io::{BufRead, Read};
+
+use super::error::{Error, Result};
+use super::r1cs::read_r1cs;
+use super::sym::read_sym;
+use crate::algebra::{Field, FS, LC, QEQ};
+use crate::storage::{Constraints, Ram, RamConstraints, RamSignals, Signals, StorageFactory};
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CircuitJson {
+    signals: Vec<CircuitJsonSignal>,
+    constraints: Vec<[HashMap<String, String>; 3]>,
+    n_outputs: usize,
+    n_pub_inputs: usize,
+    n_prv_inputs: usize,
+    n_vars: usize,
+}
+
+#[derive(Deserialize)]
+struct CircuitJsonSignal {
+    names: Vec<String>,
+    id: Option<i64>,
+}
+
+/// import a circuit compiled by the official circom from its `circuit.json`,
+///   signal ids are the circom variable indexes. The json has no prime, so the
+///   values are taken to be in `field`
+pub fn import_circuit_json<R: Read>(input: R, field: Field) -> Result<(RamSignals, RamConstraints)> {
+    let circuit: CircuitJson = serde_json::from_reader(input)?;
+
+    let mut names = HashMap::new();
+    for (n, signal) in circuit.signals.iter().enumerate() {
+        let var = signal.id.unwrap_or(n as i64);
+        if var >= 0 && !signal.names.is_empty() {
+            names.entry(var as usize).or_insert_with(|| signal.names[0].clone());
+        }
+    }
+
+    let parse_lc = |lc: &HashMap<String, String>| -> Result<LC> {
+        let mut lc = lc
+            .iter()
+            .map(|(var, value)| {
+                let var = var
+                    .parse::<usize>()
+                    .map_err(|_| Error::BadFormat(format!("bad variable {}", var)))?;
+                Ok((var, FS::parse(value)?))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        lc.sort_by_key(|(var, _)| *var);
+        Ok(LC(lc))
+    };
+
+    let mut constraints = Vec::with_capacity(circuit.constraints.len());
+    for [a, b, c] in &circuit.constraints {
+        constraints.push(QEQ {
+            a: parse_lc(a)?,
+            b: parse_lc(b)?,
+            c: parse_lc(c)?.neg(field),
+        });
+    }
+
+    build(
+        field,
+        circuit.n_vars,
+        [circuit.n_outputs, circuit.n_pub_inputs, circuit.n_prv_inputs],
+        &names,
+        constraints,
+    )
+}
+
+/// import a circuit from an iden3 binary r1cs file, names are taken from the
+///   sym file if any, signal ids are the r1cs wires
+pub fn import_r1cs<R: Read, S: BufRead>(
+    r1cs: R,
+    sym: Option<S>,
+) -> Result<(RamSignals, RamConstraints)> {
+    let r1cs = read_r1cs(r1cs)?;
+
+    let mut names = HashMap::new();
+    if let Some(sym) = sym {
+        for entry in read_sym(sym)? {
+            if entry.wire >= 0 {
+                names.entry(entry.wire as usize).or_insert(entry.full_name);
+            }
+        }
+    }
+
+    let header = &r1cs.header;
+    build(
+        header.field,
+        header.n_wires as usize,
+        [
+            header.n_pub_out as usize,
+            header.n_pub_in as usize,
+            header.n_prv_in as usize,
+        ],
+        &names,
+        r1cs.constraints,
+    )
+}
+
+/// wires are ordered as one, outputs, public inputs, private inputs, internals
+fn build(
+    field: Field,
+    n_wires: usize,
+    n_io: [usize; 3],
+    names: &HashMap<usize, String>,
+    qeqs: Vec<QEQ>,
+) -> Result<(RamSignals, RamConstraints)> {
+    let mut ram = Ram::new(field);
+    let mut signals = ram.new_signals()?;
+    let mut constraints = ram.new_constraints()?;
+
+    let types = [
+        SignalType::Output,
+        SignalType::PublicInput,
+        SignalType::PrivateInput,
+    ];
+    for wire in 1..n_wires {
+        let mut xtype = SignalType::Internal;
+        let mut first = 1;
+        for (count, signal_type) in n_io.iter().zip(types.iter()) {
+            if wire >= first && wire < first + count {
+                xtype = *signal_type;
+            }
+            first += count;
+        }
+        let full_name = names
+            .get(&wire)
+            .cloned()
+            .unwrap_or_else(|| format!("main.wire{}", wire));
+        signals.insert(full_name, xtype, None)?;
+    }
+
+    for qeq in &qeqs {
+        for lc in &[&qeq.a, &qeq.b, &qeq.c] {
+            if let Some((wire, _)) = lc.0.iter().find(|(wire, _)| *wire >= n_wires) {
+                return Err(Error::BadFormat(format!("constraint uses unknown wire {}", wire)));
+            }
+        }
+    }
+    constraints.push_batch(qeqs.into_iter().map(|qeq| (qeq, None)).collect())?;
+
+    Ok((signals, constraints))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::evaluator::{check_constrains_eval_zero, Evaluator, Mode, Scope};
+    use crate::format::{write_r1cs, write_sym, WireMap};
+    use crate::storage::{assign_witness, build_witness};
+    use crate::algebra::{Value, Witness};
+    use std::io::Cursor;
+
+    const CIRCUIT: &str = "
+        template t() {
+            signal private input a;
+            signal input b;
+            signal tmp;
+            signal output c;
+            tmp <== a * b;
+            c <== tmp * 2 + 1;
+        }
+        component main = t();
+    ";
+
+    fn eval(mode: Mode) -> Evaluator<'static, RamSignals, RamConstraints> {
+        let mut ram = Ram::default();
+        let mut eval = Evaluator::new(mode, ram.new_signals().unwrap(), ram.new_constraints().unwrap());
+        eval.set_deferred_value("main.a".to_string(), Value::from(3));
+        eval.set_deferred_value("main.b".to_string(), Value::from(7));
+        eval.eval_inline(&mut Scope::new(true, None, "root".to_string()), CIRCUIT)
+            .unwrap();
+        eval
+    }
+
+    #[test]
+    fn test_import_r1cs() -> Result<()> {
+        let ev_r1cs = eval(Mode::GenConstraints);
+        let ev_witness = eval(Mode::GenWitness);
+
+        let mut r1cs = Cursor::new(Vec::new());
+        write_r1cs(&mut r1cs, &ev_r1cs.signals, &ev_r1cs.constraints)?;
+        let mut sym = Vec::new();
+        write_sym(&mut sym, &ev_r1cs.signals)?;
+
+        let (mut signals, constraints) = import_r1cs(&r1cs.get_ref()[..], Some(&sym[..]))?;
+        assert_eq!(ev_r1cs.signals.len()?, signals.len()?);
+        assert_eq!(ev_r1cs.constraints.len()?, constraints.len()?);
+
+        let c = signals.get_by_name("main.c")?.unwrap();
+        assert_eq!(SignalType::Output, c.xtype);
+        let a = signals.get_by_name("main.a")?.unwrap();
+        assert_eq!(SignalType::PrivateInput, a.xtype);
+
+        let wires = WireMap::new(&ev_r1cs.signals)?;
+        let witness = wires.to_wires(&build_witness(&ev_witness.signals)?);
+        assign_witness(&mut signals, &witness)?;
+        check_constrains_eval_zero(&constraints, &signals).unwrap();
+        assert_eq!("Some(43)", format!("{:?}", signals.get_by_name("main.c")?.unwrap().value));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_circuit_json() -> Result<()> {
+        // out <== a * b
+        let json = r#"{
+            "signals": [
+                { "names": ["one"], "triggerComponents": [] },
+                { "names": ["main.out"], "triggerComponents": [] },
+                { "names": ["main.a"], "triggerComponents": [0] },
+                { "names": ["main.b"], "triggerComponents": [0] }
+            ],
+            "constraints": [
+                [ { "2": "21888242871839275222246405745257275088548364400416034343698204186575808495616" },
+                  { "3": "1" },
+                  { "1": "21888242871839275222246405745257275088548364400416034343698204186575808495616" } ]
+            ],
+            "nOutputs": 1,
+            "nPubInputs": 0,
+            "nPrvInputs": 2,
+            "nVars": 4
+        }"#;
+
+        let (mut signals, constraints) = import_circuit_json(json.as_bytes(), Field::Bn254)?;
+        assert_eq!(4, signals.len()?);
+        assert_eq!(1, constraints.len()?);
+        assert_eq!(SignalType::Output, signals.get_by_id(1)?.unwrap().xtype);
+        assert_eq!("main.b", signals.get_by_id(3)?.unwrap().full_name.to_string());
+
+        let witness = Witness::from(vec![FS::one(), FS::from(12), FS::from(3), FS::from(4)]);
+        assign_witness(&mut signals, &witness)?;
+        check_constrains_eval_zero(&constraints, &signals).unwrap();
+
+        Ok(())
+    }
+}
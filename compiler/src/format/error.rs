@@ -0,0 +1,37 @@
+use crate::algebra;
+use crate::storage;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Storage(storage::Error),
+    Algebra(algebra::Error),
+    Json(serde_json::Error),
+    BadFormat(String),
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<storage::Error> for Error {
+    fn from(err: storage::Error) -> Self {
+        Error::Storage(err)
+    }
+}
+
+impl From<algebra::Error> for Error {
+    fn from(err: algebra::Error) -> Self {
+        Error::Algebra(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
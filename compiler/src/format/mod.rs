@@ -0,0 +1,13 @@
+mod circom;
+mod error;
+mod r1cs;
+mod sym;
+mod wires;
+mod wtns;
+
+pub use self::circom::{import_circuit_json, import_r1cs};
+pub use self::error::{Error, Result};
+pub use self::r1cs::{read_r1cs, write_r1cs, R1cs, R1csHeader};
+pub use self::sym::{read_sym, write_sym, SymEntry};
+pub use self::wires::WireMap;
+pub use self::wtns::{read_wtns, write_witness_json, write_wtns};
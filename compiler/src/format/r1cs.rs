@@ -0,0 +1,379 @@
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use num_bigint::BigUint;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use super::error::{Error, Result};
+use super::wires::WireMap;
+use crate::algebra::{Field, FS, LC, QEQ};
+use crate::storage::{Constraints, Signals};
+
+// iden3 binary r1cs format
+//
+//   magic "r1cs", version : u32, n sections : u32
+//   for each section: type : u32, size : u64, content
+//
+//   1 header      : n8 : u32, prime, n wires : u32, n pub out : u32,
+//                   n pub in : u32, n prv in : u32, n labels : u64,
+//                   n constraints : u32
+//   2 constraints : A, B, C linear combinations as
+//                   n coefs : u32, (wire : u32, value : n8 bytes LE)*
+//   3 wire2label  : label : u64 for each wire
+//
+// constraints are A*B - C = 0, so C is the negated `c` of our QEQ
+//
+const MAGIC: &[u8; 4] = b"r1cs";
+const VERSION: u32 = 1;
+const N8: usize = 32;
+
+const SECTION_HEADER: u32 = 1;
+const SECTION_CONSTRAINTS: u32 = 2;
+const SECTION_WIRE2LABEL: u32 = 3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct R1csHeader {
+    pub field: Field,
+    pub n_wires: u32,
+    pub n_pub_out: u32,
+    pub n_pub_in: u32,
+    pub n_prv_in: u32,
+    pub n_labels: u64,
+    pub n_constraints: u32,
+}
+
+/// a parsed r1cs file, constraints refer to wires
+pub struct R1cs {
+    pub header: R1csHeader,
+    pub constraints: Vec<QEQ>,
+    pub wire_to_label: Vec<u64>,
+}
+
+/// write the circuit in the iden3 binary r1cs format
+pub fn write_r1cs<W, S, C>(mut out: W, signals: &S, constraints: &C) -> Result<()>
+where
+    W: Write + Seek,
+    S: Signals,
+    C: Constraints,
+{
+    let field = signals.field();
+    let wires = WireMap::new(signals)?;
+
+    out.write_all(MAGIC)?;
+    out.write_u32::<LittleEndian>(VERSION)?;
+    out.write_u32::<LittleEndian>(3)?;
+
+    // header
+    let section = start_section(&mut out, SECTION_HEADER)?;
+    out.write_u32::<LittleEndian>(N8 as u32)?;
+    write_fs(&mut out, field.prime())?;
+    out.write_u32::<LittleEndian>(wires.len() as u32)?;
+    out.write_u32::<LittleEndian>(wires.n_pub_out as u32)?;
+    out.write_u32::<LittleEndian>(wires.n_pub_in as u32)?;
+    out.write_u32::<LittleEndian>(wires.n_prv_in as u32)?;
+    out.write_u64::<LittleEndian>(signals.len()? as u64)?;
+    out.write_u32::<LittleEndian>(constraints.len()? as u32)?;
+    end_section(&mut out, section)?;
+
+    // constraints
+    let section = start_section(&mut out, SECTION_CONSTRAINTS)?;
+    for qeq in constraints.iter() {
+        let qeq = qeq?;
+        write_lc(&mut out, &wires, &qeq.a)?;
+        write_lc(&mut out, &wires, &qeq.b)?;
+        write_lc(&mut out, &wires, &qeq.c.neg(field))?;
+    }
+    end_section(&mut out, section)?;
+
+    // wire to label
+    let section = start_section(&mut out, SECTION_WIRE2LABEL)?;
+    for wire in 0..wires.len() {
+        out.write_u64::<LittleEndian>(wires.signal(wire) as u64)?;
+    }
+    end_section(&mut out, section)?;
+
+    Ok(())
+}
+
+/// read an iden3 binary r1cs file
+pub fn read_r1cs<R: Read>(mut input: R) -> Result<R1cs> {
+    let mut magic = [0u8; 4];
+    input.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(Error::BadFormat("not an r1cs file".to_string()));
+    }
+    let version = input.read_u32::<LittleEndian>()?;
+    if version != VERSION {
+        return Err(Error::BadFormat(format!("unsupported r1cs version {}", version)));
+    }
+
+    // sections can come in any order, the constraints need the header to be parsed
+    let mut header = None;
+    let mut constraints_section = None;
+    let mut wire_to_label = Vec::new();
+
+    let n_sections = input.read_u32::<LittleEndian>()?;
+    for _ in 0..n_sections {
+        let section_type = input.read_u32::<LittleEndian>()?;
+        let size = input.read_u64::<LittleEndian>()?;
+        let content = read_section(&mut input, size)?;
+        match section_type {
+            SECTION_HEADER => header = Some(truncated(read_header(&mut &content[..]), "r1cs header")?),
+            SECTION_CONSTRAINTS => constraints_section = Some(content),
+            SECTION_WIRE2LABEL => {
+                let mut content = &content[..];
+                for _ in 0..size / 8 {
+                    wire_to_label.push(content.read_u64::<LittleEndian>()?);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let header = header.ok_or_else(|| Error::BadFormat("missing r1cs header".to_string()))?;
+    let mut constraints = Vec::new();
+    if let Some(content) = constraints_section {
+        let mut content = &content[..];
+        for _ in 0..header.n_constraints {
+            let a = truncated(read_lc(&mut content, header.field), "r1cs constraints")?;
+            let b = truncated(read_lc(&mut content, header.field), "r1cs constraints")?;
+            let c = truncated(read_lc(&mut content, header.field), "r1cs constraints")?;
+            constraints.push(QEQ { a, b, c: c.neg(header.field) });
+        }
+    }
+
+    Ok(R1cs {
+        header,
+        constraints,
+        wire_to_label,
+    })
+}
+
+/// content of a section, its size comes from the file so it is only trusted as
+///   far as there is input to back it
+fn read_section<R: Read>(input: &mut R, size: u64) -> Result<Vec<u8>> {
+    let mut content = Vec::new();
+    input.take(size).read_to_end(&mut content)?;
+    if content.len() as u64 != size {
+        return Err(Error::BadFormat("truncated r1cs section".to_string()));
+    }
+    Ok(content)
+}
+
+/// reading past the end of a section means it is shorter than its content
+fn truncated<T>(res: Result<T>, what: &str) -> Result<T> {
+    match res {
+        Err(Error::Io(ref err)) if err.kind() == std::io::ErrorKind::UnexpectedEof => {
+            Err(Error::BadFormat(format!("truncated {}", what)))
+        }
+        res => res,
+    }
+}
+
+fn start_section<W: Write + Seek>(out: &mut W, section_type: u32) -> Result<u64> {
+    out.write_u32::<LittleEndian>(section_type)?;
+    let offset = out.seek(SeekFrom::Current(0))?;
+    out.write_u64::<LittleEndian>(0)?;
+    Ok(offset)
+}
+
+fn end_section<W: Write + Seek>(out: &mut W, offset: u64) -> Result<()> {
+    let end = out.seek(SeekFrom::Current(0))?;
+    out.seek(SeekFrom::Start(offset))?;
+    out.write_u64::<LittleEndian>(end - offset - 8)?;
+    out.seek(SeekFrom::Start(end))?;
+    Ok(())
+}
+
+fn write_fs<W: Write>(out: &mut W, n: &BigUint) -> Result<()> {
+    let mut le = n.to_bytes_le();
+    le.resize(N8, 0);
+    out.write_all(&le)?;
+    Ok(())
+}
+
+fn write_lc<W: Write>(out: &mut W, wires: &WireMap, lc: &LC) -> Result<()> {
+    out.write_u32::<LittleEndian>(lc.0.len() as u32)?;
+    for (signal, value) in &lc.0 {
+        out.write_u32::<LittleEndian>(wires.wire(*signal) as u32)?;
+        write_fs(out, &value.0)?;
+    }
+    Ok(())
+}
+
+fn read_header<R: Read>(input: &mut R) -> Result<R1csHeader> {
+    let n8 = input.read_u32::<LittleEndian>()? as usize;
+    if n8 != N8 {
+        return Err(Error::BadFormat(format!("unsupported field size {}", n8)));
+    }
+    let mut prime = [0u8; N8];
+    input.read_exact(&mut prime)?;
+    let prime = BigUint::from_bytes_le(&prime);
+    let field = Field::from_prime(&prime)
+        .ok_or_else(|| Error::BadFormat(format!("unsupported field {}", prime)))?;
+    Ok(R1csHeader {
+        field,
+        n_wires: input.read_u32::<LittleEndian>()?,
+        n_pub_out: input.read_u32::<LittleEndian>()?,
+        n_pub_in: input.read_u32::<LittleEndian>()?,
+        n_prv_in: input.read_u32::<LittleEndian>()?,
+        n_labels: input.read_u64::<LittleEndian>()?,
+        n_constraints: input.read_u32::<LittleEndian>()?,
+    })
+}
+
+fn read_lc<R: Read>(input: &mut R, field: Field) -> Result<LC> {
+    let n_coefs = input.read_u32::<LittleEndian>()?;
+    let mut lc = LC::new();
+    let mut value = [0u8; N8];
+    for _ in 0..n_coefs {
+        let wire = input.read_u32::<LittleEndian>()? as usize;
+        input.read_exact(&mut value)?;
+        lc.0.push((wire, FS::reduce(&BigUint::from_bytes_le(&value), field)));
+    }
+    Ok(lc)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::evaluator::{Evaluator, Mode, Scope};
+    use crate::storage::{Ram, StorageFactory};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_r1cs_roundtrip() -> Result<()> {
+        let mut ram = Ram::default();
+        let mut eval = Evaluator::new(
+            Mode::GenConstraints,
+            ram.new_signals()?,
+            ram.new_constraints()?,
+        );
+        eval.eval_inline(
+            &mut Scope::new(true, None, "root".to_string()),
+            "
+            template t() {
+                signal private input a;
+                signal input b;
+                signal tmp;
+                signal output c;
+                tmp <== a * b;
+                c <== tmp * 2 + 1;
+            }
+            component main = t();
+            ",
+        )
+        .unwrap();
+
+        let mut out = Cursor::new(Vec::new());
+        write_r1cs(&mut out, &eval.signals, &eval.constraints)?;
+        out.set_position(0);
+        let r1cs = read_r1cs(out)?;
+
+        let wires = WireMap::new(&eval.signals)?;
+        assert_eq!(R1csHeader {
+            field: Field::Bn254,
+            n_wires: 5,
+            n_pub_out: 1,
+            n_pub_in: 1,
+            n_prv_in: 1,
+            n_labels: 5,
+            n_constraints: 2,
+        }, r1cs.header);
+
+        // one, main.c, main.b, main.a, main.tmp
+        let labels = (0..5).map(|n| wires.signal(n) as u64).collect::<Vec<_>>();
+        assert_eq!(labels, r1cs.wire_to_label);
+        assert_eq!("main.c", eval.signals.get_by_id(labels[1] as usize)?.unwrap().full_name.to_string());
+
+        let witness = crate::algebra::Witness::from((0..5).map(FS::from).collect::<Vec<_>>());
+        let by_wire = wires.to_wires(&witness);
+        for wire in 0..5 {
+            assert_eq!(FS::from(labels[wire]), by_wire.0[wire]);
+        }
+        assert_eq!(format!("{:?}", witness), format!("{:?}", wires.to_signals(&wires.to_wires(&witness))));
+
+        // constraints are the same, once signals are renamed as wires
+        for (n, qeq) in eval.constraints.iter().enumerate() {
+            let qeq = qeq?;
+            let rename = |lc: &LC| {
+                let mut lc = LC(lc.0.iter().map(|(s, v)| (wires.wire(*s), v.clone())).collect());
+                lc.0.sort_by_key(|(s, _)| *s);
+                lc
+            };
+            let sorted = |lc: &LC| {
+                let mut lc = lc.clone();
+                lc.0.sort_by_key(|(s, _)| *s);
+                lc
+            };
+            let read = &r1cs.constraints[n];
+            assert_eq!(format!("{:?}", rename(&qeq.a)), format!("{:?}", sorted(&read.a)));
+            assert_eq!(format!("{:?}", rename(&qeq.b)), format!("{:?}", sorted(&read.b)));
+            assert_eq!(format!("{:?}", rename(&qeq.c)), format!("{:?}", sorted(&read.c)));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_r1cs_field() -> Result<()> {
+        // the values are read in the field of the file, not in the one of the thread
+        let mut ram = Ram::new(Field::Bls12_381);
+        let mut eval = Evaluator::new(
+            Mode::GenConstraints,
+            ram.new_signals()?,
+            ram.new_constraints()?,
+        );
+        eval.eval_inline(
+            &mut Scope::new(true, None, "root".to_string()),
+            "
+            template t() {
+                signal input a;
+                signal output c;
+                c <== a * a - 1;
+            }
+            component main = t();
+            ",
+        )
+        .unwrap();
+
+        let mut out = Cursor::new(Vec::new());
+        write_r1cs(&mut out, &eval.signals, &eval.constraints)?;
+        out.set_position(0);
+        let r1cs = read_r1cs(out)?;
+        assert_eq!(Field::Bls12_381, r1cs.header.field);
+
+        let values = |lc: &LC| {
+            let mut values = lc.0.iter().map(|(_, v)| v.0.clone()).collect::<Vec<_>>();
+            values.sort();
+            values
+        };
+        let qeq = eval.constraints.get(0)?;
+        assert_eq!(values(&qeq.c), values(&r1cs.constraints[0].c));
+        Ok(())
+    }
+
+    #[test]
+    fn test_r1cs_truncated() {
+        // the header section claims a huge size, then a header that is too short
+        let mut huge = b"r1cs\x01\x00\x00\x00\x01\x00\x00\x00\x01\x00\x00\x00".to_vec();
+        huge.extend_from_slice(&u64::max_value().to_le_bytes());
+        huge.extend_from_slice(&[0u8; 16]);
+        let mut short = b"r1cs\x01\x00\x00\x00\x01\x00\x00\x00\x01\x00\x00\x00".to_vec();
+        short.extend_from_slice(&4u64.to_le_bytes());
+        short.extend_from_slice(&32u32.to_le_bytes());
+
+        for input in vec![huge, short] {
+            match read_r1cs(&input[..]) {
+                Err(Error::BadFormat(_)) => {}
+                _ => panic!("expected bad format"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_r1cs_bad_magic() {
+        match read_r1cs(&b"wtns\x01\x00\x00\x00"[..]) {
+            Err(Error::BadFormat(_)) => {}
+            _ => panic!("expected bad format"),
+        }
+    }
+}
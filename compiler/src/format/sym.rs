@@ -0,0 +1,88 @@
+use std::io::{BufRead, Write};
+
+use super::error::{Error, Result};
+use super::wires::WireMap;
+use crate::storage::Signals;
+
+/// write one `id,wire,component,full_name` line per signal
+pub fn write_sym<W: Write, S: Signals>(mut out: W, signals: &S) -> Result<()> {
+    let wires = WireMap::new(signals)?;
+    for signal in signals.iter() {
+        let signal = signal?;
+        let full_name = signal.full_name.to_string();
+        let component = full_name.rfind('.').map_or("", |pos| &full_name[..pos]);
+        writeln!(
+            out,
+            "{},{},{},{}",
+            signal.id,
+            wires.wire(signal.id),
+            component,
+            full_name
+        )?;
+    }
+    Ok(())
+}
+
+/// a line of a sym file, `wire` is negative for signals removed by optimization
+pub struct SymEntry {
+    pub id: u64,
+    pub wire: i64,
+    pub component: String,
+    pub full_name: String,
+}
+
+/// read a sym file, either ours or the one of the official circom where
+///   the third column is a component index instead of a path
+pub fn read_sym<R: BufRead>(input: R) -> Result<Vec<SymEntry>> {
+    let mut entries = Vec::new();
+    for line in input.lines() {
+        let line = line?;
+        if line.is_empty() {
+            continue;
+        }
+        let fields = line.splitn(4, ',').collect::<Vec<_>>();
+        if fields.len() != 4 {
+            return Err(Error::BadFormat(format!("bad sym line '{}'", line)));
+        }
+        let parse = |field: &str| {
+            field
+                .parse::<i64>()
+                .map_err(|_| Error::BadFormat(format!("bad sym line '{}'", line)))
+        };
+        entries.push(SymEntry {
+            id: parse(fields[0])? as u64,
+            wire: parse(fields[1])?,
+            component: fields[2].to_string(),
+            full_name: fields[3].to_string(),
+        });
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::storage::{Ram, StorageFactory};
+    use circom2_parser::ast::SignalType;
+
+    #[test]
+    fn test_sym() -> Result<()> {
+        let mut signals = Ram::default().new_signals()?;
+        signals.insert("main.c.t".to_string(), SignalType::Internal, None)?;
+        signals.insert("main.out".to_string(), SignalType::Output, None)?;
+        signals.insert("main.in[0]".to_string(), SignalType::PrivateInput, None)?;
+
+        let mut out = Vec::new();
+        write_sym(&mut out, &signals)?;
+        assert_eq!(
+            "0,0,,one\n1,3,main.c,main.c.t\n2,1,main,main.out\n3,2,main,main.in[0]\n",
+            String::from_utf8_lossy(&out)
+        );
+
+        let entries = read_sym(&out[..])?;
+        assert_eq!(4, entries.len());
+        assert_eq!((2, 1), (entries[2].id, entries[2].wire));
+        assert_eq!("main.in[0]", entries[3].full_name);
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+use circom2_parser::ast::SignalType;
+
+use super::error::Result;
+use crate::algebra::{SignalId, Witness, SIGNAL_ONE};
+use crate::storage::Signals;
+
+/// assignment of signals to wires in the order expected by iden3 tools:
+///   one, main outputs, main public inputs, main private inputs, everything else
+pub struct WireMap {
+    pub n_pub_out: usize,
+    pub n_pub_in: usize,
+    pub n_prv_in: usize,
+    wire_to_signal: Vec<SignalId>,
+    signal_to_wire: Vec<usize>,
+}
+
+impl WireMap {
+    pub fn new<S: Signals>(signals: &S) -> Result<Self> {
+        let mut groups: [Vec<SignalId>; 4] = Default::default();
+        for signal in signals.iter_range(SIGNAL_ONE + 1..signals.len()?) {
+            let signal = signal?;
+            let is_main = signal.full_name.0.chars().filter(|ch| *ch == '.').count() == 1;
+            let group = match signal.xtype {
+                SignalType::Output if is_main => 0,
+                SignalType::PublicInput if is_main => 1,
+                SignalType::PrivateInput if is_main => 2,
+                _ => 3,
+            };
+            groups[group].push(signal.id);
+        }
+
+        let mut wire_to_signal = vec![SIGNAL_ONE];
+        for group in groups.iter() {
+            wire_to_signal.extend_from_slice(group);
+        }
+        let mut signal_to_wire = vec![0; wire_to_signal.len()];
+        for (wire, signal) in wire_to_signal.iter().enumerate() {
+            signal_to_wire[*signal] = wire;
+        }
+
+        Ok(WireMap {
+            n_pub_out: groups[0].len(),
+            n_pub_in: groups[1].len(),
+            n_prv_in: groups[2].len(),
+            wire_to_signal,
+            signal_to_wire,
+        })
+    }
+    pub fn len(&self) -> usize {
+        self.wire_to_signal.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.wire_to_signal.is_empty()
+    }
+    pub fn signal(&self, wire: usize) -> SignalId {
+        self.wire_to_signal[wire]
+    }
+    pub fn wire(&self, signal: SignalId) -> usize {
+        self.signal_to_wire[signal]
+    }
+
+    /// witness values indexed by wire instead of by signal
+    pub fn to_wires(&self, witness: &Witness) -> Witness {
+        Witness::from(
+            self.wire_to_signal
+                .iter()
+                .map(|signal| witness.0[*signal].clone())
+                .collect::<Vec<_>>(),
+        )
+    }
+    /// witness values indexed by signal instead of by wire
+    pub fn to_signals(&self, witness: &Witness) -> Witness {
+        Witness::from(
+            self.signal_to_wire
+                .iter()
+                .map(|wire| witness.0[*wire].clone())
+                .collect::<Vec<_>>(),
+        )
+    }
+}
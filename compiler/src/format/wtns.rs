@@ -0,0 +1,175 @@
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use num_bigint::BigUint;
+use std::io::{Read, Write};
+
+use super::error::{Error, Result};
+use crate::algebra::{Field, Witness, FS};
+
+// iden3 binary wtns format
+//
+//   magic "wtns", version : u32, n sections : u32
+//   for each section: type : u32, size : u64, content
+//
+//   1 header : n8 : u32, prime, n witness : u32
+//   2 values : n8 bytes LE for each witness value
+//
+const MAGIC: &[u8; 4] = b"wtns";
+const VERSION: u32 = 2;
+const N8: usize = 32;
+
+const SECTION_HEADER: u32 = 1;
+const SECTION_VALUES: u32 = 2;
+
+/// write the witness of a circuit in `field` in the iden3 binary wtns format
+pub fn write_wtns<W: Write>(mut out: W, field: Field, witness: &Witness) -> Result<()> {
+    out.write_all(MAGIC)?;
+    out.write_u32::<LittleEndian>(VERSION)?;
+    out.write_u32::<LittleEndian>(2)?;
+
+    out.write_u32::<LittleEndian>(SECTION_HEADER)?;
+    out.write_u64::<LittleEndian>((4 + N8 + 4) as u64)?;
+    out.write_u32::<LittleEndian>(N8 as u32)?;
+    write_fs(&mut out, field.prime())?;
+    out.write_u32::<LittleEndian>(witness.len() as u32)?;
+
+    out.write_u32::<LittleEndian>(SECTION_VALUES)?;
+    out.write_u64::<LittleEndian>((N8 * witness.len()) as u64)?;
+    for value in &witness.0 {
+        write_fs(&mut out, &value.0)?;
+    }
+
+    Ok(())
+}
+
+/// read an iden3 binary wtns file, its values have to be in `field`
+pub fn read_wtns<R: Read>(mut input: R, field: Field) -> Result<Witness> {
+    let mut magic = [0u8; 4];
+    input.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(Error::BadFormat("not a wtns file".to_string()));
+    }
+    let version = input.read_u32::<LittleEndian>()?;
+    if version != VERSION {
+        return Err(Error::BadFormat(format!("unsupported wtns version {}", version)));
+    }
+
+    let mut n_witness = None;
+    let mut values = None;
+
+    let n_sections = input.read_u32::<LittleEndian>()?;
+    for _ in 0..n_sections {
+        let section_type = input.read_u32::<LittleEndian>()?;
+        let size = input.read_u64::<LittleEndian>()?;
+        let content = read_section(&mut input, size)?;
+        let mut content = &content[..];
+        match section_type {
+            SECTION_HEADER => {
+                if content.len() < 4 + N8 + 4 {
+                    return Err(Error::BadFormat("truncated wtns header".to_string()));
+                }
+                let n8 = content.read_u32::<LittleEndian>()? as usize;
+                if n8 != N8 {
+                    return Err(Error::BadFormat(format!("unsupported field size {}", n8)));
+                }
+                let prime = BigUint::from_bytes_le(&content[..N8]);
+                if prime != *field.prime() {
+                    return Err(Error::BadFormat(format!("unsupported field {}", prime)));
+                }
+                content = &content[N8..];
+                n_witness = Some(content.read_u32::<LittleEndian>()? as usize);
+            }
+            SECTION_VALUES => {
+                values = Some(
+                    content
+                        .chunks(N8)
+                        .map(|value| FS::reduce(&BigUint::from_bytes_le(value), field))
+                        .collect::<Vec<_>>(),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    match (n_witness, values) {
+        (Some(n_witness), Some(values)) if n_witness == values.len() => Ok(Witness::from(values)),
+        (Some(_), Some(_)) => Err(Error::BadFormat("witness length mismatch".to_string())),
+        _ => Err(Error::BadFormat("missing wtns section".to_string())),
+    }
+}
+
+/// write the witness as a json array of decimal strings
+pub fn write_witness_json<W: Write>(mut out: W, witness: &Witness) -> Result<()> {
+    writeln!(out, "[")?;
+    for (n, value) in witness.0.iter().enumerate() {
+        let sep = if n + 1 < witness.len() { "," } else { "" };
+        writeln!(out, " \"{}\"{}", value.0, sep)?;
+    }
+    writeln!(out, "]")?;
+    Ok(())
+}
+
+/// content of a section, its size comes from the file so it is only trusted as
+///   far as there is input to back it
+fn read_section<R: Read>(input: &mut R, size: u64) -> Result<Vec<u8>> {
+    let mut content = Vec::new();
+    input.take(size).read_to_end(&mut content)?;
+    if content.len() as u64 != size {
+        return Err(Error::BadFormat("truncated wtns section".to_string()));
+    }
+    Ok(content)
+}
+
+fn write_fs<W: Write>(out: &mut W, n: &BigUint) -> Result<()> {
+    let mut le = n.to_bytes_le();
+    le.resize(N8, 0);
+    out.write_all(&le)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_wtns_roundtrip() -> Result<()> {
+        let witness = Witness::from(vec![FS::one(), FS::from(21), FS::one().neg(Field::Bn254)]);
+
+        let mut out = Vec::new();
+        write_wtns(&mut out, Field::Bn254, &witness)?;
+        assert_eq!(4 + 4 + 4 + (4 + 8 + 40) + (4 + 8 + 3 * 32), out.len());
+        assert_eq!(format!("{:?}", witness), format!("{:?}", read_wtns(&out[..], Field::Bn254)?));
+        match read_wtns(&out[..], Field::Bls12_381) {
+            Err(Error::BadFormat(_)) => {}
+            _ => panic!("expected a field mismatch"),
+        }
+
+        let mut json = Vec::new();
+        write_witness_json(&mut json, &witness)?;
+        assert_eq!(
+            format!(
+                "[\n \"1\",\n \"21\",\n \"{}\"\n]\n",
+                Field::Bn254.prime() - &BigUint::from(1u32)
+            ),
+            String::from_utf8_lossy(&json)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_wtns_truncated() {
+        let section = |size: u64, content: &[u8]| {
+            let mut input = b"wtns\x02\x00\x00\x00\x01\x00\x00\x00\x01\x00\x00\x00".to_vec();
+            input.extend_from_slice(&size.to_le_bytes());
+            input.extend_from_slice(content);
+            input
+        };
+        // a huge section size, and a header shorter than n8, prime and n witness
+        for input in vec![section(u64::max_value(), &[0u8; 8]), section(4, &32u32.to_le_bytes())] {
+            match read_wtns(&input[..], Field::Bn254) {
+                Err(Error::BadFormat(_)) => {}
+                _ => panic!("expected bad format"),
+            }
+        }
+    }
+}
@@ -7,14 +7,19 @@ extern crate blake2_rfc;
 extern crate hex;
 extern crate serde;
 extern crate serde_cbor;
+extern crate serde_json;
 #[macro_use]
 extern crate serde_derive;
 #[macro_use]
 extern crate log;
 extern crate rand;
 extern crate itertools;
+extern crate rayon;
+extern crate memmap;
 
 pub mod algebra;
 pub mod evaluator;
+pub mod format;
+pub mod progress;
 pub mod storage;
 pub mod tester;
@@ -0,0 +1,144 @@
+use std::fmt;
+use std::time::Instant;
+
+/// long running steps reported to a `Progress`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    /// evaluating the circuit, counts constraints
+    Constraints,
+    /// evaluating the circuit with its inputs, counts signals
+    Witness,
+    /// reading a proving key
+    LoadKey,
+    /// computing the proving and verifying keys
+    Setup,
+    /// computing a proof, counts constraints
+    Prove,
+    /// checking the created proof
+    Verify,
+    /// writing the keys, counts constraints
+    WriteKeys,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Phase::Constraints => "constraints",
+            Phase::Witness => "witness",
+            Phase::LoadKey => "load key",
+            Phase::Setup => "setup",
+            Phase::Prove => "prove",
+            Phase::Verify => "verify",
+            Phase::WriteKeys => "write keys",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// receives the progress of the evaluator, the setup and the prover, every
+///   method does nothing by default
+pub trait Progress {
+    /// `phase` begins, `total` is its number of items if known
+    fn start(&mut self, _phase: Phase, _total: Option<usize>) {}
+    /// `done` items of `phase` are processed, at `rate` items per second
+    fn update(&mut self, _phase: Phase, _done: usize, _rate: f64) {}
+    /// `phase` ended after processing `done` items
+    fn finish(&mut self, _phase: Phase, _done: usize) {}
+}
+
+impl<'a> fmt::Debug for dyn Progress + 'a {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Progress")
+    }
+}
+
+/// forwards to the borrowed progress, to lend it to an evaluator
+impl<P: Progress + ?Sized> Progress for &mut P {
+    fn start(&mut self, phase: Phase, total: Option<usize>) {
+        (**self).start(phase, total)
+    }
+    fn update(&mut self, phase: Phase, done: usize, rate: f64) {
+        (**self).update(phase, done, rate)
+    }
+    fn finish(&mut self, phase: Phase, done: usize) {
+        (**self).finish(phase, done)
+    }
+}
+
+/// discards the progress
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoProgress;
+
+impl Progress for NoProgress {}
+
+/// counts the items of a phase, reporting every `every` items with the rate
+///   since the previous report
+#[derive(Debug)]
+pub struct Counter {
+    phase: Phase,
+    every: usize,
+    done: usize,
+    last: Instant,
+}
+
+impl Counter {
+    pub fn new(phase: Phase, every: usize) -> Self {
+        Counter {
+            phase,
+            every,
+            done: 0,
+            last: Instant::now(),
+        }
+    }
+
+    /// set the items processed so far
+    pub fn set(&mut self, done: usize, progress: &mut dyn Progress) {
+        let reports = done / self.every > self.done / self.every;
+        self.done = done;
+        if reports {
+            let now = Instant::now();
+            let millis = now.duration_since(self.last).as_millis();
+            let rate = if millis > 0 { (self.every * 1000) as f64 / millis as f64 } else { 0.0 };
+            progress.update(self.phase, done, rate);
+            self.last = now;
+        }
+    }
+
+    /// one more item processed
+    pub fn inc(&mut self, progress: &mut dyn Progress) {
+        let done = self.done + 1;
+        self.set(done, progress);
+    }
+
+    pub fn done(&self) -> usize {
+        self.done
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder(Vec<(Phase, usize)>);
+
+    impl Progress for Recorder {
+        fn update(&mut self, phase: Phase, done: usize, _rate: f64) {
+            self.0.push((phase, done));
+        }
+    }
+
+    #[test]
+    fn test_counter_reports_every() {
+        let mut recorder = Recorder::default();
+        let mut counter = Counter::new(Phase::Prove, 3);
+        for _ in 0..7 {
+            counter.inc(&mut recorder);
+        }
+        assert_eq!(7, counter.done());
+        assert_eq!(vec![(Phase::Prove, 3), (Phase::Prove, 6)], recorder.0);
+
+        counter.set(13, &mut recorder);
+        assert_eq!((Phase::Prove, 13), recorder.0[2]);
+    }
+}
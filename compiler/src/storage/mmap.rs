@@ -0,0 +1,760 @@
+use circom2_parser::ast::SignalType;
+use std::fs::{File, OpenOptions};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use byteorder::{ByteOrder, LittleEndian};
+use memmap::MmapMut;
+use num_bigint::BigUint;
+use serde_cbor::{from_slice, to_vec};
+
+use super::error::{Error, Result};
+use super::types::*;
+use super::StorageFactory;
+use crate::algebra;
+use crate::algebra::{Field, SignalId, FS, QEQ};
+
+const INITIAL_CAPACITY: u64 = 1 << 20;
+
+// signal record layout
+//
+//   xtype       : u8
+//   value tag   : u8   (none, field scalar or symbolic)
+//   padding     : 6 bytes
+//   name offset : u64
+//   name len    : u64
+//   value       : 32 bytes, field scalar in little endian or
+//                 (offset: u64, len: u64) of the cbor encoded value
+//
+const SIGNAL_RECORD_SIZE: usize = 56;
+const VALUE_NONE: u8 = 0;
+const VALUE_FIELD_SCALAR: u8 = 1;
+const VALUE_SYMBOLIC: u8 = 2;
+
+// name index layout
+//
+//   an open addressing hash table of (name hash: u64, id + 1: u64) slots,
+//   a zero id marks an empty slot, the table doubles when half full
+//
+// plus a prefix index with the u64 ids sorted by name, rebuilt when the
+//   signals are dropped, signals inserted after it are scanned
+//
+const INDEX_SLOT_SIZE: usize = 16;
+
+pub struct Mmap {
+    base_path: String,
+    field: Field,
+    count: usize,
+}
+
+impl Mmap {
+    pub fn new(base_path: String, field: Field) -> Mmap {
+        Mmap {
+            base_path,
+            field,
+            count: 0,
+        }
+    }
+    fn next_path(&mut self, kind: &str) -> Result<PathBuf> {
+        let mut full_path = PathBuf::new();
+        full_path.push(&self.base_path);
+        full_path.push(format!("_{}_{}", kind, self.count));
+        self.count += 1;
+        std::fs::create_dir_all(&full_path)?;
+        Ok(full_path)
+    }
+}
+
+impl Mmap {
+    /// open the last signals and constraints stored under `base_path`
+    pub fn open(base_path: &str) -> Result<(MmapSignals, MmapConstraints)> {
+        let last = |kind: &str| -> Result<PathBuf> {
+            let prefix = format!("_{}_", kind);
+            let mut last: Option<(usize, PathBuf)> = None;
+            for entry in std::fs::read_dir(base_path)? {
+                let path = entry?.path();
+                let count = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .filter(|name| name.starts_with(&prefix))
+                    .and_then(|name| name[prefix.len()..].parse::<usize>().ok());
+                if let Some(count) = count {
+                    if last.as_ref().map_or(true, |(n, _)| count > *n) {
+                        last = Some((count, path));
+                    }
+                }
+            }
+            last.map(|(_, path)| path)
+                .ok_or_else(|| Error::NotFound(format!("{} in {}", kind, base_path)))
+        };
+        Ok((
+            MmapSignals::open(&last("signals")?)?,
+            MmapConstraints::open(&last("constraints")?)?,
+        ))
+    }
+}
+
+impl StorageFactory<MmapSignals, MmapConstraints> for Mmap {
+    fn new_signals(&mut self) -> Result<MmapSignals> {
+        MmapSignals::new(&self.next_path("signals")?, self.field)
+    }
+    fn new_constraints(&mut self) -> Result<MmapConstraints> {
+        MmapConstraints::new(&self.next_path("constraints")?)
+    }
+}
+
+/// an append-only file read and written through a memory map, the file
+///   grows by doubling and is truncated to its used size when dropped
+struct MappedFile {
+    file: File,
+    map: MmapMut,
+    len: u64,
+}
+
+impl MappedFile {
+    fn create(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        file.set_len(INITIAL_CAPACITY)?;
+        let map = unsafe { MmapMut::map_mut(&file)? };
+        Ok(MappedFile { file, map, len: 0 })
+    }
+    /// a file of `size` zeroed bytes, all of them in use
+    fn create_table(path: &Path, size: u64) -> Result<Self> {
+        let mut table = Self::create(path)?;
+        table.reserve(size)?;
+        table.len = size;
+        Ok(table)
+    }
+    fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        let len = file.metadata()?.len();
+        file.set_len(len.max(INITIAL_CAPACITY))?;
+        let map = unsafe { MmapMut::map_mut(&file)? };
+        Ok(MappedFile { file, map, len })
+    }
+    fn reserve(&mut self, additional: u64) -> Result<()> {
+        let needed = self.len + additional;
+        let mut capacity = self.map.len() as u64;
+        if needed > capacity {
+            while capacity < needed {
+                capacity *= 2;
+            }
+            self.map.flush()?;
+            self.file.set_len(capacity)?;
+            self.map = unsafe { MmapMut::map_mut(&self.file)? };
+        }
+        Ok(())
+    }
+    fn flush(&self) -> Result<()> {
+        self.map.flush()?;
+        Ok(())
+    }
+    fn append(&mut self, bytes: &[u8]) -> Result<u64> {
+        self.reserve(bytes.len() as u64)?;
+        let offset = self.len;
+        self.bytes_mut(offset, bytes.len()).copy_from_slice(bytes);
+        self.len += bytes.len() as u64;
+        Ok(offset)
+    }
+    fn bytes(&self, offset: u64, len: usize) -> &[u8] {
+        &self.map[offset as usize..offset as usize + len]
+    }
+    fn bytes_mut(&mut self, offset: u64, len: usize) -> &mut [u8] {
+        &mut self.map[offset as usize..offset as usize + len]
+    }
+}
+
+impl Drop for MappedFile {
+    fn drop(&mut self) {
+        let _ = self.map.flush();
+        let _ = self.file.set_len(self.len);
+    }
+}
+
+pub struct MmapSignals {
+    path: PathBuf,
+    field: Field,
+    records: MappedFile,
+    names: MappedFile,
+    values: MappedFile,
+    index: MappedFile,
+    sorted: MappedFile,
+}
+
+impl MmapSignals {
+    pub fn new(path: &Path, field: Field) -> Result<Self> {
+        std::fs::write(path.join("field"), field.prime().to_string())?;
+        let mut signals = MmapSignals {
+            path: path.to_path_buf(),
+            field,
+            records: MappedFile::create(&path.join("records"))?,
+            names: MappedFile::create(&path.join("names"))?,
+            values: MappedFile::create(&path.join("values"))?,
+            index: MappedFile::create_table(&path.join("index"), INITIAL_CAPACITY)?,
+            sorted: MappedFile::create(&path.join("sorted"))?,
+        };
+        signals.insert("one".to_string(), SignalType::PublicInput, None)?;
+        Ok(signals)
+    }
+
+    /// open signals previously stored in `path`
+    pub fn open(path: &Path) -> Result<Self> {
+        let prime = std::fs::read_to_string(path.join("field"))?;
+        let field = BigUint::parse_bytes(prime.as_bytes(), 10)
+            .and_then(|prime| Field::from_prime(&prime))
+            .ok_or_else(|| Error::Inner(format!("unsupported field {} in {:?}", prime, path)))?;
+        let signals = MmapSignals {
+            path: path.to_path_buf(),
+            field,
+            records: MappedFile::open(&path.join("records"))?,
+            names: MappedFile::open(&path.join("names"))?,
+            values: MappedFile::open(&path.join("values"))?,
+            index: MappedFile::open(&path.join("index"))?,
+            sorted: MappedFile::open(&path.join("sorted"))?,
+        };
+        if signals.records.len as usize % SIGNAL_RECORD_SIZE != 0
+            || signals.index.len == 0
+            || signals.index.len as usize % INDEX_SLOT_SIZE != 0
+        {
+            return Err(Error::Inner(format!("bad signals files in {:?}", path)));
+        }
+        Ok(signals)
+    }
+
+    /// sort the prefix index with the signals inserted since it was last sorted
+    ///   and write the files. Signals that are not finished are still readable,
+    ///   the prefix lookups scan the unsorted ones
+    pub fn finish(&mut self) -> Result<()> {
+        if self.sorted_len() != self.len()? {
+            self.sort_index()?;
+        }
+        for file in &[&self.records, &self.names, &self.values, &self.index, &self.sorted] {
+            file.flush()?;
+        }
+        Ok(())
+    }
+
+    fn record(&self, id: SignalId) -> &[u8] {
+        record(&self.records, id)
+    }
+
+    fn name(&self, record: &[u8]) -> Result<String> {
+        String::from_utf8(name(&self.names, record).to_vec())
+            .map_err(|err| Error::Inner(err.to_string()))
+    }
+
+    fn slots(&self) -> usize {
+        self.index.len as usize / INDEX_SLOT_SIZE
+    }
+
+    fn index_insert(&mut self, hash: u64, id: SignalId) -> Result<()> {
+        if 2 * (id + 1) > self.slots() {
+            self.index_grow()?;
+        }
+        table_insert(&mut self.index, hash, id);
+        Ok(())
+    }
+
+    fn index_grow(&mut self) -> Result<()> {
+        let tmp_path = self.path.join("index.new");
+        let mut grown = MappedFile::create_table(&tmp_path, 2 * self.index.len)?;
+        for slot in 0..self.slots() {
+            let entry = self.index.bytes((slot * INDEX_SLOT_SIZE) as u64, INDEX_SLOT_SIZE);
+            let id = LittleEndian::read_u64(&entry[8..16]);
+            if id != 0 {
+                table_insert(&mut grown, LittleEndian::read_u64(&entry[0..8]), id as usize - 1);
+            }
+        }
+        grown.map.flush()?;
+        std::fs::rename(&tmp_path, self.path.join("index"))?;
+        self.index = grown;
+        Ok(())
+    }
+
+    fn sorted_len(&self) -> usize {
+        self.sorted.len as usize / 8
+    }
+
+    fn sorted_id(&self, n: usize) -> SignalId {
+        LittleEndian::read_u64(self.sorted.bytes((n * 8) as u64, 8)) as SignalId
+    }
+
+    /// rebuild the prefix index with every signal, sorting it in place
+    fn sort_index(&mut self) -> Result<()> {
+        let len = self.len()?;
+        self.sorted.len = 0;
+        self.sorted.reserve((len * 8) as u64)?;
+        self.sorted.len = (len * 8) as u64;
+
+        let ids = unsafe {
+            std::slice::from_raw_parts_mut(self.sorted.map.as_mut_ptr() as *mut u64, len)
+        };
+        for (id, slot) in ids.iter_mut().enumerate() {
+            *slot = (id as u64).to_le();
+        }
+        let (records, names) = (&self.records, &self.names);
+        ids.sort_unstable_by(|a, b| {
+            let a = name(names, record(records, u64::from_le(*a) as usize));
+            let b = name(names, record(records, u64::from_le(*b) as usize));
+            a.cmp(b)
+        });
+        Ok(())
+    }
+
+    fn load(&self, id: SignalId) -> Result<Option<Signal>> {
+        if id >= self.len()? {
+            return Ok(None);
+        }
+        let record = self.record(id);
+        let value = match record[1] {
+            VALUE_NONE => None,
+            // stored reduced, in the field of the signals
+            VALUE_FIELD_SCALAR => Some(algebra::Value::from(FS(BigUint::from_bytes_le(
+                &record[24..56],
+            )))),
+            VALUE_SYMBOLIC => {
+                let offset = LittleEndian::read_u64(&record[24..32]);
+                let len = LittleEndian::read_u64(&record[32..40]) as usize;
+                Some(from_slice::<algebra::Value>(self.values.bytes(offset, len))?)
+            }
+            tag => return Err(Error::Inner(format!("bad value tag {} in signal {}", tag, id))),
+        };
+        Ok(Some(Signal {
+            id,
+            xtype: decode_xtype(record[0])?,
+            full_name: SignalName::new(self.name(record)?),
+            value,
+        }))
+    }
+
+    fn encode_value(&mut self, value: Option<&algebra::Value>, slot: &mut [u8]) -> Result<u8> {
+        match value {
+            None => Ok(VALUE_NONE),
+            Some(algebra::Value::FieldScalar(fs)) => {
+                let le = fs.0.to_bytes_le();
+                slot[..le.len()].copy_from_slice(&le);
+                Ok(VALUE_FIELD_SCALAR)
+            }
+            Some(value) => {
+                let cbor = to_vec(value)?;
+                let offset = self.values.append(&cbor)?;
+                LittleEndian::write_u64(&mut slot[0..8], offset);
+                LittleEndian::write_u64(&mut slot[8..16], cbor.len() as u64);
+                Ok(VALUE_SYMBOLIC)
+            }
+        }
+    }
+}
+
+impl Signals for MmapSignals {
+    fn field(&self) -> Field {
+        self.field
+    }
+
+    fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    fn len(&self) -> Result<usize> {
+        Ok(self.records.len as usize / SIGNAL_RECORD_SIZE)
+    }
+
+    fn insert(
+        &mut self,
+        full_name: String,
+        xtype: SignalType,
+        value: Option<algebra::Value>,
+    ) -> Result<SignalId> {
+        let id = self.len()?;
+        let name_offset = self.names.append(full_name.as_bytes())?;
+
+        let mut record = [0u8; SIGNAL_RECORD_SIZE];
+        record[0] = encode_xtype(xtype);
+        record[1] = self.encode_value(value.as_ref(), &mut record[24..56])?;
+        LittleEndian::write_u64(&mut record[8..16], name_offset);
+        LittleEndian::write_u64(&mut record[16..24], full_name.len() as u64);
+        self.records.append(&record)?;
+        self.index_insert(name_hash(&full_name), id)?;
+
+        Ok(id)
+    }
+
+    fn update(&mut self, id: SignalId, value: algebra::Value) -> Result<()> {
+        if id >= self.len()? {
+            return Err(Error::NotFound(format!("signal {}", id)));
+        }
+        let mut slot = [0u8; 32];
+        let tag = self.encode_value(Some(&value), &mut slot)?;
+        let record = self
+            .records
+            .bytes_mut((id * SIGNAL_RECORD_SIZE) as u64, SIGNAL_RECORD_SIZE);
+        record[1] = tag;
+        record[24..56].copy_from_slice(&slot);
+        Ok(())
+    }
+
+    fn get_by_id(&self, id: SignalId) -> Result<Option<Arc<Signal>>> {
+        Ok(self.load(id)?.map(Arc::new))
+    }
+
+    fn get_by_name(&self, full_name: &str) -> Result<Option<Arc<Signal>>> {
+        let hash = name_hash(full_name);
+        let mask = self.slots() - 1;
+        let mut slot = hash as usize & mask;
+        loop {
+            let entry = self.index.bytes((slot * INDEX_SLOT_SIZE) as u64, INDEX_SLOT_SIZE);
+            let id = LittleEndian::read_u64(&entry[8..16]) as usize;
+            if id == 0 {
+                return Ok(None);
+            }
+            if LittleEndian::read_u64(&entry[0..8]) == hash
+                && name(&self.names, self.record(id - 1)) == full_name.as_bytes()
+            {
+                return self.get_by_id(id - 1);
+            }
+            slot = (slot + 1) & mask;
+        }
+    }
+
+    fn to_string(&self, id: SignalId) -> Result<String> {
+        let s = self
+            .load(id)?
+            .ok_or_else(|| Error::NotFound(format!("signal {}", id)))?;
+        Ok(format!("{:?}:{:?}:{:?}", s.full_name, s.xtype, s.value))
+    }
+
+    fn iter_range(&self, range: Range<SignalId>) -> SignalIter {
+        let len = self.records.len as usize / SIGNAL_RECORD_SIZE;
+        Box::new(
+            (range.start..range.end.min(len))
+                .map(move |id| Ok(Arc::new(self.load(id)?.unwrap()))),
+        )
+    }
+
+    fn get_by_prefix(&self, component_name: &str) -> Result<Vec<Arc<Signal>>> {
+        let prefix = format!("{}.", component_name);
+        let prefix = prefix.as_bytes();
+        let name_of = |n| name(&self.names, self.record(self.sorted_id(n)));
+
+        // binary search the sorted ids, then scan the ones inserted after sorting
+        let (mut lo, mut hi) = (0, self.sorted_len());
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if name_of(mid) < prefix {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        let mut ids = (lo..self.sorted_len())
+            .take_while(|n| name_of(*n).starts_with(prefix))
+            .map(|n| self.sorted_id(n))
+            .collect::<Vec<_>>();
+        ids.extend(
+            (self.sorted_len().min(self.len()?)..self.len()?)
+                .filter(|id| name(&self.names, self.record(*id)).starts_with(prefix)),
+        );
+        ids.sort();
+
+        ids.into_iter()
+            .map(|id| Ok(Arc::new(self.load(id)?.unwrap())))
+            .collect()
+    }
+}
+
+// constraint record layout
+//
+//   qeq len   : u32
+//   qeq       : cbor encoded QEQ
+//   debug len : u32   (u32::MAX if there is no debug info)
+//   debug     : utf8 string
+//
+// plus an offsets file with the u64 start of each record
+//
+pub struct MmapConstraints {
+    data: MappedFile,
+    offsets: MappedFile,
+}
+
+impl MmapConstraints {
+    pub fn new(path: &Path) -> Result<Self> {
+        Ok(MmapConstraints {
+            data: MappedFile::create(&path.join("constraints"))?,
+            offsets: MappedFile::create(&path.join("offsets"))?,
+        })
+    }
+
+    /// open constraints previously stored in `path`
+    pub fn open(path: &Path) -> Result<Self> {
+        let constraints = MmapConstraints {
+            data: MappedFile::open(&path.join("constraints"))?,
+            offsets: MappedFile::open(&path.join("offsets"))?,
+        };
+        if constraints.offsets.len % 8 != 0 {
+            return Err(Error::Inner(format!("bad constraints files in {:?}", path)));
+        }
+        Ok(constraints)
+    }
+
+    fn offset(&self, i: usize) -> Result<u64> {
+        if i >= self.len()? {
+            return Err(Error::NotFound(format!("Constraint at index {}", i)));
+        }
+        Ok(LittleEndian::read_u64(self.offsets.bytes((i * 8) as u64, 8)))
+    }
+}
+
+impl Constraints for MmapConstraints {
+    fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
+    }
+    fn len(&self) -> Result<usize> {
+        Ok(self.offsets.len as usize / 8)
+    }
+    fn get(&self, i: usize) -> Result<QEQ> {
+        let offset = self.offset(i)?;
+        let len = LittleEndian::read_u32(self.data.bytes(offset, 4)) as usize;
+        Ok(from_slice::<QEQ>(self.data.bytes(offset + 4, len))?)
+    }
+    fn get_debug(&self, i: usize) -> Option<String> {
+        let offset = self.offset(i).ok()?;
+        let len = LittleEndian::read_u32(self.data.bytes(offset, 4)) as u64;
+        let debug_offset = offset + 4 + len;
+        let debug_len = LittleEndian::read_u32(self.data.bytes(debug_offset, 4));
+        if debug_len == u32::max_value() {
+            None
+        } else {
+            let debug = self.data.bytes(debug_offset + 4, debug_len as usize);
+            Some(String::from_utf8_lossy(debug).to_string())
+        }
+    }
+    fn push(&mut self, qeq: QEQ, debug: Option<String>) -> Result<usize> {
+        let index = self.len()?;
+        let qeq = to_vec(&qeq)?;
+
+        let mut record = Vec::with_capacity(qeq.len() + 8);
+        let mut len = [0u8; 4];
+        LittleEndian::write_u32(&mut len, qeq.len() as u32);
+        record.extend_from_slice(&len);
+        record.extend_from_slice(&qeq);
+        match debug {
+            Some(debug) => {
+                LittleEndian::write_u32(&mut len, debug.len() as u32);
+                record.extend_from_slice(&len);
+                record.extend_from_slice(debug.as_bytes());
+            }
+            None => {
+                LittleEndian::write_u32(&mut len, u32::max_value());
+                record.extend_from_slice(&len);
+            }
+        }
+
+        let offset = self.data.append(&record)?;
+        let mut offset_bytes = [0u8; 8];
+        LittleEndian::write_u64(&mut offset_bytes, offset);
+        self.offsets.append(&offset_bytes)?;
+
+        Ok(index)
+    }
+    fn push_batch(&mut self, batch: Vec<(QEQ, Option<String>)>) -> Result<usize> {
+        let first = self.len()?;
+        for (qeq, debug) in batch {
+            self.push(qeq, debug)?;
+        }
+        Ok(first)
+    }
+    fn iter_range(&self, range: Range<usize>) -> ConstraintIter {
+        let len = self.offsets.len as usize / 8;
+        Box::new((range.start..range.end.min(len)).map(move |i| self.get(i)))
+    }
+}
+
+fn encode_xtype(xtype: SignalType) -> u8 {
+    match xtype {
+        SignalType::Output => 0,
+        SignalType::PublicInput => 1,
+        SignalType::PrivateInput => 2,
+        SignalType::Internal => 3,
+    }
+}
+
+fn decode_xtype(v: u8) -> Result<SignalType> {
+    match v {
+        0 => Ok(SignalType::Output),
+        1 => Ok(SignalType::PublicInput),
+        2 => Ok(SignalType::PrivateInput),
+        3 => Ok(SignalType::Internal),
+        _ => Err(Error::Inner(format!("bad signal type {}", v))),
+    }
+}
+
+fn record(records: &MappedFile, id: SignalId) -> &[u8] {
+    records.bytes((id * SIGNAL_RECORD_SIZE) as u64, SIGNAL_RECORD_SIZE)
+}
+
+fn name<'a>(names: &'a MappedFile, record: &[u8]) -> &'a [u8] {
+    let offset = LittleEndian::read_u64(&record[8..16]);
+    let len = LittleEndian::read_u64(&record[16..24]) as usize;
+    names.bytes(offset, len)
+}
+
+fn table_insert(table: &mut MappedFile, hash: u64, id: SignalId) {
+    let mask = table.len as usize / INDEX_SLOT_SIZE - 1;
+    let mut slot = hash as usize & mask;
+    loop {
+        let entry = table.bytes_mut((slot * INDEX_SLOT_SIZE) as u64, INDEX_SLOT_SIZE);
+        if LittleEndian::read_u64(&entry[8..16]) == 0 {
+            LittleEndian::write_u64(&mut entry[0..8], hash);
+            LittleEndian::write_u64(&mut entry[8..16], id as u64 + 1);
+            return;
+        }
+        slot = (slot + 1) & mask;
+    }
+}
+
+// FNV-1a, the index is persisted so the hash must not change between builds
+fn name_hash(name: &str) -> u64 {
+    name.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::algebra::{Value, LC};
+
+    fn init() -> Mmap {
+        let mut path = std::env::temp_dir();
+        path.push(format!(
+            "circom2_mmap_{}_{}",
+            std::process::id(),
+            name_hash(&format!("{:?}", std::time::Instant::now()))
+        ));
+        Mmap::new(path.to_str().expect("bad OS filename").to_string(), Field::Bn254)
+    }
+
+    #[test]
+    fn test_mmap_signals() -> Result<()> {
+        let one = FS::one();
+        let two = FS::from(2);
+        let three = FS::from(3);
+
+        let mut signals = init().new_signals()?;
+        assert_eq!(1, signals.len()?);
+
+        signals.insert("main.s1".to_string(), SignalType::Internal, Some(Value::from(one)))?;
+        signals.insert("main.s2".to_string(), SignalType::Output, Some(Value::from(two)))?;
+        signals.insert("main.c.s3".to_string(), SignalType::PrivateInput, None)?;
+        assert_eq!(4, signals.len()?);
+
+        let s1 = signals.get_by_name("main.s1")?.unwrap();
+        assert_eq!("Some(1)", format!("{:?}", s1.value));
+
+        let s2 = signals.get_by_name("main.s2")?.unwrap();
+        assert_eq!(SignalType::Output, s2.xtype);
+        assert_eq!("Some(2)", format!("{:?}", s2.value));
+
+        let s3 = signals.get_by_name("main.c.s3")?.unwrap();
+        assert_eq!("main.c.s3", s3.full_name.to_string());
+        assert!(s3.value.is_none());
+
+        signals.update(s3.id, Value::from(three))?;
+        let s3 = signals.get_by_id(s3.id)?.unwrap();
+        assert_eq!("Some(3)", format!("{:?}", s3.value));
+
+        signals.update(s1.id, Value::from(LC::from_signal(2, FS::one())))?;
+        let s1 = signals.get_by_id(s1.id)?.unwrap();
+        assert_eq!("Some(1s2)", format!("{:?}", s1.value));
+
+        assert!(signals.get_by_name("main.s4")?.is_none());
+        assert!(signals.get_by_id(4)?.is_none());
+
+        let prefixed = signals.get_by_prefix("main.c")?;
+        assert_eq!(1, prefixed.len());
+        assert_eq!(3, prefixed[0].id);
+        assert_eq!(2, signals.iter_range(1..3).count());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mmap_signals_grow() -> Result<()> {
+        let mut signals = init().new_signals()?;
+        let count = 2 * INITIAL_CAPACITY as usize / SIGNAL_RECORD_SIZE;
+        for n in 0..count {
+            signals.insert(format!("main.s{}", n), SignalType::Internal, Some(Value::from(n as u64)))?;
+        }
+        assert_eq!(count + 1, signals.len()?);
+
+        let last = signals.get_by_name(&format!("main.s{}", count - 1))?.unwrap();
+        assert_eq!(format!("Some({})", count - 1), format!("{:?}", last.value));
+        Ok(())
+    }
+
+    #[test]
+    fn test_mmap_open() -> Result<()> {
+        let mut storage = init();
+        let base_path = storage.base_path.clone();
+        {
+            let mut signals = storage.new_signals()?;
+            let mut constraints = storage.new_constraints()?;
+            for n in 0..3 {
+                signals.insert(format!("main.c.s{}", n), SignalType::Internal, Some(Value::from(n as u64)))?;
+            }
+            signals.insert("main.s".to_string(), SignalType::Output, None)?;
+            constraints.push(QEQ::from(&FS::one()), Some("file:1".to_string()))?;
+            signals.finish()?;
+        }
+
+        let (mut signals, constraints) = Mmap::open(&base_path)?;
+        assert_eq!(Field::Bn254, signals.field());
+        assert_eq!(5, signals.len()?);
+        assert_eq!(1, constraints.len()?);
+        assert_eq!(Some("file:1".to_string()), constraints.get_debug(0));
+
+        let s1 = signals.get_by_name("main.c.s1")?.unwrap();
+        assert_eq!("Some(1)", format!("{:?}", s1.value));
+
+        signals.insert("main.c.s3".to_string(), SignalType::Internal, None)?;
+        let prefixed = signals
+            .get_by_prefix("main.c")?
+            .iter()
+            .map(|s| s.id)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![1, 2, 3, 5], prefixed);
+        assert!(signals.get_by_name("main.c.s3")?.is_some());
+        assert!(signals.get_by_name("main.c")?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_mmap_constraints() -> Result<()> {
+        let one = QEQ::from(&FS::one());
+        let two = QEQ::from(&FS::from(2));
+
+        let mut constraints = init().new_constraints()?;
+        assert_eq!(0, constraints.len()?);
+
+        let c1 = constraints.push(one.clone(), None)?;
+        let c2 = constraints.push(two, Some("file:10".to_string()))?;
+        let c3 = constraints.push_batch(vec![(one, None)])?;
+
+        assert_eq!(3, constraints.len()?);
+        assert_eq!("[ ]*[ ]+[1s0]", format!("{:?}", constraints.get(c1)?));
+        assert_eq!("[ ]*[ ]+[2s0]", format!("{:?}", constraints.get(c2)?));
+        assert_eq!("[ ]*[ ]+[1s0]", format!("{:?}", constraints.get(c3)?));
+        assert_eq!(None, constraints.get_debug(c1));
+        assert_eq!(Some("file:10".to_string()), constraints.get_debug(c2));
+        assert!(constraints.get(3).is_err());
+        assert_eq!(3, constraints.iter().count());
+
+        Ok(())
+    }
+}
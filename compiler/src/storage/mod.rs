@@ -1,10 +1,12 @@
 mod error;
 mod factory;
+mod mmap;
 mod ram;
 mod types;
 mod utils;
 
 pub use self::error::{Error, Result};
+pub use self::mmap::{Mmap, MmapConstraints, MmapSignals};
 pub use self::ram::{Ram, RamConstraints, RamSignals};
-pub use self::types::{Constraints, Signal, Signals,StorageFactory,SignalName};
-pub use self::utils::{count_public_inputs,is_public_input};
\ No newline at end of file
+pub use self::types::{ConstraintIter, Constraints, Signal, SignalIter, Signals,StorageFactory,SignalName};
+pub use self::utils::{assign_witness,build_witness,count_public_inputs,is_public_input};
\ No newline at end of file
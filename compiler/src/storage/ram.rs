@@ -1,25 +1,34 @@
 use circom2_parser::ast::SignalType;
 use std::collections::HashMap;
 use std::fmt::{Debug, Formatter};
-use std::rc::Rc;
+use std::ops::Range;
+use std::sync::Arc;
 
 use super::types::*;
 use crate::algebra;
-use crate::algebra::{SignalId, QEQ};
+use crate::algebra::{Field, SignalId, QEQ};
 
 use super::error::Result;
 use super::StorageFactory;
 
-pub struct Ram {}
+pub struct Ram {
+    field: Field,
+}
+impl Ram {
+    /// storage for signals in `field`
+    pub fn new(field: Field) -> Self {
+        Ram { field }
+    }
+}
 impl Default for Ram {
     fn default() -> Self {
-        Ram {}
+        Ram::new(Field::Bn254)
     }
 }
 
 impl StorageFactory<RamSignals, RamConstraints> for Ram {
     fn new_signals(&mut self) -> Result<RamSignals> {
-        Ok(RamSignals::default())
+        Ok(RamSignals::new(self.field))
     }
     fn new_constraints(&mut self) -> Result<RamConstraints> {
         Ok(RamConstraints::default())
@@ -27,18 +36,16 @@ impl StorageFactory<RamSignals, RamConstraints> for Ram {
 }
 
 pub struct RamSignals {
+    field: Field,
     names: HashMap<SignalName, SignalId>,
-    ids: Vec<Rc<Signal>>,
+    ids: Vec<Arc<Signal>>,
 }
 
 impl RamSignals {
-}
-
-impl Default for RamSignals {
-    fn default() -> Self {
+    pub fn new(field: Field) -> Self {
         let ids = Vec::new();
         let names = HashMap::new();
-        let mut signals = Self { names, ids };
+        let mut signals = Self { field, names, ids };
         // FIX
         signals
             .insert("one".to_string(), SignalType::PublicInput, None)
@@ -47,7 +54,17 @@ impl Default for RamSignals {
     }
 }
 
+impl Default for RamSignals {
+    fn default() -> Self {
+        RamSignals::new(Field::Bn254)
+    }
+}
+
 impl Signals for RamSignals {
+    fn field(&self) -> Field {
+        self.field
+    }
+
     fn is_empty(&self) -> Result<bool> {
         Ok(self.len()? == 0)
     }
@@ -56,7 +73,7 @@ impl Signals for RamSignals {
         Ok(self.ids.len())
     }
 
-    fn get_by_id(&self, id: SignalId) -> Result<Option<Rc<Signal>>> {
+    fn get_by_id(&self, id: SignalId) -> Result<Option<Arc<Signal>>> {
         if (id as usize) < self.ids.len() {
             Ok(Some(self.ids[id as usize].clone()))
         } else {
@@ -66,15 +83,15 @@ impl Signals for RamSignals {
 
     fn update(&mut self, id: SignalId, value: algebra::Value) -> Result<()> {
         let signal = &mut self.ids[id as usize];
-        if let Some(signal) =  Rc::get_mut(signal) {
+        if let Some(signal) =  Arc::get_mut(signal) {
             signal.value = Some(value);
         } else {
-            (*Rc::make_mut(signal)).value = Some(value);
+            (*Arc::make_mut(signal)).value = Some(value);
         }
         Ok(())
     }
 
-    fn get_by_name(&self, full_name: &str) -> Result<Option<Rc<Signal>>> {
+    fn get_by_name(&self, full_name: &str) -> Result<Option<Arc<Signal>>> {
         Ok(self
             .names
             .get(full_name)
@@ -97,7 +114,7 @@ impl Signals for RamSignals {
             value,
         };
 
-        self.ids.push(Rc::new(signal));
+        self.ids.push(Arc::new(signal));
         self.names.insert(full_name_rc, id);
 
         Ok(id)
@@ -106,6 +123,21 @@ impl Signals for RamSignals {
         let s = &self.ids[id as usize];
         Ok(format!("{:?}:{:?}:{:?}", s.full_name, s.xtype, s.value))
     }
+    fn iter_range(&self, range: Range<SignalId>) -> SignalIter {
+        let len = self.ids.len();
+        let end = range.end.min(len);
+        let range = range.start.min(end)..end;
+        Box::new(self.ids[range].iter().map(|s| Ok(s.clone())))
+    }
+    fn get_by_prefix(&self, component_name: &str) -> Result<Vec<Arc<Signal>>> {
+        let prefix = format!("{}.", component_name);
+        Ok(self
+            .ids
+            .iter()
+            .filter(|s| s.full_name.0.starts_with(&prefix))
+            .cloned()
+            .collect())
+    }
 }
 
 impl Debug for RamSignals {
@@ -143,4 +175,75 @@ impl Constraints for RamConstraints {
         self.0.push((qeq,debug));
         Ok(self.0.len() - 1)
     }
+    fn push_batch(&mut self, mut batch: Vec<(QEQ, Option<String>)>) -> Result<usize> {
+        let first = self.0.len();
+        self.0.append(&mut batch);
+        Ok(first)
+    }
+    fn iter_range(&self, range: Range<usize>) -> ConstraintIter {
+        let len = self.0.len();
+        let end = range.end.min(len);
+        let range = range.start.min(end)..end;
+        Box::new(self.0[range].iter().map(|(qeq, _)| Ok(qeq.clone())))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::algebra::{Value, FS};
+
+    #[test]
+    fn test_ram_signals_iter() -> Result<()> {
+        let mut signals = Ram::default().new_signals()?;
+        signals.insert("main.a".to_string(), SignalType::PublicInput, Some(Value::from(1)))?;
+        signals.insert("main.c.b".to_string(), SignalType::Internal, None)?;
+        signals.insert("main.cc".to_string(), SignalType::Internal, None)?;
+        signals.insert("main.c.d".to_string(), SignalType::Internal, None)?;
+
+        let names = |v: Vec<Arc<Signal>>| {
+            v.iter()
+                .map(|s| s.full_name.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+
+        let all = signals.iter().collect::<Result<Vec<_>>>()?;
+        assert_eq!("one,main.a,main.c.b,main.cc,main.c.d", names(all));
+
+        let range = signals.iter_range(1..3).collect::<Result<Vec<_>>>()?;
+        assert_eq!("main.a,main.c.b", names(range));
+
+        let clipped = signals.iter_range(4..10).collect::<Result<Vec<_>>>()?;
+        assert_eq!("main.c.d", names(clipped));
+
+        assert_eq!(0, signals.iter_range(5..2).count());
+
+        assert_eq!("main.c.b,main.c.d", names(signals.get_by_prefix("main.c")?));
+        Ok(())
+    }
+
+    #[test]
+    fn test_ram_constraints_batch() -> Result<()> {
+        let one = QEQ::from(&FS::one());
+        let two = QEQ::from(&FS::from(2));
+
+        let mut constraints = Ram::default().new_constraints()?;
+        constraints.push(one.clone(), None)?;
+        let first = constraints.push_batch(vec![(two, None), (one, Some("dbg".to_string()))])?;
+
+        assert_eq!(1, first);
+        assert_eq!(3, constraints.len()?);
+        assert_eq!(Some("dbg".to_string()), constraints.get_debug(2));
+
+        let all = constraints
+            .iter()
+            .map(|qeq| qeq.map(|qeq| format!("{:?}", qeq)))
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(vec!["[ ]*[ ]+[1s0]", "[ ]*[ ]+[2s0]", "[ ]*[ ]+[1s0]"], all);
+
+        assert_eq!(1, constraints.iter_range(2..3).count());
+        assert_eq!(0, constraints.iter_range(3..1).count());
+        Ok(())
+    }
 }
@@ -2,19 +2,21 @@
 use crate::algebra::QEQ;
 use circom2_parser::ast::SignalType;
 use std::fmt::{Debug, Formatter};
-use std::rc::Rc;
+use std::iter;
+use std::ops::Range;
+use std::sync::Arc;
 
 use super::error::Result;
 
 use crate::algebra;
-use crate::algebra::SignalId;
+use crate::algebra::{Field, SignalId};
 
 #[derive(Clone)]
-pub struct SignalName(pub Rc<String>); // see E0210
+pub struct SignalName(pub Arc<String>); // see E0210
 
 impl SignalName {
     pub fn new(s: String) -> Self {
-        SignalName(Rc::new(s))
+        SignalName(Arc::new(s))
     }
 }
 
@@ -56,7 +58,12 @@ pub struct Signal {
     pub value: Option<algebra::Value>,
 }
 
-pub trait Signals {
+pub type SignalIter<'a> = Box<dyn Iterator<Item = Result<Arc<Signal>>> + 'a>;
+pub type ConstraintIter<'a> = Box<dyn Iterator<Item = Result<QEQ>> + 'a>;
+
+pub trait Signals: Send + Sync {
+    /// prime field of the signal values
+    fn field(&self) -> Field;
     fn is_empty(&self) -> Result<bool>;
     fn len(&self) -> Result<usize>;
     fn insert(
@@ -66,18 +73,43 @@ pub trait Signals {
         value: Option<algebra::Value>,
     ) -> Result<SignalId>;
     fn update(&mut self, id: SignalId, value: algebra::Value) -> Result<()>;
-    fn get_by_id(&self, id: SignalId) -> Result<Option<Rc<Signal>>>;
-    fn get_by_name(&self, full_name: &str) -> Result<Option<Rc<Signal>>>;
+    fn get_by_id(&self, id: SignalId) -> Result<Option<Arc<Signal>>>;
+    fn get_by_name(&self, full_name: &str) -> Result<Option<Arc<Signal>>>;
     fn to_string(&self, id: SignalId) -> Result<String>;
+
+    /// signals whose id is in `range`, in id order
+    fn iter_range(&self, range: Range<SignalId>) -> SignalIter;
+
+    /// every signal declared inside `component_name`, including subcomponents
+    fn get_by_prefix(&self, component_name: &str) -> Result<Vec<Arc<Signal>>>;
+
+    fn iter(&self) -> SignalIter {
+        match self.len() {
+            Ok(len) => self.iter_range(0..len),
+            Err(err) => Box::new(iter::once(Err(err))),
+        }
+    }
 }
 
-pub trait Constraints {
+pub trait Constraints: Send + Sync {
     fn is_empty(&self) -> Result<bool>;
     fn len(&self) -> Result<usize>;
     fn get(&self, i: usize) -> Result<QEQ>;
     fn get_debug(&self, i: usize) -> Option<String>;
     fn push(&mut self, qeq: QEQ, debug: Option<String>) -> Result<usize>;
 
+    /// appends all constraints, returns the index of the first one
+    fn push_batch(&mut self, batch: Vec<(QEQ, Option<String>)>) -> Result<usize>;
+
+    /// constraints whose index is in `range`, in index order
+    fn iter_range(&self, range: Range<usize>) -> ConstraintIter;
+
+    fn iter(&self) -> ConstraintIter {
+        match self.len() {
+            Ok(len) => self.iter_range(0..len),
+            Err(err) => Box::new(iter::once(Err(err))),
+        }
+    }
 }
 
 pub trait StorageFactory<S: Signals, C: Constraints> {
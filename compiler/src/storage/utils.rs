@@ -1,6 +1,7 @@
 use circom2_parser::ast::SignalType;
 use super::types::*;
-use super::error::Result;
+use super::error::{Error, Result};
+use crate::algebra::{Value, Witness};
 
 pub fn is_public_input(signal: &Signal) -> bool {
     let component_len = signal.full_name.0.chars().filter(|ch| *ch == '.').count();
@@ -9,12 +10,38 @@ pub fn is_public_input(signal: &Signal) -> bool {
 }
 
 pub fn count_public_inputs<S:Signals>(signals:&S) -> Result<usize> {
-    let mut count = 0;;
-    for i in 1..signals.len()? {
-        let signal = signals.get_by_id(i)?.unwrap();
-        if is_public_input(&signal) {
+    let mut count = 0;
+    for signal in signals.iter_range(1..signals.len()?) {
+        if is_public_input(&*signal?) {
             count += 1;
         }
     }
     Ok(count)
 }
+
+pub fn build_witness<S:Signals>(signals:&S) -> Result<Witness> {
+    let mut witness = Witness::new();
+    for signal in signals.iter_range(1..signals.len()?) {
+        let signal = signal?;
+        match &signal.value {
+            Some(Value::FieldScalar(fs)) => witness.push(fs.clone()),
+            _ => return Err(Error::NotFound(format!("value of signal {:?}", signal))),
+        };
+    }
+    Ok(witness)
+}
+
+/// set the value of each signal from the witness, the inverse of `build_witness`
+pub fn assign_witness<S:Signals>(signals:&mut S, witness:&Witness) -> Result<()> {
+    if witness.len() != signals.len()? {
+        return Err(Error::Inner(format!(
+            "witness has {} values but there are {} signals",
+            witness.len(),
+            signals.len()?
+        )));
+    }
+    for (id, value) in witness.0.iter().enumerate().skip(1) {
+        signals.update(id, Value::from(value.clone()))?;
+    }
+    Ok(())
+}
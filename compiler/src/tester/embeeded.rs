@@ -10,7 +10,7 @@ pub fn run_embeeded_tests<F, S, C>(
     filename: &str,
     mut factory: F,
     debug : bool,
-) -> Result<Option<(Evaluator<S, C>, String)>>
+) -> Result<Option<(Evaluator<'static, S, C>, String)>>
 where
     S: Signals,
     C: Constraints,
@@ -38,10 +38,10 @@ where
 
             for test_name in tests.iter() {
 
-                println!("📏 Testing {} ",test_name);
+                info!("📏 Testing {} ",test_name);
 
                 // Generate constraints
-                println!("  ➡ Generating constraints");
+                info!("  ➡ Generating constraints");
                 let mut ev_constraints = Evaluator::new(
                     Mode::GenConstraints,
                     factory.new_signals()?,
@@ -53,7 +53,7 @@ where
                     return Err(Error::Evaluator(err)); 
                 }
                 // Generate witness
-                println!("➡ Generating witness");
+                info!("➡ Generating witness");
                 let mut ev_witness = Evaluator::new(
                     Mode::GenWitness,
                     factory.new_signals()?,
@@ -74,9 +74,10 @@ where
                     cn_count
                 };
                 
-                for n in 1..ckeck_up_to {
-                    let wi_signal = &*ev_witness.signals.get_by_id(n).unwrap().unwrap();
-                    let cn_signal = &*ev_constraints.signals.get_by_id(n).unwrap().unwrap();
+                let wi_signals = ev_witness.signals.iter_range(1..ckeck_up_to);
+                let cn_signals = ev_constraints.signals.iter_range(1..ckeck_up_to);
+                for (wi_signal, cn_signal) in wi_signals.zip(cn_signals) {
+                    let (wi_signal, cn_signal) = (wi_signal?, cn_signal?);
                     if wi_signal.full_name.0 != cn_signal.full_name.0 {
                         panic!(
                             "constrain & witness signals differ #cn(len={})={},#wi(len={})={}",
@@ -97,7 +98,7 @@ where
                 }
 
                 // Test constraints
-                println!("➡  Testing {} constraints evals to zero", ev_constraints.constraints.len()?);
+                info!("➡  Testing {} constraints evals to zero", ev_constraints.constraints.len()?);
                 check_constrains_eval_zero(&ev_constraints.constraints,&ev_witness.signals)?;   
             }
         }
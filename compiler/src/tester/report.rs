@@ -8,7 +8,7 @@ use codespan::{ByteSpan, CodeMap, Span};
 use codespan_reporting::termcolor::{ColorChoice, StandardStream};
 use codespan_reporting::{emit, Diagnostic, Label, Severity};
 
-pub fn dump_error<S: Signals, C: Constraints>(eval: &Evaluator<S, C>, err: &str) {
+pub fn dump_error<S: Signals, C: Constraints>(eval: &Evaluator<'_, S, C>, err: &str) {
     let msg = format!("{}", err);
 
     if let Some(ctx) = &eval.last_error {
@@ -17,11 +17,11 @@ pub fn dump_error<S: Signals, C: Constraints>(eval: &Evaluator<S, C>, err: &str)
             (1 + (ctx.meta.end - ctx.meta.start) as i64).into(),
         );
 
-        println!("SCOPE DUMP ------------------------------------------------");
-        println!("{}", ctx.scope);
+        error!("SCOPE DUMP ------------------------------------------------");
+        error!("{}", ctx.scope);
 
         if ctx.file != "" {
-            println!("Located in {}:{}",ctx.file,ctx.meta.start );
+            error!("Located in {}:{}",ctx.file,ctx.meta.start );
 
             let mut code_map = CodeMap::new();
             code_map
@@ -34,7 +34,7 @@ pub fn dump_error<S: Signals, C: Constraints>(eval: &Evaluator<S, C>, err: &str)
             let writer = StandardStream::stderr(ColorChoice::Always);
             emit(&mut writer.lock(), &code_map, &error).unwrap();
         } else {
-            println!("No ctx.file located {}",ctx.file);
+            error!("No ctx.file located {}",ctx.file);
         }
     } 
 }
@@ -5,18 +5,86 @@ extern crate circom2_prover;
 extern crate circom2_compiler;
 
 use neon::prelude::*;
+use neon::result::{NeonResult, Throw};
+
+use std::path::Path;
+
+use circom2_compiler::progress::{Phase, Progress};
+use circom2_prover::groth16::Bn256;
+
+const DEFAULT_VERIFICATION_KEY: &str = "verification_key.json";
+
+/// calls the optional js function `onProgress({ event, phase, total, done, rate })`,
+///   after the first exception of the callback it is not called again
+struct JsProgress<'a, 'b> {
+    cx: &'b mut FunctionContext<'a>,
+    callback: Option<Handle<'a, JsFunction>>,
+    thrown: Option<Throw>,
+}
+
+impl<'a, 'b> JsProgress<'a, 'b> {
+    fn new(cx: &'b mut FunctionContext<'a>, arg: i32) -> Self {
+        let callback = cx.argument_opt(arg).and_then(|v| v.downcast::<JsFunction>().ok());
+        JsProgress { cx, callback, thrown: None }
+    }
+
+    fn emit(&mut self, event: &str, phase: Phase, total: Option<usize>, done: Option<usize>, rate: Option<f64>) {
+        if let Some(callback) = self.callback {
+            if let Err(throw) = self.call(callback, event, phase, total, done, rate) {
+                self.callback = None;
+                self.thrown = Some(throw);
+            }
+        }
+    }
+
+    fn call(&mut self, callback: Handle<'a, JsFunction>, event: &str, phase: Phase, total: Option<usize>, done: Option<usize>, rate: Option<f64>) -> NeonResult<()> {
+        let cx = &mut *self.cx;
+        let obj = cx.empty_object();
+        let value = cx.string(event);
+        obj.set(cx, "event", value)?;
+        let value = cx.string(phase.to_string());
+        obj.set(cx, "phase", value)?;
+        for (key, number) in &[("total", total.map(|v| v as f64)), ("done", done.map(|v| v as f64)), ("rate", rate)] {
+            if let Some(number) = number {
+                let value = cx.number(*number);
+                obj.set(cx, *key, value)?;
+            }
+        }
+        let this = cx.undefined();
+        callback.call(cx, this, vec![obj])?;
+        Ok(())
+    }
+
+    /// the exception of the callback, if any, is rethrown
+    fn result<T>(self, result: JsResult<'a, T>) -> JsResult<'a, T> {
+        match self.thrown {
+            Some(throw) => Err(throw),
+            None => result,
+        }
+    }
+}
+
+impl<'a, 'b> Progress for JsProgress<'a, 'b> {
+    fn start(&mut self, phase: Phase, total: Option<usize>) {
+        self.emit("start", phase, total, None, None);
+    }
+    fn update(&mut self, phase: Phase, done: usize, rate: f64) {
+        self.emit("update", phase, None, Some(done), Some(rate));
+    }
+    fn finish(&mut self, phase: Phase, done: usize) {
+        self.emit("finish", phase, None, Some(done), None);
+    }
+}
 
 fn verbose(mut cx: FunctionContext) -> JsResult<JsUndefined> {
    let on = cx.argument::<JsBoolean>(0)?.value();
    if on {
-        circom2_prover::groth16::bellman_verbose(true);
         stderrlog::new()
             .verbosity(2)
             .timestamp(stderrlog::Timestamp::Off)
             .init()
             .unwrap();
    } else {
-        circom2_prover::groth16::bellman_verbose(false);
         stderrlog::new()
             .quiet(true)
             .init()
@@ -31,11 +99,21 @@ fn setup_sync(mut cx: FunctionContext) -> JsResult<JsUndefined> {
     let pk_path = cx.argument::<JsString>(1)?.value();
     let sol_path = cx.argument::<JsString>(2)?.value();
 
-    if let Err(err) = circom2_prover::groth16::setup_ram(&circuit_path,&pk_path,&sol_path) {
-        cx.throw_error(format!("{:?}",err))
-    } else {
-        Ok(cx.undefined())
-    }
+    // setupSync(circuit, pk, sol, [vk], [onProgress]), the verification key
+    //   defaults to a verification_key.json next to the proving key
+    let vk_path = cx.argument_opt(3).and_then(|v| v.downcast::<JsString>().ok()).map(|v| v.value());
+    let progress_arg = if vk_path.is_some() { 4 } else { 3 };
+    let vk_path = vk_path.unwrap_or_else(|| {
+        Path::new(&pk_path).with_file_name(DEFAULT_VERIFICATION_KEY).to_string_lossy().to_string()
+    });
+
+    let mut progress = JsProgress::new(&mut cx, progress_arg);
+    let setup = circom2_prover::groth16::setup_ram::<Bn256>(&circuit_path,&pk_path,Some(&sol_path),&vk_path,None,&mut progress);
+    let result = match setup {
+        Err(err) => progress.cx.throw_error(format!("{:?}",err)),
+        Ok(()) => Ok(progress.cx.undefined()),
+    };
+    progress.result(result)
 
 }
 
@@ -43,21 +121,18 @@ fn prove_sync(mut cx: FunctionContext) -> JsResult<JsString> {
     let circuit_path = cx.argument::<JsString>(0)?.value();
     let pk_path = cx.argument::<JsString>(1)?.value();
     let inputs = cx.argument::<JsString>(2)?.value();
-    match circom2_prover::groth16::flatten_json("main",&inputs) {
-        Ok(inputs) => {
-            match circom2_prover::groth16::prove_ram(&circuit_path,&pk_path,inputs) {
-                Ok(proof) => {
-                    Ok(cx.string(proof))
-                }
-                Err(err) => {
-                    cx.throw_error(format!("{:?}",err))
-                } 
-            }
-        } 
+    let mut progress = JsProgress::new(&mut cx, 3);
+    let proof = circom2_prover::groth16::flatten_json("main",&inputs)
+        .and_then(|inputs| circom2_prover::groth16::prove_ram::<Bn256>(&circuit_path,&pk_path,inputs,None,&mut progress));
+    let result = match proof {
+        Ok(proof) => {
+            Ok(progress.cx.string(proof))
+        }
         Err(err) => {
-            cx.throw_error(format!("{:?}",err))
+            progress.cx.throw_error(format!("{:?}",err))
         }
-    }
+    };
+    progress.result(result)
 }
 
 register_module!(mut cx, {
@@ -1,4 +1,5 @@
 const circom2js = require("../lib/index.js");
+const assert = require("assert");
 const fs = require("fs");
 
 describe("Basic test", function () {
@@ -6,6 +7,7 @@ describe("Basic test", function () {
     const circuit_path = "/tmp/circuit.circom"; 
     const pk_path = "/tmp/proving.key"; 
     const sol_path = "/tmp/verification.sol"; 
+    const vk_path = "/tmp/verification_key.json"; 
 
     it("Test simple circuit", async () => {
 
@@ -23,7 +25,17 @@ describe("Basic test", function () {
       fs.writeFileSync(circuit_path,circuit);
       
       circom2js.verbose(true)
+      const phases = [];
+      const onProgress = (progress) => {
+        if (progress.event == "finish") phases.push(progress.phase);
+      };
+      circom2js.setupSync(circuit_path,pk_path,sol_path,vk_path,onProgress);
+      assert.deepEqual(phases, ["constraints", "setup", "write keys"]);
+
+      // the verification key is optional, it defaults to one next to the proving key
+      fs.unlinkSync(vk_path);
       circom2js.setupSync(circuit_path,pk_path,sol_path);
+      assert(fs.existsSync(vk_path));
       
       all_inputs = { p:2, q:3 }
       proof_and_public_inputs = circom2js.proveSync(circuit_path,pk_path,JSON.stringify(all_inputs))
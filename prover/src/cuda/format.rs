@@ -1,27 +1,54 @@
 use circom2_parser::ast::SignalType;
-use circom2_compiler::algebra::{LC, QEQ};
-use circom2_compiler::storage::{Constraints, Signals,Result};
-use byteorder::{LittleEndian, WriteBytesExt};
+use circom2_compiler::algebra::{Field, FS, LC, QEQ};
+use circom2_compiler::storage::{build_witness, Constraints, Error, Result, Signals};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use num_bigint::BigUint;
 use std::fs::File;
-use std::io::{Seek, SeekFrom, Write};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
 
-pub fn export_r1cs(path: &str, constraints: &Constraints, signals: &Signals) -> Result<()> {
+// cuda constraints layout, 32 bit little endian words
+//
+//   nWords, nPubInputs, nOutputs, nVars, nConstraints,
+//   R1CSA_nWords, R1CSB_nWords, R1CSC_nWords
+//   R1CS.a, R1CS.b and -R1CS.c, each one as
+//     N constraints, cumulative count of coefficients of each constraint,
+//     for each constraint its signal ids followed by its coefficients
+//
+// cuda witness layout, 32 bit little endian words
+//
+//   nWords, nVars
+//   values : nVars field elements by signal id
+//
+// field elements are 8 words, little endian
+//
+const HEADER_WORDS: usize = 8;
+const FS_BYTES: usize = 32;
 
-    // find the number of public inputs, by now should be ordered 
+/// constraints read from a cuda constraints file, `c` is negated back so they
+///   are the ones that were exported
+pub struct CudaR1cs {
+    pub n_pub_inputs: u32,
+    pub n_outputs: u32,
+    pub n_vars: u32,
+    pub constraints: Vec<QEQ>,
+}
+
+pub fn export_r1cs<C: Constraints, S: Signals>(path: &str, constraints: &C, signals: &S) -> Result<()> {
+    let field = signals.field();
+
+    // find the number of public inputs, by now should be ordered
     //   in the following way:
     //
     //   SignalType::Internal      (value=one)
     //   SignalType::PrivateInput  (value not set)
     //   SignalType::PublicInput   (value not set)
     //
-    
-    println!("Scanning constraints...");
 
     let mut public_signal_count = 0;
     let mut private_signal_count = 0;
 
-    for n in 0..signals.len()? {
-        let s = signals.get_by_id(n)?.unwrap();
+    for s in signals.iter() {
+        let s = s?;
         let component_len = s.full_name.0.chars().filter(|ch| *ch == '.').count();
         if component_len == 1 {
             match s.xtype {
@@ -32,52 +59,48 @@ pub fn export_r1cs(path: &str, constraints: &Constraints, signals: &Signals) ->
         }
     }
 
-    println!("public_signal_count = {}",public_signal_count);
-    println!("private_signal_count = {}",private_signal_count);
+    debug!("public_signal_count = {}",public_signal_count);
+    debug!("private_signal_count = {}",private_signal_count);
 
     let input_signals_count = public_signal_count + private_signal_count;
 
-    println!("Writing cuda constraints file {}...",path);
+    info!("Writing cuda constraints file {}...",path);
 
-    let mut file = File::create(&path)?;
+    let mut file = BufWriter::new(File::create(path)?);
 
     // nWords : File size in 32 bit workds --------------- 32 bits
     let offset_words = file.seek(SeekFrom::Current(0))?;
-    file.write_u32::<LittleEndian>(0).unwrap();
+    file.write_u32::<LittleEndian>(0)?;
 
     // nPubInputs : -------------------------------------- 32 bits
-    file.write_u32::<LittleEndian>(input_signals_count).unwrap();
+    file.write_u32::<LittleEndian>(input_signals_count)?;
 
     // nOutputs   : -------------------------------------- 32 bits
-    file.write_u32::<LittleEndian>(0).unwrap();
+    file.write_u32::<LittleEndian>(0)?;
 
     // nVars      : -------------------------------------- 32 bits
-    file.write_u32::<LittleEndian>(signals.len()? as u32)
-        .unwrap();
+    file.write_u32::<LittleEndian>(signals.len()? as u32)?;
 
     // nConstraints : Number of constraints--------------- 32 bits
-    file.write_u32::<LittleEndian>(constraints.len()? as u32)
-        .unwrap();
+    file.write_u32::<LittleEndian>(constraints.len()? as u32)?;
 
     // R1CSA_nWords : R1CSA size in 32 bit words --------- 32 bits
     let offset_r1cs_a = file.seek(SeekFrom::Current(0))?;
-    file.write_u32::<LittleEndian>(0).unwrap();
+    file.write_u32::<LittleEndian>(0)?;
 
     // R1CSB_nWords : R1CSB size in 32 bit words --------- 32 bits
     let offset_r1cs_b = file.seek(SeekFrom::Current(0))?;
-    file.write_u32::<LittleEndian>(0).unwrap();
+    file.write_u32::<LittleEndian>(0)?;
 
     // R1CSC_nWords : R1CSC size in 32 bit words --------- 32 bits
     let offset_r1cs_c = file.seek(SeekFrom::Current(0))?;
-    file.write_u32::<LittleEndian>(0).unwrap();
+    file.write_u32::<LittleEndian>(0)?;
 
-    fn write_lc(file: &mut File, constraints: &Constraints, lc_of: &Fn(QEQ) -> LC) -> Result<()> {
-        let zeroes = vec![0; 32];
+    fn write_lc<W: Write, C: Constraints, F: Fn(QEQ) -> LC>(file: &mut W, constraints: &C, lc_of: F) -> Result<()> {
         let constraints_len = constraints.len()?;
 
         // N constraints  -------------------------------- 32 bits
-        file.write_u32::<LittleEndian>(constraints_len as u32)
-            .unwrap();
+        file.write_u32::<LittleEndian>(constraints_len as u32)?;
 
         // cumsum(  -> cumulative
         //    N coeff constraints[0] ---------------------- 32 bits
@@ -87,60 +110,256 @@ pub fn export_r1cs(path: &str, constraints: &Constraints, signals: &Signals) ->
         //                                                            N constraints[2] +...+ Nconstraints[N-1]
         // )
         let mut coeff_count = 0;
-        for n in 0..constraints_len {
-            let lc = lc_of(constraints.get(n)?).0;
+        for qeq in constraints.iter() {
+            let lc = lc_of(qeq?).0;
             coeff_count += lc.len();
-            file.write_u32::<LittleEndian>(coeff_count as u32).unwrap();
+            file.write_u32::<LittleEndian>(coeff_count as u32)?;
         }
 
-        for n in 0..constraints_len {
-            let lc = lc_of(constraints.get(n)?).0;
+        for qeq in constraints.iter() {
+            let lc = lc_of(qeq?).0;
             for (signal_id, _) in lc.iter() {
-                file.write_u32::<LittleEndian>(*signal_id as u32).unwrap();
+                file.write_u32::<LittleEndian>(*signal_id as u32)?;
             }
             for (_, mult) in lc.iter() {
-                let le = mult.0.to_bytes_le();
-                file.write_all(&le)?;
-                if le.len() < 32 {
-                    file.write_all(&zeroes[le.len()..32])?;
-                }
+                write_fs(file, mult)?;
             }
         }
         Ok(())
     }
 
     // Write R1CS.a
-    let offset_start_a = file.seek(SeekFrom::Current(0))? as u32;
-    write_lc(&mut file, constraints, &|qeq| qeq.a)?;
+    let offset_start_a = file.seek(SeekFrom::Current(0))?;
+    write_lc(&mut file, constraints, |qeq| qeq.a)?;
 
     // Write R1CS.b
-    let offset_start_b = file.seek(SeekFrom::Current(0))? as u32;
-    write_lc(&mut file, constraints, &|qeq| qeq.b)?;
+    let offset_start_b = file.seek(SeekFrom::Current(0))?;
+    write_lc(&mut file, constraints, |qeq| qeq.b)?;
 
     // Write -R1CS.c
-    let offset_start_c = file.seek(SeekFrom::Current(0))? as u32;
-    write_lc(&mut file, constraints, &|qeq| -&(qeq.c))?;
+    let offset_start_c = file.seek(SeekFrom::Current(0))?;
+    write_lc(&mut file, constraints, |qeq| qeq.c.neg(field))?;
 
-    let offset_end = file.seek(SeekFrom::End(0))? as u32;
+    let offset_end = file.seek(SeekFrom::End(0))?;
 
     // Write R1CS.a len
     file.seek(SeekFrom::Start(offset_r1cs_a))?;
-    file.write_u32::<LittleEndian>((offset_start_b - offset_start_a) / 4)
-        .unwrap();
+    file.write_u32::<LittleEndian>(words(offset_start_b - offset_start_a)?)?;
 
     // Write R1CS.b len
     file.seek(SeekFrom::Start(offset_r1cs_b))?;
-    file.write_u32::<LittleEndian>((offset_start_c - offset_start_b) / 4)
-        .unwrap();
+    file.write_u32::<LittleEndian>(words(offset_start_c - offset_start_b)?)?;
 
     // Write R1CS.c len
     file.seek(SeekFrom::Start(offset_r1cs_c))?;
-    file.write_u32::<LittleEndian>((offset_end - offset_start_c) / 4)
-        .unwrap();
+    file.write_u32::<LittleEndian>(words(offset_end - offset_start_c)?)?;
 
     // Write nWords
     file.seek(SeekFrom::Start(offset_words))?;
-    file.write_u32::<LittleEndian>(offset_end / 4).unwrap();
+    file.write_u32::<LittleEndian>(words(offset_end)?)?;
 
+    file.flush()?;
     Ok(())
 }
+
+/// write the values of the signals by id, for the constraints of `export_r1cs`
+pub fn export_witness<S: Signals>(path: &str, signals: &S) -> Result<()> {
+    let witness = build_witness(signals)?;
+
+    info!("Writing cuda witness file {}...", path);
+
+    let n_words = 2 + witness.len() as u64 * (FS_BYTES / 4) as u64;
+    let mut file = BufWriter::new(File::create(path)?);
+    file.write_u32::<LittleEndian>(words(n_words * 4)?)?;
+    file.write_u32::<LittleEndian>(witness.len() as u32)?;
+    for value in witness.0.iter() {
+        write_fs(&mut file, value)?;
+    }
+    file.flush()?;
+    Ok(())
+}
+
+/// read a file written by `export_r1cs` for a circuit in `field`
+pub fn import_r1cs<R: Read>(mut input: R, field: Field) -> Result<CudaR1cs> {
+    let mut bytes = Vec::new();
+    input.read_to_end(&mut bytes)?;
+    let mut input = &bytes[..];
+
+    let mut header = [0u32; HEADER_WORDS];
+    for word in header.iter_mut() {
+        *word = input.read_u32::<LittleEndian>()?;
+    }
+    let [n_words, n_pub_inputs, n_outputs, n_vars, n_constraints, a_words, b_words, c_words] = header;
+    if n_words as usize * 4 != bytes.len()
+        || HEADER_WORDS as u64 + a_words as u64 + b_words as u64 + c_words as u64 != n_words as u64
+    {
+        return Err(Error::Inner("cuda constraints file sizes do not match".to_string()));
+    }
+
+    let a = read_lcs(&mut input, field, n_constraints, a_words)?;
+    let b = read_lcs(&mut input, field, n_constraints, b_words)?;
+    let c = read_lcs(&mut input, field, n_constraints, c_words)?;
+    let constraints = a
+        .into_iter()
+        .zip(b)
+        .zip(c)
+        .map(|((a, b), c)| QEQ { a, b, c: c.neg(field) })
+        .collect();
+
+    Ok(CudaR1cs {
+        n_pub_inputs,
+        n_outputs,
+        n_vars,
+        constraints,
+    })
+}
+
+/// one of the R1CS.a, R1CS.b or -R1CS.c sections, of `n_words` words. The counts
+///   are checked against the size of the input before allocating for them
+fn read_lcs(input: &mut &[u8], field: Field, n_constraints: u32, n_words: u32) -> Result<Vec<LC>> {
+    let start = input.len();
+    if input.read_u32::<LittleEndian>()? != n_constraints {
+        return Err(Error::Inner("cuda constraints file counts do not match".to_string()));
+    }
+    if n_constraints as usize > input.len() / 4 {
+        return Err(Error::Inner("cuda constraints file is truncated".to_string()));
+    }
+    let mut counts = Vec::with_capacity(n_constraints as usize);
+    for _ in 0..n_constraints {
+        counts.push(input.read_u32::<LittleEndian>()? as usize);
+    }
+
+    let mut lcs = Vec::with_capacity(n_constraints as usize);
+    let mut previous = 0;
+    for count in counts {
+        let len = count
+            .checked_sub(previous)
+            .ok_or_else(|| Error::Inner("cuda constraints file counts are not cumulative".to_string()))?;
+        previous = count;
+        if len > input.len() / (4 + FS_BYTES) {
+            return Err(Error::Inner("cuda constraints file is truncated".to_string()));
+        }
+
+        let mut signals = Vec::with_capacity(len);
+        for _ in 0..len {
+            signals.push(input.read_u32::<LittleEndian>()? as usize);
+        }
+        let mut lc = LC::new();
+        let mut value = [0u8; FS_BYTES];
+        for signal in signals {
+            input.read_exact(&mut value)?;
+            let value = BigUint::from_bytes_le(&value);
+            if &value >= field.prime() {
+                return Err(Error::Inner("cuda constraints file value is not in the field".to_string()));
+            }
+            lc.0.push((signal, FS(value)));
+        }
+        lcs.push(lc);
+    }
+
+    if (start - input.len()) as u64 != n_words as u64 * 4 {
+        return Err(Error::Inner("cuda constraints file sizes do not match".to_string()));
+    }
+    Ok(lcs)
+}
+
+fn write_fs<W: Write>(out: &mut W, value: &FS) -> Result<()> {
+    let mut le = value.0.to_bytes_le();
+    le.resize(FS_BYTES, 0);
+    out.write_all(&le)?;
+    Ok(())
+}
+
+/// 32 bit words in `bytes`, that have to fit in a 32 bit size of the format
+fn words(bytes: u64) -> Result<u32> {
+    if bytes % 4 != 0 || bytes / 4 > u64::from(u32::max_value()) {
+        return Err(Error::Inner(format!("{} bytes do not fit in the cuda format", bytes)));
+    }
+    Ok((bytes / 4) as u32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use circom2_compiler::algebra::Value;
+    use circom2_compiler::evaluator::{Evaluator, Mode, Scope};
+    use circom2_compiler::storage::{Ram, StorageFactory};
+
+    const CIRCUIT: &str = "
+        template t() {
+            signal private input a;
+            signal input k;
+            signal output c;
+
+            c <== (a + k) * (a - 2);
+        }
+        component main = t();
+    ";
+
+    #[test]
+    fn test_cuda_roundtrip() {
+        let mut ram = Ram::default();
+        let mut ev_r1cs = Evaluator::new(Mode::GenConstraints, ram.new_signals().unwrap(), ram.new_constraints().unwrap());
+        ev_r1cs.eval_inline(&mut Scope::new(true, None, "root".to_string()), CIRCUIT)
+            .unwrap();
+        assert!(ev_r1cs.constraints.len().unwrap() > 0);
+
+        export_r1cs("/tmp/cuda.r1cs", &ev_r1cs.constraints, &ev_r1cs.signals).unwrap();
+        let r1cs = import_r1cs(File::open("/tmp/cuda.r1cs").unwrap(), Field::Bn254).unwrap();
+        // main.c, main.k and main.a
+        assert_eq!(3, r1cs.n_pub_inputs);
+        assert_eq!(ev_r1cs.signals.len().unwrap() as u32, r1cs.n_vars);
+        let expected: Vec<QEQ> = ev_r1cs.constraints.iter().map(|qeq| qeq.unwrap()).collect();
+        assert_eq!(format!("{:?}", expected), format!("{:?}", r1cs.constraints));
+
+        let mut ram = Ram::default();
+        let mut ev_witness = Evaluator::new(Mode::GenWitness, ram.new_signals().unwrap(), ram.new_constraints().unwrap());
+        ev_witness.set_deferred_value("main.a".to_string(), Value::from(3));
+        ev_witness.set_deferred_value("main.k".to_string(), Value::from(5));
+        ev_witness.eval_inline(&mut Scope::new(true, None, "root".to_string()), CIRCUIT)
+            .unwrap();
+
+        export_witness("/tmp/cuda.wtns", &ev_witness.signals).unwrap();
+        let mut bytes = Vec::new();
+        File::open("/tmp/cuda.wtns").unwrap().read_to_end(&mut bytes).unwrap();
+        let n_vars = ev_witness.signals.len().unwrap();
+        assert_eq!(r1cs.n_vars, n_vars as u32);
+        let mut input = &bytes[..];
+        assert_eq!((2 + 8 * n_vars) as u32, input.read_u32::<LittleEndian>().unwrap());
+        assert_eq!(n_vars as u32, input.read_u32::<LittleEndian>().unwrap());
+        for value in build_witness(&ev_witness.signals).unwrap().0 {
+            let mut le = [0u8; FS_BYTES];
+            input.read_exact(&mut le).unwrap();
+            assert_eq!(value.0, BigUint::from_bytes_le(&le));
+        }
+        assert!(input.is_empty());
+    }
+
+    #[test]
+    fn test_cuda_bad_size() {
+        let mut ram = Ram::default();
+        let mut ev = Evaluator::new(Mode::GenConstraints, ram.new_signals().unwrap(), ram.new_constraints().unwrap());
+        ev.eval_inline(&mut Scope::new(true, None, "root".to_string()), CIRCUIT)
+            .unwrap();
+
+        export_r1cs("/tmp/cuda_bad.r1cs", &ev.constraints, &ev.signals).unwrap();
+        let mut bytes = Vec::new();
+        File::open("/tmp/cuda_bad.r1cs").unwrap().read_to_end(&mut bytes).unwrap();
+        let word = |bytes: &[u8], n: usize| (&bytes[n * 4..]).read_u32::<LittleEndian>().unwrap() as usize;
+        let n_constraints = word(&bytes, 4);
+        let first = HEADER_WORDS + 1 + n_constraints + word(&bytes, HEADER_WORDS + 1);
+
+        // counts larger than the file are refused before allocating for them
+        let mut huge = bytes.clone();
+        huge[(HEADER_WORDS + 1) * 4..(HEADER_WORDS + 2) * 4].copy_from_slice(&u32::max_value().to_le_bytes());
+        assert!(import_r1cs(&huge[..], Field::Bn254).is_err());
+
+        // and values that are not in the field
+        let mut unreduced = bytes.clone();
+        unreduced[first * 4..first * 4 + FS_BYTES].copy_from_slice(&[0xff; FS_BYTES]);
+        assert!(import_r1cs(&unreduced[..], Field::Bn254).is_err());
+
+        bytes.truncate(bytes.len() - 4);
+        assert!(import_r1cs(&bytes[..], Field::Bn254).is_err());
+    }
+}
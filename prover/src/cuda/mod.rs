@@ -1,3 +1,3 @@
 mod format;
 
-pub use self::format::export_r1cs;
\ No newline at end of file
+pub use self::format::{export_r1cs, export_witness, import_r1cs, CudaR1cs};
@@ -0,0 +1,98 @@
+use std::io::Write;
+use std::str::FromStr;
+
+use bellman::groth16::VerifyingKey;
+use pairing::Engine;
+
+use circom2_compiler::algebra::Field;
+
+use super::error::{Error, Result};
+use super::ethereum::{self, SolidityOptions};
+
+pub use pairing::bls12_381::Bls12;
+pub use pairing::bn256::Bn256;
+
+/// curve of the keys in the proving key header
+pub const CURVE_BN256: u32 = 1;
+pub const CURVE_BLS12_381: u32 = 2;
+
+/// pairing engine used to create keys and proofs, its scalar field has to be the
+///   field the circuit was compiled with
+pub trait Curve: Engine {
+    /// id of the curve in the proving key header
+    const ID: u32;
+    /// name of the curve in the snarkjs json files
+    const NAME: &'static str;
+    /// scalar field of the curve
+    const FIELD: Field;
+
+    /// solidity verifier of a verifying key, only for curves with EVM precompiles
+    fn generate_solidity<W: Write>(
+        vk: &VerifyingKey<Self>,
+        input_length: usize,
+        options: &SolidityOptions,
+        out: &mut W,
+    ) -> Result<()>;
+}
+
+impl Curve for Bn256 {
+    const ID: u32 = CURVE_BN256;
+    const NAME: &'static str = "bn128";
+    const FIELD: Field = Field::Bn254;
+
+    fn generate_solidity<W: Write>(
+        vk: &VerifyingKey<Self>,
+        input_length: usize,
+        options: &SolidityOptions,
+        out: &mut W,
+    ) -> Result<()> {
+        ethereum::generate_solidity(vk, input_length, options, out)
+    }
+}
+
+impl Curve for Bls12 {
+    const ID: u32 = CURVE_BLS12_381;
+    const NAME: &'static str = "bls12381";
+    const FIELD: Field = Field::Bls12_381;
+
+    fn generate_solidity<W: Write>(
+        _vk: &VerifyingKey<Self>,
+        _input_length: usize,
+        _options: &SolidityOptions,
+        _out: &mut W,
+    ) -> Result<()> {
+        Err(Error::Unsupported(
+            "solidity verifiers are only available for bn254, ethereum has no bls12-381 pairing precompile"
+                .to_string(),
+        ))
+    }
+}
+
+/// curve selected by name, `bn254` (also `bn128`) or `bls12-381`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CurveName {
+    Bn254,
+    Bls12_381,
+}
+
+impl CurveName {
+    /// scalar field of the curve, to be set before compiling the circuit
+    pub fn field(self) -> Field {
+        match self {
+            CurveName::Bn254 => Bn256::FIELD,
+            CurveName::Bls12_381 => Bls12::FIELD,
+        }
+    }
+}
+
+impl FromStr for CurveName {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "bn254" | "bn128" => Ok(CurveName::Bn254),
+            "bls12-381" | "bls12381" => Ok(CurveName::Bls12_381),
+            _ => Err(Error::BadFormat(format!("unsupported curve {}, use bn254 or bls12-381", s))),
+        }
+    }
+}
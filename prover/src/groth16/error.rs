@@ -6,8 +6,12 @@ pub enum Error {
     Cbor(serde_cbor::error::Error),
     Algebra(circom2_compiler::algebra::Error),
     Evaluator(circom2_compiler::evaluator::Error),
+    Format(circom2_compiler::format::Error),
     BadFormat(String),
+    Mismatch(String),
+    CircuitMismatch(String),
     Unexpected(String),
+    Unsupported(String),
     Json(serde_json::error::Error),
 }
 
@@ -53,5 +57,11 @@ impl From<circom2_compiler::evaluator::Error> for Error {
     }
 }
 
+impl From<circom2_compiler::format::Error> for Error {
+    fn from(err: circom2_compiler::format::Error) -> Self {
+        Error::Format(err)
+    }
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -1,12 +1,62 @@
 use pairing::bn256::Bn256;
-use bellman::groth16::VerifyingKey;
+use bellman::groth16::{Proof, VerifyingKey};
 use std::io::Write;
+use std::str::FromStr;
+
+use circom2_compiler::algebra::FS;
 
 use super::format;
-use super::error::Result;
+use super::error::{Error, Result};
+
+/// solidity version the verifier is written for
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Pragma {
+    V05,
+    V06,
+    V08,
+}
+
+impl FromStr for Pragma {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "0.5" => Ok(Pragma::V05),
+            "0.6" => Ok(Pragma::V06),
+            "0.8" => Ok(Pragma::V08),
+            _ => Err(Error::BadFormat(format!("unsupported solidity version {}, use 0.5, 0.6 or 0.8", s))),
+        }
+    }
+}
+
+/// verifier contract options, the default is the `^0.5.0` `Verifier`
+///   contract with a `verifyTx` that emits an event
+#[derive(Clone, Debug)]
+pub struct SolidityOptions {
+    pub pragma: Pragma,
+    pub contract_name: String,
+    /// `verifyTx` is a view function without events
+    pub view: bool,
+    /// `verifyTx` is external and takes its parameters as calldata
+    pub calldata: bool,
+    /// also add a `verifyBatch` that checks many proofs with a single pairing
+    pub batch: bool,
+}
+
+impl Default for SolidityOptions {
+    fn default() -> Self {
+        SolidityOptions {
+            pragma: Pragma::V05,
+            contract_name: "Verifier".to_string(),
+            view: false,
+            calldata: false,
+            batch: false,
+        }
+    }
+}
 
 const CONTRACT_TEMPLATE: &str = r#"
-pragma solidity ^0.5.0;
+pragma solidity <%pragma%>;
 library Pairing {
     struct G1Point {
         uint X;
@@ -49,7 +99,7 @@ library Pairing {
         bool success;
         // solium-disable-next-line security/no-inline-assembly
         assembly {
-            success := staticcall(sub(gas, 2000), 6, input, 0xc0, r, 0x60)
+            success := staticcall(sub(<%gas%>, 2000), 6, input, 0xc0, r, 0x60)
             // Use "invalid" to make gas estimation work
             switch success case 0 { invalid() }
         }
@@ -65,7 +115,7 @@ library Pairing {
         bool success;
         // solium-disable-next-line security/no-inline-assembly
         assembly {
-            success := staticcall(sub(gas, 2000), 7, input, 0x80, r, 0x60)
+            success := staticcall(sub(<%gas%>, 2000), 7, input, 0x80, r, 0x60)
             // Use "invalid" to make gas estimation work
             switch success case 0 { invalid() }
         }
@@ -93,7 +143,7 @@ library Pairing {
         bool success;
         // solium-disable-next-line security/no-inline-assembly
         assembly {
-            success := staticcall(sub(gas, 2000), 8, add(input, 0x20), mul(inputSize, 0x20), out, 0x20)
+            success := staticcall(sub(<%gas%>, 2000), 8, add(input, 0x20), mul(inputSize, 0x20), out, 0x20)
             // Use "invalid" to make gas estimation work
             switch success case 0 { invalid() }
         }
@@ -147,8 +197,9 @@ library Pairing {
     }
 }
 
-contract Verifier {
+contract <%contract_name%> {
     using Pairing for *;
+    uint constant SNARK_SCALAR_FIELD = 21888242871839275222246405745257275088548364400416034343698204186575808495617;
     struct VerifyingKey {
         Pairing.G1Point a;
         Pairing.G2Point b;
@@ -169,13 +220,15 @@ contract Verifier {
         vk.gammaABC = new Pairing.G1Point[](<%vk_gammaABC_length%>);
         <%vk_gammaABC_pts%>
     }
-    function verify(uint[] memory input, Proof memory proof) internal returns (uint) {
+    function verify(uint[] memory input, Proof memory proof) internal <%verify_mutability%>returns (uint) {
         VerifyingKey memory vk = verifyingKey();
         require(input.length + 1 == vk.gammaABC.length);
         // Compute the linear combination vk_x
         Pairing.G1Point memory vk_x = Pairing.G1Point(0, 0);
-        for (uint i = 0; i < input.length; i++)
+        for (uint i = 0; i < input.length; i++) {
+            require(input[i] < SNARK_SCALAR_FIELD, "verifier-gte-snark-scalar-field");
             vk_x = Pairing.addition(vk_x, Pairing.scalar_mul(vk.gammaABC[i + 1], input[i]));
+        }
         vk_x = Pairing.addition(vk_x, vk.gammaABC[0]);
         if(!Pairing.pairingProd4(
              proof.A, proof.B,
@@ -184,13 +237,12 @@ contract Verifier {
              Pairing.negate(vk.a), vk.b)) return 1;
         return 0;
     }
-    event Verified(string s);
-    function verifyTx(
-            uint[2] memory a,
-            uint[2][2] memory b,
-            uint[2] memory c,
-            uint[<%vk_input_length%>] memory input
-        ) public returns (bool r) {
+<%verified_event%>    function verifyTx(
+            uint[2] <%location%> a,
+            uint[2][2] <%location%> b,
+            uint[2] <%location%> c,
+            uint[<%vk_input_length%>] <%location%> input
+        ) <%visibility%> returns (bool r) {
         Proof memory proof;
         proof.A = Pairing.G1Point(a[0], a[1]);
         proof.B = Pairing.G2Point([b[0][0], b[0][1]], [b[1][0], b[1][1]]);
@@ -199,19 +251,104 @@ contract Verifier {
         for(uint i = 0; i < input.length; i++){
             inputValues[i] = input[i];
         }
-        if (verify(inputValues, proof) == 0) {
+<%verify_result%>
+    }
+<%verify_batch%>}
+"#;
+
+const VERIFIED_EVENT: &str = "    event Verified(string s);\n";
+
+const VERIFY_RESULT_EVENT: &str = r#"        if (verify(inputValues, proof) == 0) {
             emit Verified("Transaction successfully verified.");
             return true;
         } else {
             return false;
+        }"#;
+
+const VERIFY_RESULT_VIEW: &str = "        return verify(inputValues, proof) == 0;";
+
+const VERIFY_BATCH: &str = r#"    function batchSeed(
+            uint[2][] memory a,
+            uint[2][2][] memory b,
+            uint[2][] memory c,
+            uint[<%vk_input_length%>][] memory input
+        ) internal pure returns (bytes32 seed) {
+        for (uint i = 0; i < a.length; i++)
+            seed = keccak256(abi.encode(seed, a[i], b[i], c[i], input[i]));
+    }
+    function batchVkX(Pairing.G1Point[] memory gammaABC, uint[<%vk_input_length%>] memory input) internal view returns (Pairing.G1Point memory vk_x) {
+        vk_x = gammaABC[0];
+        for (uint i = 0; i < input.length; i++) {
+            require(input[i] < SNARK_SCALAR_FIELD, "verifier-gte-snark-scalar-field");
+            vk_x = Pairing.addition(vk_x, Pairing.scalar_mul(gammaABC[i + 1], input[i]));
         }
     }
-}
+    /// checks all the proofs with a single pairing over a random linear combination
+    /// of their equations, the coefficients are derived from all the proofs
+    function verifyBatch(
+            uint[2][] memory a,
+            uint[2][2][] memory b,
+            uint[2][] memory c,
+            uint[<%vk_input_length%>][] memory input
+        ) public view returns (bool) {
+        require(a.length > 0, "verifier-batch-empty");
+        require(a.length == b.length && a.length == c.length && a.length == input.length, "verifier-batch-lengths");
+        VerifyingKey memory vk = verifyingKey();
+        bytes32 seed = batchSeed(a, b, c, input);
+        // p1 ends with the sums of r * alpha, r * vk_x and r * C, negated at the end
+        Pairing.G1Point[] memory p1 = new Pairing.G1Point[](a.length + 3);
+        Pairing.G2Point[] memory p2 = new Pairing.G2Point[](a.length + 3);
+        uint sumR = 0;
+        for (uint i = 0; i < a.length; i++) {
+            uint r = uint(keccak256(abi.encode(seed, i))) % SNARK_SCALAR_FIELD;
+            sumR = addmod(sumR, r, SNARK_SCALAR_FIELD);
+            p1[i] = Pairing.scalar_mul(Pairing.G1Point(a[i][0], a[i][1]), r);
+            p2[i] = Pairing.G2Point([b[i][0][0], b[i][0][1]], [b[i][1][0], b[i][1][1]]);
+            p1[a.length + 1] = Pairing.addition(p1[a.length + 1], Pairing.scalar_mul(batchVkX(vk.gammaABC, input[i]), r));
+            p1[a.length + 2] = Pairing.addition(p1[a.length + 2], Pairing.scalar_mul(Pairing.G1Point(c[i][0], c[i][1]), r));
+        }
+        p1[a.length] = Pairing.negate(Pairing.scalar_mul(vk.a, sumR));
+        p1[a.length + 1] = Pairing.negate(p1[a.length + 1]);
+        p1[a.length + 2] = Pairing.negate(p1[a.length + 2]);
+        p2[a.length] = vk.b;
+        p2[a.length + 1] = vk.gamma;
+        p2[a.length + 2] = vk.delta;
+        return Pairing.pairing(p1, p2);
+    }
 "#;
 
-pub fn generate_solidity<W : Write>(vk: &VerifyingKey<Bn256>, input_length: usize, out: &mut W) -> Result<()> {
+pub fn generate_solidity<W : Write>(
+    vk: &VerifyingKey<Bn256>,
+    input_length: usize,
+    options: &SolidityOptions,
+    out: &mut W,
+) -> Result<()> {
+
+    if !is_identifier(&options.contract_name) {
+        return Err(Error::BadFormat(format!("bad contract name '{}'", options.contract_name)));
+    }
+    let (pragma, gas) = match options.pragma {
+        Pragma::V05 => ("^0.5.0", "gas"),
+        Pragma::V06 => ("^0.6.0", "gas()"),
+        Pragma::V08 => ("^0.8.0", "gas()"),
+    };
+    let visibility = match (options.calldata, options.view) {
+        (false, false) => "public",
+        (false, true) => "public view",
+        (true, false) => "external",
+        (true, true) => "external view",
+    };
 
     let mut contract = String::from(CONTRACT_TEMPLATE);
+    contract = contract.replace("<%pragma%>", pragma);
+    contract = contract.replace("<%gas%>", gas);
+    contract = contract.replace("<%contract_name%>", &options.contract_name);
+    contract = contract.replace("<%verify_mutability%>", if options.view { "view " } else { "" });
+    contract = contract.replace("<%verified_event%>", if options.view { "" } else { VERIFIED_EVENT });
+    contract = contract.replace("<%location%>", if options.calldata { "calldata" } else { "memory" });
+    contract = contract.replace("<%visibility%>", visibility);
+    contract = contract.replace("<%verify_result%>", if options.view { VERIFY_RESULT_VIEW } else { VERIFY_RESULT_EVENT });
+    contract = contract.replace("<%verify_batch%>", if options.batch { VERIFY_BATCH } else { "" });
     contract = contract.replace("<%vk_a%>", &format::parse_g1_hex(&vk.alpha_g1));
     contract = contract.replace("<%vk_b%>", &format::parse_g2_hex(&vk.beta_g2));
     contract = contract.replace("<%vk_gamma%>", &format::parse_g2_hex(&vk.gamma_g2));
@@ -235,4 +372,175 @@ pub fn generate_solidity<W : Write>(vk: &VerifyingKey<Bn256>, input_length: usiz
     out.write_all(contract.as_bytes())?;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// arguments of `verifyTx(a, b, c, input)` as 0x hex words, the coordinates
+///   of `b` are swapped to c1, c0 as the verifier expects
+#[derive(Debug, PartialEq)]
+pub struct Calldata {
+    pub a: [String; 2],
+    pub b: [[String; 2]; 2],
+    pub c: [String; 2],
+    pub input: Vec<String>,
+}
+
+impl Calldata {
+    pub fn new(proof: &Proof<Bn256>, public_inputs: &[FS]) -> Self {
+        let a = format::parse_g1(&proof.a);
+        let b = format::parse_g2(&proof.b);
+        let c = format::parse_g1(&proof.c);
+        Calldata {
+            a: [a.0, a.1],
+            b: [[b.0, b.1], [b.2, b.3]],
+            c: [c.0, c.1],
+            input: public_inputs
+                .iter()
+                .map(|v| format!("0x{:0>64}", v.0.to_str_radix(16)))
+                .collect(),
+        }
+    }
+
+    /// the arguments as written in a solidity or web3 call
+    pub fn to_args(&self) -> String {
+        let array = |values: &[String]| {
+            let quoted = values.iter().map(|v| format!("\"{}\"", v)).collect::<Vec<_>>();
+            format!("[{}]", quoted.join(","))
+        };
+        format!(
+            "{},[{},{}],{},{}",
+            array(&self.a),
+            array(&self.b[0]),
+            array(&self.b[1]),
+            array(&self.c),
+            array(&self.input)
+        )
+    }
+
+    /// abi encoding of the arguments, without the function selector
+    pub fn to_abi_hex(&self) -> String {
+        let words = self.a.iter()
+            .chain(self.b[0].iter())
+            .chain(self.b[1].iter())
+            .chain(self.c.iter())
+            .chain(self.input.iter());
+        format!("0x{}", words.map(|word| &word[2..]).collect::<String>())
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    let valid = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '$';
+    match chars.next() {
+        Some(first) => !first.is_ascii_digit() && valid(first) && chars.all(valid),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pairing::bn256::{G1Affine, G2Affine};
+    use pairing::{CurveAffine, CurveProjective};
+
+    fn test_vk() -> VerifyingKey<Bn256> {
+        let mut g1_double = G1Affine::one().into_projective();
+        g1_double.double();
+        let mut g1_neg = G1Affine::one();
+        g1_neg.negate();
+        let mut g2_neg = G2Affine::one();
+        g2_neg.negate();
+        VerifyingKey {
+            alpha_g1: g1_double.into_affine(),
+            beta_g1: G1Affine::zero(),
+            beta_g2: G2Affine::one(),
+            gamma_g2: g2_neg,
+            delta_g1: G1Affine::zero(),
+            delta_g2: G2Affine::one(),
+            ic: vec![G1Affine::one(), g1_neg, g1_double.into_affine()],
+        }
+    }
+
+    #[test]
+    fn test_solidity_golden() {
+        let goldens = [
+            (Pragma::V05, false, false, false, include_str!("testdata/verifier_05.sol")),
+            (Pragma::V05, false, true, false, include_str!("testdata/verifier_05_calldata.sol")),
+            (Pragma::V05, true, false, false, include_str!("testdata/verifier_05_view.sol")),
+            (Pragma::V05, true, true, false, include_str!("testdata/verifier_05_view_calldata.sol")),
+            (Pragma::V06, false, false, false, include_str!("testdata/verifier_06.sol")),
+            (Pragma::V06, false, true, false, include_str!("testdata/verifier_06_calldata.sol")),
+            (Pragma::V06, true, false, false, include_str!("testdata/verifier_06_view.sol")),
+            (Pragma::V06, true, true, false, include_str!("testdata/verifier_06_view_calldata.sol")),
+            (Pragma::V08, false, false, false, include_str!("testdata/verifier_08.sol")),
+            (Pragma::V08, false, true, false, include_str!("testdata/verifier_08_calldata.sol")),
+            (Pragma::V08, true, false, false, include_str!("testdata/verifier_08_view.sol")),
+            (Pragma::V08, true, true, false, include_str!("testdata/verifier_08_view_calldata.sol")),
+            (Pragma::V05, false, false, true, include_str!("testdata/verifier_05_batch.sol")),
+            (Pragma::V06, false, false, true, include_str!("testdata/verifier_06_batch.sol")),
+            (Pragma::V08, true, true, true, include_str!("testdata/verifier_08_view_calldata_batch.sol")),
+        ];
+        let vk = test_vk();
+        for (pragma, view, calldata, batch, golden) in goldens.iter() {
+            let options = SolidityOptions {
+                pragma: *pragma,
+                contract_name: "TestVerifier".to_string(),
+                view: *view,
+                calldata: *calldata,
+                batch: *batch,
+            };
+            let mut out = Vec::new();
+            generate_solidity(&vk, 2, &options, &mut out).unwrap();
+            assert_eq!(String::from_utf8(out).unwrap(), *golden, "{:?}", options);
+        }
+    }
+
+    #[test]
+    fn test_solidity_options() {
+        assert_eq!("0.6".parse::<Pragma>().unwrap(), Pragma::V06);
+        assert!("0.7".parse::<Pragma>().is_err());
+
+        let mut options = SolidityOptions::default();
+        options.contract_name = "1Verifier".to_string();
+        assert!(generate_solidity(&test_vk(), 2, &options, &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_calldata() {
+        let mut c = G1Affine::one();
+        c.negate();
+        let proof = Proof {
+            a: G1Affine::one(),
+            b: G2Affine::one(),
+            c,
+        };
+        let calldata = Calldata::new(&proof, &[FS::one(), FS::parse("33").unwrap()]);
+
+        let word = |hex: &str| format!("0x{:0>64}", hex);
+        let g2_x1 = "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2";
+        let g2_x0 = "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed";
+        let g2_y1 = "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b";
+        let g2_y0 = "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa";
+        let c_y = "30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45";
+        assert_eq!(
+            calldata,
+            Calldata {
+                a: [word("1"), word("2")],
+                b: [[word(g2_x1), word(g2_x0)], [word(g2_y1), word(g2_y0)]],
+                c: [word("1"), word(c_y)],
+                input: vec![word("1"), word("21")],
+            }
+        );
+        assert_eq!(
+            calldata.to_args(),
+            format!(
+                "[\"{}\",\"{}\"],[[\"{}\",\"{}\"],[\"{}\",\"{}\"]],[\"{}\",\"{}\"],[\"{}\",\"{}\"]",
+                word("1"), word("2"), word(g2_x1), word(g2_x0), word(g2_y1), word(g2_y0),
+                word("1"), word(c_y), word("1"), word("21")
+            )
+        );
+        let abi = calldata.to_abi_hex();
+        assert_eq!(abi.len(), 2 + 10 * 64);
+        assert_eq!(&abi[2 + 2 * 64..2 + 3 * 64], g2_x1);
+        assert_eq!(&abi[2 + 9 * 64..], word("21")[2..].to_string());
+    }
+}
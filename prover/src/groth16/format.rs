@@ -1,19 +1,23 @@
 extern crate rand;
 
-use pairing::bn256::Bn256;
-use regex::Regex;
+use pairing::{CurveAffine, EncodedPoint};
+use num_bigint::BigUint;
 
-use circom2_compiler::algebra::{Value, FS, LC, QEQ};
-use circom2_compiler::storage::Constraints;
+use circom2_compiler::algebra::{Field, SignalId, Value, Witness, FS, LC, QEQ, SIGNAL_ONE};
+use circom2_compiler::format::WireMap;
+use circom2_compiler::storage::{is_public_input, Constraints, Signals};
 use circom2_compiler::storage::Ram;
-use circom2_compiler::storage::RamConstraints;
+use circom2_compiler::storage::{RamConstraints, RamSignals};
 use circom2_compiler::storage::StorageFactory;
+use circom2_parser::ast::SignalType;
 
 use bellman::LinearCombination;
 
+use std::collections::HashMap;
 use std::io::{Read, Write};
 
-use bellman::groth16::{Parameters, Proof};
+use bellman::groth16::{Parameters, Proof, VerifyingKey};
+use blake2_rfc::blake2b::Blake2b;
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use ff::PrimeField;
 use pairing::Engine;
@@ -22,62 +26,63 @@ use error::{Error, Result};
 use serde_cbor::{from_slice, to_vec};
 use serde_json;
 
+use super::curve::{Curve, CURVE_BLS12_381, CURVE_BN256};
 use super::error;
 
 #[derive(Serialize, Deserialize)]
 struct JsonInputAndProof([String; 2],[[String; 2]; 2],[String; 2],Vec<String>);
 
-/*
-Taken from Thibaut Schaeffer's ZoKrates
-https://github.com/Zokrates/ZoKrates/commit/20790b72fff3b48a518dd7b910f7e005612faf95
-*/
-
-lazy_static! {
-    static ref G2_REGEX: Regex = Regex::new(r"G2\(x=Fq2\(Fq\((?P<x0>0[xX][0-9a-fA-F]{64})\) \+ Fq\((?P<x1>0[xX][0-9a-fA-F]{64})\) \* u\), y=Fq2\(Fq\((?P<y0>0[xX][0-9a-fA-F]{64})\) \+ Fq\((?P<y1>0[xX][0-9a-fA-F]{64})\) \* u\)\)").unwrap();
-}
-
-lazy_static! {
-    static ref G1_REGEX: Regex = Regex::new(
-        r"G1\(x=Fq\((?P<x>0[xX][0-9a-fA-F]{64})\), y=Fq\((?P<y>0[xX][0-9a-fA-F]{64})\)\)"
-    )
-    .unwrap();
+/// snarkjs groth16 verification key, points are decimal projective coordinates
+#[derive(Serialize, Deserialize)]
+struct JsonVerifyingKey {
+    protocol: String,
+    curve: String,
+    #[serde(rename = "nPublic")]
+    n_public: usize,
+    vk_alpha_1: [String; 3],
+    vk_beta_2: [[String; 2]; 3],
+    vk_gamma_2: [[String; 2]; 3],
+    vk_delta_2: [[String; 2]; 3],
+    #[serde(rename = "IC")]
+    ic: Vec<[String; 3]>,
 }
 
-lazy_static! {
-    static ref FR_REGEX: Regex = Regex::new(r"Fr\((?P<x>0[xX][0-9a-fA-F]{64})\)").unwrap();
+/// hex coordinates of a G1 point, the point at infinity is (0, 0)
+pub(crate) fn parse_g1<G: CurveAffine>(e: &G) -> (String, String) {
+    let c = coords(e, 2);
+    (c[0].clone(), c[1].clone())
 }
 
-fn parse_g1(e: &<Bn256 as bellman::pairing::Engine>::G1Affine) -> (String, String) {
-    let raw_e = e.to_string();
-
-    let captures = G1_REGEX.captures(&raw_e).unwrap();
-
-    (
-        captures.name(&"x").unwrap().as_str().to_string(),
-        captures.name(&"y").unwrap().as_str().to_string(),
-    )
+/// hex coordinates of a G2 point as (x.c1, x.c0, y.c1, y.c0)
+pub(crate) fn parse_g2<G: CurveAffine>(e: &G) -> (String, String, String, String) {
+    let c = coords(e, 4);
+    (c[0].clone(), c[1].clone(), c[2].clone(), c[3].clone())
 }
 
-fn parse_g2(e: &<Bn256 as bellman::pairing::Engine>::G2Affine) -> (String, String, String, String) {
-    let raw_e = e.to_string();
-
-    let captures = G2_REGEX.captures(&raw_e).unwrap();
-
-    (
-        captures.name(&"x1").unwrap().as_str().to_string(),
-        captures.name(&"x0").unwrap().as_str().to_string(),
-        captures.name(&"y1").unwrap().as_str().to_string(),
-        captures.name(&"y0").unwrap().as_str().to_string(),
-    )
+/// big endian coordinates of a point, in the order of its uncompressed encoding,
+///   which has no flag bits set for points other than infinity
+fn coords<G: CurveAffine>(e: &G, n: usize) -> Vec<String> {
+    let encoded = e.into_uncompressed();
+    let bytes = encoded.as_ref();
+    bytes
+        .chunks(bytes.len() / n)
+        .map(|coord| {
+            if e.is_zero() {
+                format!("0x{}", "00".repeat(coord.len()))
+            } else {
+                format!("0x{}", coord.iter().map(|b| format!("{:02x}", b)).collect::<String>())
+            }
+        })
+        .collect()
 }
 
-pub fn parse_g1_hex(e: &<Bn256 as bellman::pairing::Engine>::G1Affine) -> String {
+pub fn parse_g1_hex<G: CurveAffine>(e: &G) -> String {
     let parsed = parse_g1(e);
 
     format!("{}, {}", parsed.0, parsed.1)
 }
 
-pub fn parse_g2_hex(e: &<Bn256 as bellman::pairing::Engine>::G2Affine) -> String {
+pub fn parse_g2_hex<G: CurveAffine>(e: &G) -> String {
     let parsed = parse_g2(e);
 
     format!("[{}, {}], [{}, {}]", parsed.0, parsed.1, parsed.2, parsed.3,)
@@ -106,9 +111,9 @@ pub fn lc_to_bellman<E: Engine>(
     base
 }
 
-pub fn write_input_and_proof<W: Write>(
+pub fn write_input_and_proof<E: Engine, W: Write>(
     public_input: Vec<(String, FS)>,
-    proof: Proof<Bn256>,
+    proof: Proof<E>,
     out: &mut W,
 ) -> Result<()> {
     let a = parse_g1(&proof.a);
@@ -129,42 +134,432 @@ pub fn write_input_and_proof<W: Write>(
     Ok(())
 }
 
-pub fn write_pk<W: Write, C: Constraints>(
+/// read a proof written by `write_input_and_proof`, returns the public inputs and the proof
+pub fn read_input_and_proof<E: Engine, R: Read>(input: R) -> Result<(Vec<FS>, Proof<E>)> {
+    let JsonInputAndProof(a, b, c, public_input) = serde_json::from_reader(input)?;
+
+    // b coordinates are written as c1, c0
+    let b = [
+        [b[0][1].clone(), b[0][0].clone()],
+        [b[1][1].clone(), b[1][0].clone()],
+    ];
+    let proof = Proof {
+        a: g1_from_str(&a)?,
+        b: g2_from_str(&b)?,
+        c: g1_from_str(&c)?,
+    };
+    let public_input = public_input
+        .iter()
+        .map(|v| FS::parse(v))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok((public_input, proof))
+}
+
+/// signals of the circuit stored in the proving key, enough to build the
+///   assignment from a witness file without the circuit source
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CircuitInfo {
+    pub wire_to_signal: Vec<SignalId>,
+    pub public: Vec<(SignalId, String)>,
+    /// `circuit_hash` of the source the keys were created from, to refuse
+    ///   proving another circuit. `None` for imported circuits and older keys
+    #[serde(default)]
+    pub source_hash: Option<String>,
+}
+
+impl CircuitInfo {
+    pub fn new<S: Signals>(signals: &S, source_hash: Option<&str>) -> Result<Self> {
+        let wires = WireMap::new(signals)?;
+        let mut public = Vec::new();
+        for signal in signals.iter_range(SIGNAL_ONE + 1..signals.len()?) {
+            let signal = signal?;
+            if is_public_input(&signal) {
+                public.push((signal.id, signal.full_name.to_string()));
+            }
+        }
+        Ok(CircuitInfo {
+            wire_to_signal: (0..wires.len()).map(|wire| wires.signal(wire)).collect(),
+            public,
+            source_hash: source_hash.map(str::to_string),
+        })
+    }
+
+    /// signals with the values of a witness ordered by wire, signals that are
+    ///   not public are named after their wire, the witness values are in `field`
+    pub fn signals(&self, field: Field, witness: &Witness) -> Result<RamSignals> {
+        if witness.len() != self.wire_to_signal.len() {
+            return Err(Error::Mismatch(format!(
+                "witness has {} values but the circuit has {} wires",
+                witness.len(),
+                self.wire_to_signal.len()
+            )));
+        }
+        let mut signal_to_wire = vec![0; self.wire_to_signal.len()];
+        for (wire, signal) in self.wire_to_signal.iter().enumerate() {
+            signal_to_wire[*signal] = wire;
+        }
+        let public = self.public.iter().cloned().collect::<HashMap<_, _>>();
+
+        let mut signals = Ram::new(field).new_signals()?;
+        for (signal, wire) in signal_to_wire.iter().enumerate().skip(1) {
+            let value = Some(Value::from(witness.0[*wire].clone()));
+            match public.get(&signal) {
+                Some(name) => signals.insert(name.clone(), SignalType::PublicInput, value)?,
+                None => signals.insert(format!("wire{}", wire), SignalType::Internal, value)?,
+            };
+        }
+        Ok(signals)
+    }
+}
+
+// proving key layout
+//
+//   magic "c2pk", version : u32, curve : u32, n signals : u64, n constraints : u64,
+//   blake2b of the constraints : 64 bytes, size of the constraints : u64
+//   constraints   : cbor QEQ, each prefixed with its size : u32
+//   circuit       : cbor CircuitInfo prefixed with its size : u32
+//   verifying key : bellman format prefixed with its size : u32
+//   parameters    : bellman format
+//
+pub(crate) const PK_MAGIC: &[u8; 4] = b"c2pk";
+pub(crate) const PK_VERSION: u32 = 1;
+pub(crate) const PK_HASH_LEN: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PkHeader {
+    pub version: u32,
+    pub curve: u32,
+    pub n_signals: u64,
+    pub n_constraints: u64,
+    pub hash: Vec<u8>,
+    /// bytes of the constraints section
+    pub constraints_len: u64,
+}
+
+pub fn write_pk<E: Curve, W: Write, S: Signals, C: Constraints>(
     mut pk: W,
+    signals: &S,
+    constraints: &C,
+    source_hash: Option<&str>,
+    params: &Parameters<E>,
+) -> Result<()> {
+    write_pk_circuit::<E, _, _, _>(&mut pk, PK_MAGIC, PK_VERSION, signals, constraints, source_hash)?;
+
+    // the verifying key on its own, so it can be read without the parameters
+    let mut vk = Vec::new();
+    params.vk.write(&mut vk)?;
+    pk.write_u32::<BigEndian>(vk.len() as u32)?;
+    pk.write_all(&vk)?;
+
+    params.write(pk)?;
+    Ok(())
+}
+
+/// header, constraints and circuit sections of a proving key, shared by the
+///   groth16 and plonk keys that only differ in the magic and what follows
+pub(crate) fn write_pk_circuit<E: Curve, W: Write, S: Signals, C: Constraints>(
+    pk: &mut W,
+    magic: &[u8; 4],
+    version: u32,
+    signals: &S,
     constraints: &C,
-    params: &Parameters<Bn256>,
+    source_hash: Option<&str>,
 ) -> Result<()> {
+    let (hash, constraints_len) = constraints_hash(constraints)?;
+
+    pk.write_all(magic)?;
+    pk.write_u32::<BigEndian>(version)?;
+    pk.write_u32::<BigEndian>(E::ID)?;
+    pk.write_u64::<BigEndian>(signals.len()? as u64)?;
+    pk.write_u64::<BigEndian>(constraints.len()? as u64)?;
+    pk.write_all(&hash)?;
+    pk.write_u64::<BigEndian>(constraints_len)?;
+
     // write constratins & proving key
-    pk.write_u32::<BigEndian>(constraints.len()? as u32)?;
-    for i in 0..constraints.len()? {
-        let qeq = to_vec(&constraints.get(i)?)?;
+    for qeq in constraints.iter() {
+        let qeq = to_vec(&qeq?)?;
         pk.write_u32::<BigEndian>(qeq.len() as u32)?;
         pk.write(&qeq)?;
     }
 
-    params.write(pk)?;
+    let circuit = to_vec(&CircuitInfo::new(signals, source_hash)?)?;
+    pk.write_u32::<BigEndian>(circuit.len() as u32)?;
+    pk.write_all(&circuit)?;
     Ok(())
 }
 
-pub fn read_pk<R: Read>(mut pk: R) -> Result<(RamConstraints, Parameters<Bn256>)> {
+/// blake2b of the constraints as stored in a proving key, and their size
+pub(crate) fn constraints_hash<C: Constraints>(constraints: &C) -> Result<(Vec<u8>, u64)> {
+    let mut hasher = Blake2b::new(PK_HASH_LEN);
+    let mut constraints_len = 0u64;
+    for qeq in constraints.iter() {
+        let qeq = to_vec(&qeq?)?;
+        hasher.update(&qeq);
+        constraints_len += 4 + qeq.len() as u64;
+    }
+    Ok((hasher.finalize().as_bytes().to_vec(), constraints_len))
+}
+
+/// read the header of a proving key, fails if it is not a proving key of this
+///   protocol up to `expected_version`
+pub(crate) fn read_header<R: Read>(pk: &mut R, expected_magic: &[u8; 4], expected_version: u32) -> Result<PkHeader> {
+    let mut magic = [0u8; 4];
+    pk.read_exact(&mut magic)?;
+    if &magic != expected_magic {
+        return Err(Error::BadFormat(
+            "not a proving key of this protocol, or generated by an older version".to_string(),
+        ));
+    }
+    let version = pk.read_u32::<BigEndian>()?;
+    if version == 0 || version > expected_version {
+        return Err(Error::BadFormat(format!(
+            "unsupported proving key version {}",
+            version
+        )));
+    }
+    let curve = pk.read_u32::<BigEndian>()?;
+    if curve != CURVE_BN256 && curve != CURVE_BLS12_381 {
+        return Err(Error::BadFormat(format!("unsupported curve {}", curve)));
+    }
+    let n_signals = pk.read_u64::<BigEndian>()?;
+    let n_constraints = pk.read_u64::<BigEndian>()?;
+    let mut hash = vec![0u8; PK_HASH_LEN];
+    pk.read_exact(&mut hash)?;
+    let constraints_len = pk.read_u64::<BigEndian>()?;
+
+    Ok(PkHeader {
+        version,
+        curve,
+        n_signals,
+        n_constraints,
+        hash,
+        constraints_len,
+    })
+}
+
+pub fn read_pk<E: Curve, R: Read>(mut pk: R) -> Result<(RamConstraints, CircuitInfo, Parameters<E>)> {
+    let (constraints, circuit) = read_pk_circuit::<E, _>(&mut pk, PK_MAGIC, PK_VERSION)?;
+
+    let vk_len = pk.read_u32::<BigEndian>()? as u64;
+    skip(&mut pk, vk_len)?;
+
+    let params: Parameters<E> = Parameters::read(pk, true)?;
+
+    Ok((constraints, circuit, params))
+}
+
+/// read a proving key skipping its constraints, the caller checks that they are
+///   the ones of its circuit with the hash of the header
+pub(crate) fn read_pk_skipping<E: Curve, R: Read>(mut pk: R) -> Result<(PkHeader, CircuitInfo, Parameters<E>)> {
+    let header = read_header(&mut pk, PK_MAGIC, PK_VERSION)?;
+    check_curve::<E>(&header)?;
+    skip(&mut pk, header.constraints_len)?;
+
+    let len = pk.read_u32::<BigEndian>()? as u64;
+    let mut buffer = Vec::new();
+    (&mut pk).take(len).read_to_end(&mut buffer)?;
+    let circuit = from_slice::<CircuitInfo>(&buffer)?;
+    if circuit.wire_to_signal.len() as u64 != header.n_signals {
+        return Err(Error::BadFormat("proving key signals are corrupted".to_string()));
+    }
+
+    let vk_len = pk.read_u32::<BigEndian>()? as u64;
+    skip(&mut pk, vk_len)?;
+
+    let params: Parameters<E> = Parameters::read(pk, true)?;
+    Ok((header, circuit, params))
+}
+
+/// read the sections written by `write_pk_circuit`, checking the hash of the constraints
+pub(crate) fn read_pk_circuit<E: Curve, R: Read>(
+    pk: &mut R,
+    magic: &[u8; 4],
+    version: u32,
+) -> Result<(RamConstraints, CircuitInfo)> {
+    let header = read_header(pk, magic, version)?;
+    check_curve::<E>(&header)?;
+
     let mut buffer = Vec::with_capacity(1024);
     let mut constraints = Ram::default().new_constraints()?;
-    let count = pk.read_u32::<BigEndian>()?;
+    let mut hasher = Blake2b::new(PK_HASH_LEN);
+    let mut constraints_len = 0u64;
 
-    for _ in 0..count {
+    for _ in 0..header.n_constraints {
         let len = pk.read_u32::<BigEndian>()? as usize;
         if len > buffer.capacity() {
             buffer.reserve(len - buffer.capacity());
         }
         buffer.resize(len, 0u8);
         pk.read_exact(&mut buffer)?;
+        hasher.update(&buffer);
+        constraints_len += 4 + len as u64;
         let qeq = from_slice::<QEQ>(&buffer)?;
         constraints.push(qeq, None)?;
     }
+    if hasher.finalize().as_bytes() != &header.hash[..]
+        || header.constraints_len != constraints_len
+    {
+        return Err(Error::BadFormat("proving key constraints are corrupted".to_string()));
+    }
+
+    let len = pk.read_u32::<BigEndian>()? as usize;
+    buffer.resize(len, 0u8);
+    pk.read_exact(&mut buffer)?;
+    let circuit = from_slice::<CircuitInfo>(&buffer)?;
+    if circuit.wire_to_signal.len() as u64 != header.n_signals {
+        return Err(Error::BadFormat("proving key signals are corrupted".to_string()));
+    }
+
+    Ok((constraints, circuit))
+}
+
+/// read only the verifying key stored in a proving key file
+pub fn read_pk_vk<E: Curve, R: Read>(mut pk: R) -> Result<VerifyingKey<E>> {
+    skip_pk_circuit::<E, _>(&mut pk, PK_MAGIC, PK_VERSION)?;
+    let _ = pk.read_u32::<BigEndian>()?;
+    Ok(VerifyingKey::read(pk)?)
+}
+
+/// skip the sections written by `write_pk_circuit` without parsing them
+pub(crate) fn skip_pk_circuit<E: Curve, R: Read>(pk: &mut R, magic: &[u8; 4], version: u32) -> Result<()> {
+    let header = read_header(pk, magic, version)?;
+    check_curve::<E>(&header)?;
+    skip(pk, header.constraints_len)?;
+    let circuit_len = pk.read_u32::<BigEndian>()? as u64;
+    skip(pk, circuit_len)
+}
+
+pub(crate) fn check_curve<E: Curve>(header: &PkHeader) -> Result<()> {
+    if header.curve != E::ID {
+        return Err(Error::Mismatch(format!(
+            "the proving key is not for the {} curve",
+            E::NAME
+        )));
+    }
+    Ok(())
+}
+
+pub(crate) fn skip<R: Read>(input: &mut R, len: u64) -> Result<()> {
+    std::io::copy(&mut input.take(len), &mut std::io::sink())?;
+    Ok(())
+}
+
+/// write the verifying key in the snarkjs verification_key.json format
+pub fn write_vk_json<E: Curve, W: Write>(vk: &VerifyingKey<E>, out: W) -> Result<()> {
+    let g1 = |p: &E::G1Affine| {
+        let (x, y) = parse_g1(p);
+        [hex_to_dec(&x), hex_to_dec(&y), "1".to_string()]
+    };
+    let g2 = |p: &E::G2Affine| {
+        let (x1, x0, y1, y0) = parse_g2(p);
+        [
+            [hex_to_dec(&x0), hex_to_dec(&x1)],
+            [hex_to_dec(&y0), hex_to_dec(&y1)],
+            ["1".to_string(), "0".to_string()],
+        ]
+    };
+
+    let json = JsonVerifyingKey {
+        protocol: "groth16".to_string(),
+        curve: E::NAME.to_string(),
+        n_public: vk.ic.len() - 1,
+        vk_alpha_1: g1(&vk.alpha_g1),
+        vk_beta_2: g2(&vk.beta_g2),
+        vk_gamma_2: g2(&vk.gamma_g2),
+        vk_delta_2: g2(&vk.delta_g2),
+        ic: vk.ic.iter().map(g1).collect(),
+    };
+
+    serde_json::to_writer_pretty(out, &json)?;
+    Ok(())
+}
+
+/// read a snarkjs verification_key.json, `beta_g1` and `delta_g1` are not part
+///   of the format and are not needed to verify, so they are set to zero
+pub fn read_vk_json<E: Curve, R: Read>(input: R) -> Result<VerifyingKey<E>> {
+    let json: JsonVerifyingKey = serde_json::from_reader(input)?;
+    if json.protocol != "groth16" && json.protocol != "groth" {
+        return Err(Error::BadFormat(format!("unsupported protocol {}", json.protocol)));
+    }
+    if json.curve.to_lowercase() != E::NAME {
+        return Err(Error::Mismatch(format!(
+            "the verification key is for the {} curve, not {}",
+            json.curve,
+            E::NAME
+        )));
+    }
+    if json.ic.len() != json.n_public + 1 {
+        return Err(Error::BadFormat("IC length does not match nPublic".to_string()));
+    }
+    Ok(VerifyingKey {
+        alpha_g1: g1_from_str(&json.vk_alpha_1)?,
+        beta_g1: E::G1Affine::zero(),
+        beta_g2: g2_from_str(&json.vk_beta_2)?,
+        gamma_g2: g2_from_str(&json.vk_gamma_2)?,
+        delta_g1: E::G1Affine::zero(),
+        delta_g2: g2_from_str(&json.vk_delta_2)?,
+        ic: json.ic.iter().map(|p| g1_from_str(p)).collect::<Result<Vec<_>>>()?,
+    })
+}
+
+pub(crate) fn hex_to_dec(hex: &str) -> String {
+    BigUint::parse_bytes(hex[2..].as_bytes(), 16)
+        .expect("bad hex coordinate")
+        .to_str_radix(10)
+}
 
-    let params: Parameters<Bn256> = Parameters::read(pk, true)?;
+/// big endian bytes of a decimal or 0x hex coordinate
+fn coord_to_be(coord: &str, out: &mut [u8]) -> Result<()> {
+    let be = FS::parse(coord)?.0.to_bytes_be();
+    if be.len() > out.len() {
+        return Err(Error::BadFormat(format!("coordinate too large {}", coord)));
+    }
+    let offset = out.len() - be.len();
+    out[offset..].copy_from_slice(&be);
+    Ok(())
+}
+
+pub(crate) fn g1_from_str<G: CurveAffine>(p: &[String]) -> Result<G> {
+    if (p.len() > 2 && p[2] == "0") || is_zero(&p[..2]) {
+        return Ok(G::zero());
+    }
+    let mut encoded = G::Uncompressed::empty();
+    {
+        let bytes = encoded.as_mut();
+        let size = bytes.len() / 2;
+        coord_to_be(&p[0], &mut bytes[0..size])?;
+        coord_to_be(&p[1], &mut bytes[size..2 * size])?;
+    }
+    encoded
+        .into_affine()
+        .map_err(|err| Error::BadFormat(format!("bad G1 point: {}", err)))
+}
+
+pub(crate) fn g2_from_str<G: CurveAffine>(p: &[[String; 2]]) -> Result<G> {
+    if (p.len() > 2 && p[2][0] == "0" && p[2][1] == "0") || (is_zero(&p[0]) && is_zero(&p[1])) {
+        return Ok(G::zero());
+    }
+    // encoded as x.c1, x.c0, y.c1, y.c0
+    let mut encoded = G::Uncompressed::empty();
+    {
+        let bytes = encoded.as_mut();
+        let size = bytes.len() / 4;
+        coord_to_be(&p[0][1], &mut bytes[0..size])?;
+        coord_to_be(&p[0][0], &mut bytes[size..2 * size])?;
+        coord_to_be(&p[1][1], &mut bytes[2 * size..3 * size])?;
+        coord_to_be(&p[1][0], &mut bytes[3 * size..4 * size])?;
+    }
+    encoded
+        .into_affine()
+        .map_err(|err| Error::BadFormat(format!("bad G2 point: {}", err)))
+}
 
-    Ok((constraints, params))
+/// (0, 0) is the point at infinity in the proof and solidity encodings
+fn is_zero(coords: &[String]) -> bool {
+    coords
+        .iter()
+        .all(|c| FS::parse(c).map(|fs| fs.0 == BigUint::from(0u32)).unwrap_or(false))
 }
 
 pub fn flatten_json(prefix: &str, json: &str) -> Result<Vec<(String, FS)>> {
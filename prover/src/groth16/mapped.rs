@@ -0,0 +1,330 @@
+use std::cmp::min;
+use std::fs::File;
+use std::io;
+use std::ops::Range;
+use std::sync::Arc;
+
+use bellman::groth16::{ParameterSource, VerifyingKey};
+use bellman::multiexp::{Source, SourceBuilder};
+use bellman::SynthesisError;
+use blake2_rfc::blake2b::Blake2b;
+use byteorder::{BigEndian, ReadBytesExt};
+use memmap::Mmap;
+use pairing::{CurveAffine, EncodedPoint};
+use serde_cbor::from_slice;
+
+use circom2_compiler::algebra::QEQ;
+use circom2_compiler::progress::{Phase, Progress};
+
+use super::curve::Curve;
+use super::error::{Error, Result};
+use super::format::{check_curve, read_header, CircuitInfo, PkHeader, PK_HASH_LEN, PK_MAGIC, PK_VERSION};
+
+/// points of the parameters decoded at once by each reader of a section
+#[cfg(not(test))]
+const CHUNK: usize = 1 << 16;
+/// small enough for the test keys to span many chunks
+#[cfg(test)]
+const CHUNK: usize = 2;
+
+/// points of the parameters, `len` points of `size` bytes from `offset`
+#[derive(Clone, Copy, Debug)]
+struct Section {
+    offset: usize,
+    size: usize,
+    len: usize,
+}
+
+/// proving key read through a memory map, only the header, the circuit and the
+///   verifying key are decoded when opened. The constraints are decoded one by
+///   one and the points of the parameters in chunks while proving
+pub struct MappedKey<E: Curve> {
+    map: Arc<Mmap>,
+    header: PkHeader,
+    constraints: Range<usize>,
+    pub circuit: CircuitInfo,
+    pub vk: VerifyingKey<E>,
+    h: Section,
+    l: Section,
+    a: Section,
+    b_g1: Section,
+    b_g2: Section,
+}
+
+impl<E: Curve> MappedKey<E> {
+    /// map the key at `path`, reported as the `LoadKey` phase
+    pub fn open(path: &str, progress: &mut dyn Progress) -> Result<Self> {
+        progress.start(Phase::LoadKey, None);
+        let file = File::open(path)?;
+        // the key is only read, it must not be modified while mapped
+        let map = unsafe { Mmap::map(&file)? };
+
+        let total = map.len();
+        let mut input = &map[..];
+        let header = read_header(&mut input, PK_MAGIC, PK_VERSION)?;
+        check_curve::<E>(&header)?;
+
+        let start = total - input.len();
+        take(&mut input, header.constraints_len as usize)?;
+        let constraints = start..total - input.len();
+
+        let len = input.read_u32::<BigEndian>()? as usize;
+        let circuit = from_slice::<CircuitInfo>(take(&mut input, len)?)?;
+        if circuit.wire_to_signal.len() as u64 != header.n_signals {
+            return Err(Error::BadFormat("proving key signals are corrupted".to_string()));
+        }
+
+        let len = input.read_u32::<BigEndian>()? as usize;
+        let vk = VerifyingKey::<E>::read(take(&mut input, len)?)?;
+        // the parameters start with the same verifying key
+        take(&mut input, len)?;
+
+        let h = section::<E::G1Affine>(total, &mut input)?;
+        let l = section::<E::G1Affine>(total, &mut input)?;
+        let a = section::<E::G1Affine>(total, &mut input)?;
+        let b_g1 = section::<E::G1Affine>(total, &mut input)?;
+        let b_g2 = section::<E::G2Affine>(total, &mut input)?;
+
+        progress.finish(Phase::LoadKey, header.n_constraints as usize);
+        Ok(MappedKey {
+            map: Arc::new(map),
+            header,
+            constraints,
+            circuit,
+            vk,
+            h,
+            l,
+            a,
+            b_g1,
+            b_g2,
+        })
+    }
+
+    pub fn n_constraints(&self) -> usize {
+        self.header.n_constraints as usize
+    }
+
+    /// call `f` with each constraint and its position, the hash of the
+    ///   constraints is checked after the last one
+    pub fn for_each_constraint<F: FnMut(usize, QEQ) -> Result<()>>(&self, mut f: F) -> Result<()> {
+        let mut input = &self.map[self.constraints.clone()];
+        let mut hasher = Blake2b::new(PK_HASH_LEN);
+        for n in 0..self.n_constraints() {
+            let len = input.read_u32::<BigEndian>()? as usize;
+            let qeq = take(&mut input, len)?;
+            hasher.update(qeq);
+            f(n, from_slice::<QEQ>(qeq)?)?;
+        }
+        if !input.is_empty() || hasher.finalize().as_bytes() != &self.header.hash[..] {
+            return Err(Error::BadFormat("proving key constraints are corrupted".to_string()));
+        }
+        Ok(())
+    }
+
+    /// the points of `section` from the one at `start`
+    fn points<G: CurveAffine>(&self, section: Section, start: usize) -> Points<G> {
+        Points {
+            map: self.map.clone(),
+            section,
+            next: start,
+            start,
+            chunk: Vec::new(),
+        }
+    }
+}
+
+/// the parameters for the bellman prover, the bases of the inputs and of the
+///   aux variables are in the same section
+impl<'a, E: Curve> ParameterSource<E> for &'a MappedKey<E> {
+    type G1Builder = Points<E::G1Affine>;
+    type G2Builder = Points<E::G2Affine>;
+
+    fn get_vk(&mut self, _: usize) -> std::result::Result<VerifyingKey<E>, SynthesisError> {
+        Ok(self.vk.clone())
+    }
+
+    fn get_h(&mut self, _: usize) -> std::result::Result<Self::G1Builder, SynthesisError> {
+        Ok(self.points(self.h, 0))
+    }
+
+    fn get_l(&mut self, _: usize) -> std::result::Result<Self::G1Builder, SynthesisError> {
+        Ok(self.points(self.l, 0))
+    }
+
+    fn get_a(
+        &mut self,
+        num_inputs: usize,
+        _: usize,
+    ) -> std::result::Result<(Self::G1Builder, Self::G1Builder), SynthesisError> {
+        Ok((self.points(self.a, 0), self.points(self.a, num_inputs)))
+    }
+
+    fn get_b_g1(
+        &mut self,
+        num_inputs: usize,
+        _: usize,
+    ) -> std::result::Result<(Self::G1Builder, Self::G1Builder), SynthesisError> {
+        Ok((self.points(self.b_g1, 0), self.points(self.b_g1, num_inputs)))
+    }
+
+    fn get_b_g2(
+        &mut self,
+        num_inputs: usize,
+        _: usize,
+    ) -> std::result::Result<(Self::G2Builder, Self::G2Builder), SynthesisError> {
+        Ok((self.points(self.b_g2, 0), self.points(self.b_g2, num_inputs)))
+    }
+}
+
+/// points of a section read in order from `next`, decoded `CHUNK` at a time.
+///   The subgroup checks would cost more than the multiexponentiations, that
+///   read the points once per window, so they are skipped: a bad point gives a
+///   proof that fails its self verification
+#[derive(Clone)]
+pub struct Points<G: CurveAffine> {
+    map: Arc<Mmap>,
+    section: Section,
+    next: usize,
+    /// `chunk` holds the points from `start`
+    start: usize,
+    chunk: Vec<G>,
+}
+
+impl<G: CurveAffine> Points<G> {
+    fn point(&mut self) -> std::result::Result<G, SynthesisError> {
+        if self.next >= self.section.len {
+            return Err(truncated());
+        }
+        if self.next < self.start || self.next >= self.start + self.chunk.len() {
+            let Section { offset, size, len } = self.section;
+            let end = min(self.next + CHUNK, len);
+            self.chunk = self.map[offset + self.next * size..offset + end * size]
+                .chunks(size)
+                .map(decode)
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            self.start = self.next;
+        }
+        let point = self.chunk[self.next - self.start];
+        self.next += 1;
+        Ok(point)
+    }
+}
+
+impl<G: CurveAffine> SourceBuilder<G> for Points<G> {
+    type Source = Self;
+
+    fn new(self) -> Self {
+        self
+    }
+}
+
+impl<G: CurveAffine> Source<G> for Points<G> {
+    fn add_assign_mixed(&mut self, to: &mut G::Projective) -> std::result::Result<(), SynthesisError> {
+        to.add_assign_mixed(&self.point()?);
+        Ok(())
+    }
+
+    fn skip(&mut self, amt: usize) -> std::result::Result<(), SynthesisError> {
+        if self.next + amt > self.section.len {
+            return Err(truncated());
+        }
+        self.next += amt;
+        Ok(())
+    }
+}
+
+fn truncated() -> SynthesisError {
+    SynthesisError::from(io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "the proving key has less points than its constraints need",
+    ))
+}
+
+/// a point of the parameters, only checking its encoding
+fn decode<G: CurveAffine>(bytes: &[u8]) -> std::result::Result<G, SynthesisError> {
+    let mut encoded = G::Uncompressed::empty();
+    encoded.as_mut().copy_from_slice(bytes);
+    match encoded.into_affine_unchecked() {
+        Ok(point) if !point.is_zero() => Ok(point),
+        Ok(_) => Err(SynthesisError::UnexpectedIdentity),
+        Err(err) => Err(SynthesisError::from(io::Error::new(io::ErrorKind::InvalidData, err))),
+    }
+}
+
+/// the first `len` bytes of `input`, that is advanced past them
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    if input.len() < len {
+        return Err(Error::BadFormat("proving key is truncated".to_string()));
+    }
+    let (taken, rest) = input.split_at(len);
+    *input = rest;
+    Ok(taken)
+}
+
+/// locate the points that follow at `input`, in a map of `total` bytes
+fn section<G: CurveAffine>(total: usize, input: &mut &[u8]) -> Result<Section> {
+    let len = input.read_u32::<BigEndian>()? as usize;
+    let size = G::Uncompressed::size();
+    let offset = total - input.len();
+    take(input, len * size)?;
+    Ok(Section { offset, size, len })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::groth16::format::read_pk_skipping;
+    use crate::groth16::phase1::{domain_size, Phase1};
+    use crate::groth16::prover::{new_rng, setup_phase1};
+    use circom2_compiler::evaluator::{Evaluator, Mode, Scope};
+    use circom2_compiler::progress::NoProgress;
+    use circom2_compiler::storage::{Constraints, Ram, StorageFactory};
+    use pairing::bn256::{Bn256, G1Affine, G2Affine};
+    use std::io::Write;
+
+    #[test]
+    fn test_mapped_points() {
+        let mut ram = Ram::default();
+        let mut ev = Evaluator::new(Mode::GenConstraints, ram.new_signals().unwrap(), ram.new_constraints().unwrap());
+        ev.eval_inline(
+            &mut Scope::new(true, None, "root".to_string()),
+            "
+            template t() {
+                signal private input a;
+                signal private input b;
+                signal output c;
+                signal s;
+
+                s <== a * b;
+                c <== (s + 1) * (a + b);
+            }
+            component main = t();
+            ",
+        )
+        .unwrap();
+
+        let (size, _) = domain_size(ev.constraints.len().unwrap() + 2).unwrap();
+        let phase1 = Phase1::insecure(size, &mut new_rng(Some(1)));
+        let mut pk = Vec::new();
+        setup_phase1(&phase1, &ev.signals, &ev.constraints, None, &mut pk, None::<Vec<u8>>, Vec::new(), &mut new_rng(Some(2)), &mut NoProgress)
+            .unwrap();
+        File::create("/tmp/mapped_points.pk").unwrap().write_all(&pk).unwrap();
+        let key = MappedKey::<Bn256>::open("/tmp/mapped_points.pk", &mut NoProgress).unwrap();
+        let (_, _, params) = read_pk_skipping::<Bn256, _>(&pk[..]).unwrap();
+
+        // the points are the same through the chunks
+        let mut h = key.points::<G1Affine>(key.h, 0);
+        let points = (0..params.h.len()).map(|_| h.point().unwrap()).collect::<Vec<_>>();
+        assert_eq!(*params.h, points);
+        assert!(h.point().is_err());
+
+        // from an offset and past skipped points
+        let mut b_g2 = key.points::<G2Affine>(key.b_g2, 1);
+        b_g2.skip(1).unwrap();
+        assert_eq!(params.b_g2[2], b_g2.point().unwrap());
+        assert!(b_g2.skip(params.b_g2.len()).is_err());
+        let mut a = key.points::<G1Affine>(key.a, params.a.len() - 1);
+        assert_eq!(params.a[params.a.len() - 1], a.point().unwrap());
+        assert!(a.point().is_err());
+    }
+}
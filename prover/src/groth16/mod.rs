@@ -1,10 +1,23 @@
+mod curve;
 mod format;
 mod error;
 mod ethereum;
+mod mapped;
+mod phase1;
+mod phase2;
 mod prover;
 mod ram;
+mod stream;
 
 pub use error::{Error,Result};
-pub use prover::{generate_verified_proof,setup,bellman_verbose};
-pub use ram::{prove_ram,setup_ram};
-pub use format::flatten_json;
\ No newline at end of file
+pub use curve::{Bls12,Bn256,Curve,CurveName};
+pub use prover::{generate_verified_proof,generate_verified_proof_with,new_rng,prove_witness,setup,setup_phase1,verify,verify_batch,verify_files,read_vk_file,write_keys};
+pub use ethereum::{generate_solidity,Calldata,Pragma,SolidityOptions};
+pub use phase1::{circuit_size,Phase1};
+pub use phase2::{Contribution,MpcParameters};
+pub use mapped::MappedKey;
+pub use stream::{prove_mapped,prove_witness_mapped};
+pub use ram::{prove_ram,setup_ram,witness_ram,Prover};
+pub use format::{flatten_json,CircuitInfo,read_input_and_proof,read_pk_vk,read_vk_json,write_vk_json};
+pub(crate) use prover::{check_circuit,check_field};
+pub(crate) use format::{fe_to_bellman_fr,g1_from_str,g2_from_str,hex_to_dec,parse_g1,parse_g2,read_pk_circuit,skip_pk_circuit,write_pk_circuit};
@@ -0,0 +1,554 @@
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::sync::Arc;
+
+use bellman::groth16::{Parameters, VerifyingKey};
+use bellman::SynthesisError;
+use byteorder::{LittleEndian, ReadBytesExt};
+use ff::{Field, PrimeField};
+use num_bigint::BigUint;
+use pairing::bn256::{Bn256, Fr, FrRepr, G1Affine, G2Affine, G1, G2};
+use pairing::{CurveAffine, CurveProjective, EncodedPoint};
+use rand::{Rand, Rng};
+
+use circom2_compiler::algebra::SIGNAL_ONE;
+use circom2_compiler::storage::{count_public_inputs, is_public_input, Constraints, Signals};
+
+use crate::group::{fft, Group};
+
+use super::error::{Error, Result};
+use super::format::fe_to_bellman_fr;
+use super::prover::check_field;
+
+// bellman powers of tau `response` file for BN254, points are compressed
+//
+//   hash of the challenge : 64 bytes
+//   tau^i * G1            : 2 * 2^power - 1
+//   tau^i * G2            : 2^power
+//   alpha * tau^i * G1    : 2^power
+//   beta * tau^i * G1     : 2^power
+//   beta * G2             : 1
+//   contribution public key, uncompressed : 6 G1 + 3 G2
+//
+const RESPONSE_HASH_LEN: u64 = 64;
+const RESPONSE_PUBKEY_LEN: u64 = 6 * 64 + 3 * 128;
+
+// snarkjs `.ptau` file, little endian, points are uncompressed x, y with each
+//   coordinate in montgomery form (G2 coordinates as c0, c1)
+//
+//   "ptau", version u32, number of sections u32
+//   each section: type u32, length u64, data
+//     1 header: n8 u32, prime q (n8 bytes), power u32, ceremony power u32
+//     2 tau^i * G1, 3 tau^i * G2, 4 alpha * tau^i * G1, 5 beta * tau^i * G1, 6 beta * G2,
+//       with the same number of points than the response file
+//   the other sections (contributions, lagrange basis) are ignored
+//
+const PTAU_MAGIC: &[u8; 4] = b"ptau";
+const PTAU_VERSION: u32 = 1;
+
+lazy_static! {
+    static ref Q: BigUint = BigUint::parse_bytes(
+        b"21888242871839275222246405745257275088696311157297823662689037894645226208583",
+        10
+    )
+    .unwrap();
+    // inverse of the montgomery factor 2^256 mod q
+    static ref R_INV: BigUint = {
+        let r = (BigUint::from(1u32) << 256) % &*Q;
+        r.modpow(&(Q.clone() - BigUint::from(2u32)), &Q)
+    };
+}
+
+/// phase-1 powers of tau, enough to derive the parameters of any circuit
+///   with up to `size()` constraints
+pub struct Phase1 {
+    pub tau_g1: Vec<G1Affine>,
+    pub tau_g2: Vec<G2Affine>,
+    pub alpha_tau_g1: Vec<G1Affine>,
+    pub beta_tau_g1: Vec<G1Affine>,
+    pub beta_g2: G2Affine,
+}
+
+impl Phase1 {
+    /// the power of a response file of `len` bytes
+    pub fn response_power(len: u64) -> Option<u32> {
+        let points = len.checked_sub(RESPONSE_HASH_LEN + RESPONSE_PUBKEY_LEN + 64 - 32)?;
+        if points % 192 != 0 || !(points / 192).is_power_of_two() {
+            return None;
+        }
+        Some((points / 192).trailing_zeros())
+    }
+
+    /// read the first `size` powers of a `response` file of 2^`power` powers
+    pub fn read_response<R: Read>(mut input: R, power: u32, size: usize) -> Result<Self> {
+        let len = 1usize << power;
+        if size > len {
+            return Err(Error::BadFormat(format!(
+                "powers of tau has 2^{} powers, {} needed",
+                power, size
+            )));
+        }
+        skip(&mut input, RESPONSE_HASH_LEN)?;
+        let tau_g1 = read_points::<G1Affine, _>(&mut input, 2 * size - 1, 2 * len - 1)?;
+        let tau_g2 = read_points::<G2Affine, _>(&mut input, size, len)?;
+        let alpha_tau_g1 = read_points::<G1Affine, _>(&mut input, size, len)?;
+        let beta_tau_g1 = read_points::<G1Affine, _>(&mut input, size, len)?;
+        let beta_g2 = read_points::<G2Affine, _>(&mut input, 1, 1)?[0];
+
+        Ok(Phase1 {
+            tau_g1,
+            tau_g2,
+            alpha_tau_g1,
+            beta_tau_g1,
+            beta_g2,
+        })
+    }
+
+    /// read the first `size` powers of a snarkjs `.ptau` file
+    pub fn read_ptau<R: Read>(mut input: R, size: usize) -> Result<Self> {
+        let mut magic = [0u8; 4];
+        input.read_exact(&mut magic)?;
+        if &magic != PTAU_MAGIC {
+            return Err(Error::BadFormat("not a .ptau file".to_string()));
+        }
+        let version = input.read_u32::<LittleEndian>()?;
+        if version != PTAU_VERSION {
+            return Err(Error::BadFormat(format!("unsupported .ptau version {}", version)));
+        }
+        let n_sections = input.read_u32::<LittleEndian>()?;
+
+        let mut header = false;
+        let (mut tau_g1, mut tau_g2, mut alpha_tau_g1, mut beta_tau_g1, mut beta_g2) =
+            (None, None, None, None, None);
+        for _ in 0..n_sections {
+            let section = input.read_u32::<LittleEndian>()?;
+            let len = input.read_u64::<LittleEndian>()?;
+            let mut data = (&mut input).take(len);
+            if section > 1 && section <= 6 && !header {
+                return Err(Error::BadFormat(".ptau points before the header".to_string()));
+            }
+            match section {
+                1 => {
+                    read_ptau_header(&mut data, size)?;
+                    header = true;
+                }
+                2 => tau_g1 = Some(read_ptau_points(&mut data, 2 * size - 1)?),
+                3 => tau_g2 = Some(read_ptau_points(&mut data, size)?),
+                4 => alpha_tau_g1 = Some(read_ptau_points(&mut data, size)?),
+                5 => beta_tau_g1 = Some(read_ptau_points(&mut data, size)?),
+                6 => beta_g2 = Some(read_ptau_points(&mut data, 1)?[0]),
+                _ => {}
+            }
+            skip(&mut data, len)?;
+        }
+
+        let missing = || Error::BadFormat(".ptau file without powers of tau".to_string());
+        Ok(Phase1 {
+            tau_g1: tau_g1.ok_or_else(missing)?,
+            tau_g2: tau_g2.ok_or_else(missing)?,
+            alpha_tau_g1: alpha_tau_g1.ok_or_else(missing)?,
+            beta_tau_g1: beta_tau_g1.ok_or_else(missing)?,
+            beta_g2: beta_g2.ok_or_else(missing)?,
+        })
+    }
+
+    /// read the first `size` powers of a `.ptau` or `response` file, and check them
+    pub fn open(path: &str, size: usize) -> Result<Self> {
+        let mut file = File::open(path)?;
+        let mut magic = [0u8; 4];
+        let is_ptau = file.read_exact(&mut magic).is_ok() && &magic == PTAU_MAGIC;
+        let file = File::open(path)?;
+        let phase1 = if is_ptau {
+            Self::read_ptau(BufReader::new(file), size)?
+        } else {
+            let power = Self::response_power(file.metadata()?.len())
+                .ok_or_else(|| Error::BadFormat(format!("{} is not a powers of tau file", path)))?;
+            Self::read_response(BufReader::new(file), power, size)?
+        };
+        phase1.verify(&mut rand::thread_rng())?;
+        Ok(phase1)
+    }
+
+    /// locally generated powers of tau.
+    ///   INSECURE: whoever runs it knows tau, use it only for tests
+    pub fn insecure<G: Rng>(size: usize, rng: &mut G) -> Self {
+        let (tau, alpha, beta) = (Fr::rand(rng), Fr::rand(rng), Fr::rand(rng));
+        let powers = |n: usize, k: Fr| {
+            let mut power = k;
+            (0..n)
+                .map(|_| {
+                    let current = power;
+                    power.mul_assign(&tau);
+                    current
+                })
+                .collect::<Vec<_>>()
+        };
+        let g1 = |p: Fr| G1Affine::one().mul(p.into_repr()).into_affine();
+        let g2 = |p: Fr| G2Affine::one().mul(p.into_repr()).into_affine();
+        Phase1 {
+            tau_g1: powers(2 * size - 1, Fr::one()).into_iter().map(g1).collect(),
+            tau_g2: powers(size, Fr::one()).into_iter().map(g2).collect(),
+            alpha_tau_g1: powers(size, alpha).into_iter().map(g1).collect(),
+            beta_tau_g1: powers(size, beta).into_iter().map(g1).collect(),
+            beta_g2: g2(beta),
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.tau_g2.len()
+    }
+
+    /// check that the points are successive powers of the same tau, and that
+    ///   alpha and beta are used consistently
+    pub fn verify<G: Rng>(&self, rng: &mut G) -> Result<()> {
+        let size = self.size();
+        if size < 2
+            || self.tau_g1.len() != 2 * size - 1
+            || self.alpha_tau_g1.len() != size
+            || self.beta_tau_g1.len() != size
+        {
+            return Err(Error::BadFormat("powers of tau have a wrong length".to_string()));
+        }
+        let tau_g1 = (self.tau_g1[0], self.tau_g1[1]);
+        let tau_g2 = (self.tau_g2[0], self.tau_g2[1]);
+
+        if self.tau_g1[0] != G1Affine::one() || self.tau_g2[0] != G2Affine::one() {
+            return Err(Error::Mismatch("powers of tau do not start with the generators".to_string()));
+        }
+        if tau_g1.1.is_zero() || self.alpha_tau_g1[0].is_zero() || self.beta_tau_g1[0].is_zero() {
+            return Err(Error::Mismatch("powers of tau are degenerated".to_string()));
+        }
+        if !same_ratio(power_pairs(&self.tau_g1, rng), tau_g2)
+            || !same_ratio(power_pairs(&self.tau_g2, rng), tau_g1)
+            || !same_ratio(power_pairs(&self.alpha_tau_g1, rng), tau_g2)
+            || !same_ratio(power_pairs(&self.beta_tau_g1, rng), tau_g2)
+        {
+            return Err(Error::Mismatch("points are not successive powers of tau".to_string()));
+        }
+        if !same_ratio((self.tau_g1[0], self.beta_tau_g1[0]), (self.tau_g2[0], self.beta_g2)) {
+            return Err(Error::Mismatch("beta in G1 and G2 are different".to_string()));
+        }
+        Ok(())
+    }
+
+    /// derive the groth16 parameters of the circuit, with gamma and delta set to one,
+    ///   the layout is the same that bellman's generator uses for `CircomCircuit`
+    pub fn parameters<S: Signals, C: Constraints>(
+        &self,
+        signals: &S,
+        constraints: &C,
+    ) -> Result<Parameters<Bn256>> {
+        check_field::<Bn256, _>(signals)?;
+        let (vars, n_inputs) = variables(signals)?;
+        let n_vars = signals.len()?;
+        let n_circuit = constraints.len()?;
+
+        // bellman adds an `input * 0 = 0` constraint for each input
+        let (m, log_m) = domain_size(n_circuit + n_inputs)?;
+        if m > self.size() {
+            return Err(Error::BadFormat(format!(
+                "powers of tau has {} powers, circuit needs {}",
+                self.size(),
+                m
+            )));
+        }
+
+        let lg1 = lagrange(&self.tau_g1[..m], log_m)?;
+        let lg2 = lagrange(&self.tau_g2[..m], log_m)?;
+        let alpha_lg1 = lagrange(&self.alpha_tau_g1[..m], log_m)?;
+        let beta_lg1 = lagrange(&self.beta_tau_g1[..m], log_m)?;
+
+        let mut a = vec![G1::zero(); n_vars];
+        let mut b_g1 = vec![G1::zero(); n_vars];
+        let mut b_g2 = vec![G2::zero(); n_vars];
+        let mut ext = vec![G1::zero(); n_vars];
+
+        for (j, qeq) in constraints.iter().enumerate() {
+            let qeq = qeq?;
+            for (signal, value) in &qeq.a.0 {
+                let (var, fr) = (vars[*signal], fe_to_bellman_fr::<Bn256>(value).into_repr());
+                a[var].add_assign(&lg1[j].mul(fr));
+                ext[var].add_assign(&beta_lg1[j].mul(fr));
+            }
+            for (signal, value) in &qeq.b.0 {
+                let (var, fr) = (vars[*signal], fe_to_bellman_fr::<Bn256>(value).into_repr());
+                b_g1[var].add_assign(&lg1[j].mul(fr));
+                b_g2[var].add_assign(&lg2[j].mul(fr));
+                ext[var].add_assign(&alpha_lg1[j].mul(fr));
+            }
+            for (signal, value) in &qeq.c.neg(signals.field()).0 {
+                let (var, fr) = (vars[*signal], fe_to_bellman_fr::<Bn256>(value).into_repr());
+                ext[var].add_assign(&lg1[j].mul(fr));
+            }
+        }
+        for input in 0..n_inputs {
+            a[input].add_assign_mixed(&lg1[n_circuit + input]);
+            ext[input].add_assign_mixed(&beta_lg1[n_circuit + input]);
+        }
+
+        // t(tau) * tau^i = tau^(i+m) - tau^i
+        let h = (0..m - 1)
+            .map(|i| {
+                let mut h = self.tau_g1[i].into_projective();
+                h.negate();
+                h.add_assign_mixed(&self.tau_g1[i + m]);
+                h.into_affine()
+            })
+            .collect::<Vec<_>>();
+
+        let ext = ext.iter().map(|p| p.into_affine()).collect::<Vec<_>>();
+        let l = ext[n_inputs..].to_vec();
+        if l.iter().any(|p| p.is_zero()) {
+            return Err(Error::from(SynthesisError::UnconstrainedVariable));
+        }
+
+        let nonzero = |v: Vec<G1>| v.iter().filter(|p| !p.is_zero()).map(|p| p.into_affine()).collect::<Vec<_>>();
+        let b_g2 = b_g2.iter().filter(|p| !p.is_zero()).map(|p| p.into_affine()).collect::<Vec<_>>();
+
+        Ok(Parameters {
+            vk: VerifyingKey {
+                alpha_g1: self.alpha_tau_g1[0],
+                beta_g1: self.beta_tau_g1[0],
+                beta_g2: self.beta_g2,
+                gamma_g2: G2Affine::one(),
+                delta_g1: G1Affine::one(),
+                delta_g2: G2Affine::one(),
+                ic: ext[..n_inputs].to_vec(),
+            },
+            h: Arc::new(h),
+            l: Arc::new(l),
+            a: Arc::new(nonzero(a)),
+            b_g1: Arc::new(nonzero(b_g1)),
+            b_g2: Arc::new(b_g2),
+        })
+    }
+}
+
+/// number of powers of tau needed by the circuit
+pub fn circuit_size<S: Signals, C: Constraints>(signals: &S, constraints: &C) -> Result<usize> {
+    let n_inputs = count_public_inputs(signals)? + 1;
+    Ok(domain_size(constraints.len()? + n_inputs)?.0)
+}
+
+/// bellman variable of each signal, inputs first and then aux, in signal order
+fn variables<S: Signals>(signals: &S) -> Result<(Vec<usize>, usize)> {
+    let mut public = vec![true; signals.len()?];
+    for signal in signals.iter_range(SIGNAL_ONE + 1..signals.len()?) {
+        let signal = signal?;
+        public[signal.id] = is_public_input(&signal);
+    }
+    let n_inputs = public.iter().filter(|p| **p).count();
+    let (mut input, mut aux) = (0, n_inputs);
+    let vars = public
+        .iter()
+        .map(|public| {
+            let next = if *public { &mut input } else { &mut aux };
+            *next += 1;
+            *next - 1
+        })
+        .collect();
+    Ok((vars, n_inputs))
+}
+
+/// smallest power of two with at least `n` elements, and its log
+pub fn domain_size(n: usize) -> Result<(usize, u32)> {
+    let (mut m, mut log_m) = (1, 0);
+    while m < n {
+        m *= 2;
+        log_m += 1;
+        if log_m >= Fr::S {
+            return Err(Error::from(SynthesisError::PolynomialDegreeTooLarge));
+        }
+    }
+    Ok((m, log_m))
+}
+
+/// lagrange basis at tau from the powers of tau, an inverse fft in the exponent
+fn lagrange<P: CurveAffine<Scalar = Fr>>(powers: &[P], log_m: u32) -> Result<Vec<P>>
+where
+    P::Projective: Group,
+{
+    let mut omega = Fr::root_of_unity();
+    for _ in log_m..Fr::S {
+        omega.square();
+    }
+    let omega_inv = omega.inverse().ok_or_else(|| Error::Unexpected("zero root of unity".to_string()))?;
+    let m_inv = Fr::from_repr(FrRepr::from(powers.len() as u64))
+        .ok()
+        .and_then(|m| m.inverse())
+        .ok_or_else(|| Error::Unexpected("bad domain size".to_string()))?;
+
+    let mut values = powers.iter().map(|p| p.into_projective()).collect::<Vec<_>>();
+    fft(&mut values, &omega_inv, log_m);
+    Ok(values
+        .iter_mut()
+        .map(|p| {
+            p.mul_assign(m_inv.into_repr());
+            p.into_affine()
+        })
+        .collect())
+}
+
+/// e(g1.0, g2.1) == e(g1.1, g2.0), so g1 and g2 pairs are related by the same factor
+pub(crate) fn same_ratio<P: CurveAffine>(g1: (P, P), g2: (P::Pair, P::Pair)) -> bool {
+    g1.0.pairing_with(&g2.1) == g1.1.pairing_with(&g2.0)
+}
+
+/// random linear combination of both vectors with the same coefficients,
+///   so `same_ratio` over the result checks all pairs at once
+pub(crate) fn merge_pairs<P: CurveAffine<Scalar = Fr>, G: Rng>(v1: &[P], v2: &[P], rng: &mut G) -> (P, P) {
+    let (mut s1, mut s2) = (P::Projective::zero(), P::Projective::zero());
+    for (p1, p2) in v1.iter().zip(v2.iter()) {
+        let rho = Fr::rand(rng);
+        s1.add_assign(&p1.mul(rho.into_repr()));
+        s2.add_assign(&p2.mul(rho.into_repr()));
+    }
+    (s1.into_affine(), s2.into_affine())
+}
+
+/// each point paired with the next one
+fn power_pairs<P: CurveAffine<Scalar = Fr>, G: Rng>(v: &[P], rng: &mut G) -> (P, P) {
+    merge_pairs(&v[..v.len() - 1], &v[1..], rng)
+}
+
+fn read_ptau_header<R: Read>(input: &mut R, size: usize) -> Result<()> {
+    let n8 = input.read_u32::<LittleEndian>()?;
+    let mut q = vec![0u8; n8 as usize];
+    input.read_exact(&mut q)?;
+    if n8 != 32 || BigUint::from_bytes_le(&q) != *Q {
+        return Err(Error::BadFormat(".ptau file is not for the BN254 curve".to_string()));
+    }
+    let power = input.read_u32::<LittleEndian>()?;
+    if power >= 64 || size > 1usize << power {
+        return Err(Error::BadFormat(format!(
+            "powers of tau has 2^{} powers, {} needed",
+            power, size
+        )));
+    }
+    Ok(())
+}
+
+fn read_ptau_points<P: CurveAffine, R: Read>(input: &mut R, count: usize) -> Result<Vec<P>> {
+    let mut points = Vec::with_capacity(count);
+    let mut coord = [0u8; 32];
+    for _ in 0..count {
+        let mut encoded = P::Uncompressed::empty();
+        let n_coords = encoded.as_ref().len() / 32;
+        let mut coords = Vec::with_capacity(n_coords);
+        let mut zero = true;
+        for _ in 0..n_coords {
+            input.read_exact(&mut coord)?;
+            zero &= coord.iter().all(|b| *b == 0);
+            coords.push(BigUint::from_bytes_le(&coord) * &*R_INV % &*Q);
+        }
+        if zero {
+            points.push(P::zero());
+            continue;
+        }
+        // the uncompressed encoding is big endian, with G2 coordinates as c1, c0
+        let order: &[usize] = if n_coords == 2 { &[0, 1] } else { &[1, 0, 3, 2] };
+        for (n, c) in order.iter().enumerate() {
+            let be = coords[*c].to_bytes_be();
+            let bytes = &mut encoded.as_mut()[n * 32..(n + 1) * 32];
+            bytes[32 - be.len()..].copy_from_slice(&be);
+        }
+        let point = encoded
+            .into_affine()
+            .map_err(|err| Error::BadFormat(format!("bad point in .ptau file: {}", err)))?;
+        points.push(point);
+    }
+    Ok(points)
+}
+
+fn read_points<P: CurveAffine, R: Read>(input: &mut R, count: usize, total: usize) -> Result<Vec<P>> {
+    let mut points = Vec::with_capacity(count);
+    let mut encoded = P::Compressed::empty();
+    for _ in 0..count {
+        input.read_exact(encoded.as_mut())?;
+        let point = encoded
+            .into_affine()
+            .map_err(|err| Error::BadFormat(format!("bad point in powers of tau: {}", err)))?;
+        points.push(point);
+    }
+    skip(input, ((total - count) * P::Compressed::size()) as u64)?;
+    Ok(points)
+}
+
+fn skip<R: Read>(input: &mut R, len: u64) -> Result<()> {
+    std::io::copy(&mut input.take(len), &mut std::io::sink())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use byteorder::WriteBytesExt;
+    use rand::{ChaChaRng, SeedableRng};
+
+    fn write_points<P: CurveAffine>(out: &mut Vec<u8>, points: &[P]) {
+        let r = BigUint::from(1u32) << 256;
+        for point in points {
+            let encoded = point.into_uncompressed();
+            let order: &[usize] = if encoded.as_ref().len() == 64 { &[0, 1] } else { &[1, 0, 3, 2] };
+            for c in order {
+                let coord = BigUint::from_bytes_be(&encoded.as_ref()[c * 32..(c + 1) * 32]);
+                let mut le = (coord * &r % &*Q).to_bytes_le();
+                le.resize(32, 0);
+                out.extend_from_slice(&le);
+            }
+        }
+    }
+
+    fn write_ptau(phase1: &Phase1, power: u32) -> Vec<u8> {
+        let mut sections = vec![Vec::new(); 7];
+        sections[0].write_u32::<LittleEndian>(32).unwrap();
+        let mut q = Q.to_bytes_le();
+        q.resize(32, 0);
+        sections[0].extend_from_slice(&q);
+        sections[0].write_u32::<LittleEndian>(power).unwrap();
+        sections[0].write_u32::<LittleEndian>(power).unwrap();
+        write_points(&mut sections[1], &phase1.tau_g1);
+        write_points(&mut sections[2], &phase1.tau_g2);
+        write_points(&mut sections[3], &phase1.alpha_tau_g1);
+        write_points(&mut sections[4], &phase1.beta_tau_g1);
+        write_points(&mut sections[5], &[phase1.beta_g2]);
+
+        let mut out = PTAU_MAGIC.to_vec();
+        out.write_u32::<LittleEndian>(PTAU_VERSION).unwrap();
+        out.write_u32::<LittleEndian>(sections.len() as u32).unwrap();
+        for (n, section) in sections.iter().enumerate() {
+            out.write_u32::<LittleEndian>(n as u32 + 1).unwrap();
+            out.write_u64::<LittleEndian>(section.len() as u64).unwrap();
+            out.extend_from_slice(section);
+        }
+        out
+    }
+
+    #[test]
+    fn test_ptau() {
+        let rng = &mut ChaChaRng::from_seed(&[1, 2]);
+        let phase1 = Phase1::insecure(4, rng);
+        phase1.verify(rng).unwrap();
+
+        let ptau = write_ptau(&phase1, 2);
+        let read = Phase1::read_ptau(&ptau[..], 4).unwrap();
+        assert!(read.tau_g1 == phase1.tau_g1);
+        assert!(read.tau_g2 == phase1.tau_g2);
+        assert!(read.alpha_tau_g1 == phase1.alpha_tau_g1);
+        assert!(read.beta_tau_g1 == phase1.beta_tau_g1);
+        assert!(read.beta_g2 == phase1.beta_g2);
+
+        // only the needed powers are read
+        let read = Phase1::read_ptau(&ptau[..], 2).unwrap();
+        assert!(read.tau_g1[..] == phase1.tau_g1[..3]);
+        read.verify(rng).unwrap();
+        assert!(Phase1::read_ptau(&ptau[..], 8).is_err());
+
+        let mut bad = Phase1::read_ptau(&ptau[..], 4).unwrap();
+        bad.alpha_tau_g1[2] = bad.alpha_tau_g1[1];
+        assert!(bad.verify(rng).is_err());
+        let mut bad = Phase1::read_ptau(&ptau[..], 4).unwrap();
+        bad.beta_g2 = G2Affine::one();
+        assert!(bad.verify(rng).is_err());
+    }
+}
@@ -0,0 +1,320 @@
+use std::io::{Read, Write};
+use std::sync::Arc;
+
+use bellman::groth16::Parameters;
+use blake2_rfc::blake2b::Blake2b;
+use byteorder::{BigEndian, ByteOrder, ReadBytesExt, WriteBytesExt};
+use ff::{Field, PrimeField};
+use pairing::bn256::{Bn256, Fr, G1Affine, G2Affine, G1, G2};
+use pairing::{CurveAffine, CurveProjective, EncodedPoint};
+use rand::{ChaChaRng, Rand, Rng, SeedableRng};
+
+use circom2_compiler::storage::{Constraints, Signals};
+
+use super::error::{Error, Result};
+use super::phase1::{merge_pairs, same_ratio, Phase1};
+
+// phase-2 parameters file
+//
+//   magic "c2mp", version : u32
+//   hash of the initial parameters : 64 bytes
+//   current parameters : bellman format
+//   n contributions : u32, for each contribution:
+//     delta after : G1, s : G1, s * delta : G1, r * delta : G2, transcript : 64 bytes
+//
+const MPC_MAGIC: &[u8; 4] = b"c2mp";
+const MPC_VERSION: u32 = 1;
+const HASH_LEN: usize = 64;
+
+/// public proof of knowledge of the delta of a contribution
+#[derive(Clone)]
+pub struct Contribution {
+    pub delta_after: G1Affine,
+    pub s: G1Affine,
+    pub s_delta: G1Affine,
+    pub r_delta: G2Affine,
+    pub transcript: Vec<u8>,
+}
+
+impl Contribution {
+    /// hash that identifies the contribution, to be published by its author
+    pub fn hash(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.write(&mut bytes).expect("cannot write to memory");
+        let mut hasher = Blake2b::new(HASH_LEN);
+        hasher.update(&bytes);
+        hasher.finalize().as_bytes().to_vec()
+    }
+
+    fn write<W: Write>(&self, out: &mut W) -> Result<()> {
+        out.write_all(self.delta_after.into_uncompressed().as_ref())?;
+        out.write_all(self.s.into_uncompressed().as_ref())?;
+        out.write_all(self.s_delta.into_uncompressed().as_ref())?;
+        out.write_all(self.r_delta.into_uncompressed().as_ref())?;
+        out.write_all(&self.transcript)?;
+        Ok(())
+    }
+
+    fn read<R: Read>(input: &mut R) -> Result<Self> {
+        let delta_after = read_point(input)?;
+        let s = read_point(input)?;
+        let s_delta = read_point(input)?;
+        let r_delta = read_point(input)?;
+        let mut transcript = vec![0u8; HASH_LEN];
+        input.read_exact(&mut transcript)?;
+        Ok(Contribution {
+            delta_after,
+            s,
+            s_delta,
+            r_delta,
+            transcript,
+        })
+    }
+}
+
+/// groth16 parameters of a circuit under a phase-2 ceremony, each contribution
+///   multiplies delta by a secret that is discarded afterwards
+pub struct MpcParameters {
+    pub params: Parameters<Bn256>,
+    pub cs_hash: Vec<u8>,
+    pub contributions: Vec<Contribution>,
+}
+
+impl MpcParameters {
+    /// initial parameters of the circuit derived from the powers of tau
+    pub fn new<S: Signals, C: Constraints>(phase1: &Phase1, signals: &S, constraints: &C) -> Result<Self> {
+        let params = phase1.parameters(signals, constraints)?;
+        let cs_hash = params_hash(&params)?;
+        Ok(MpcParameters {
+            params,
+            cs_hash,
+            contributions: Vec::new(),
+        })
+    }
+
+    /// add a contribution with randomness from `rng`, returns its hash
+    pub fn contribute<G: Rng>(&mut self, rng: &mut G) -> Result<Vec<u8>> {
+        let delta = Fr::rand(rng);
+        let delta_inv = delta.inverse().ok_or_else(|| Error::Unexpected("zero delta".to_string()))?;
+
+        let s = G1::rand(rng).into_affine();
+        let s_delta = s.mul(delta.into_repr()).into_affine();
+        let transcript = transcript(&self.cs_hash, &self.contributions, &s, &s_delta)?;
+        let mut r_delta = hash_to_g2(&transcript);
+        r_delta.mul_assign(delta.into_repr());
+        let r_delta = r_delta.into_affine();
+
+        let scale = |points: &[G1Affine], k: Fr| points.iter().map(|p| p.mul(k.into_repr()).into_affine()).collect::<Vec<_>>();
+        self.params.vk.delta_g1 = self.params.vk.delta_g1.mul(delta.into_repr()).into_affine();
+        self.params.vk.delta_g2 = self.params.vk.delta_g2.mul(delta.into_repr()).into_affine();
+        self.params.h = Arc::new(scale(&self.params.h, delta_inv));
+        self.params.l = Arc::new(scale(&self.params.l, delta_inv));
+
+        let contribution = Contribution {
+            delta_after: self.params.vk.delta_g1,
+            s,
+            s_delta,
+            r_delta,
+            transcript,
+        };
+        let hash = contribution.hash();
+        self.contributions.push(contribution);
+        Ok(hash)
+    }
+
+    /// check that the parameters come from the circuit and the powers of tau,
+    ///   and that every contribution is valid, returns their hashes in order
+    pub fn verify<S: Signals, C: Constraints>(&self, phase1: &Phase1, signals: &S, constraints: &C) -> Result<Vec<Vec<u8>>> {
+        let initial = phase1.parameters(signals, constraints)?;
+        if params_hash(&initial)? != self.cs_hash {
+            return Err(Error::Mismatch("parameters are not for this circuit".to_string()));
+        }
+        let (vk, init_vk) = (&self.params.vk, &initial.vk);
+        if vk.alpha_g1 != init_vk.alpha_g1
+            || vk.beta_g1 != init_vk.beta_g1
+            || vk.beta_g2 != init_vk.beta_g2
+            || vk.gamma_g2 != init_vk.gamma_g2
+            || vk.ic != init_vk.ic
+            || self.params.a != initial.a
+            || self.params.b_g1 != initial.b_g1
+            || self.params.b_g2 != initial.b_g2
+            || self.params.h.len() != initial.h.len()
+            || self.params.l.len() != initial.l.len()
+        {
+            return Err(Error::Mismatch("parameters were modified outside delta".to_string()));
+        }
+
+        let mut hashes = Vec::new();
+        let mut delta_before = init_vk.delta_g1;
+        for (n, contribution) in self.contributions.iter().enumerate() {
+            let previous = &self.contributions[..n];
+            let transcript = transcript(&self.cs_hash, previous, &contribution.s, &contribution.s_delta)?;
+            let r = hash_to_g2(&transcript).into_affine();
+            if transcript != contribution.transcript
+                || !same_ratio((contribution.s, contribution.s_delta), (r, contribution.r_delta))
+                || !same_ratio((delta_before, contribution.delta_after), (r, contribution.r_delta))
+            {
+                return Err(Error::Mismatch(format!("contribution #{} is not valid", n + 1)));
+            }
+            delta_before = contribution.delta_after;
+            hashes.push(contribution.hash());
+        }
+
+        // delta is the last contribution, and h and l are divided by it
+        let rng = &mut rand::thread_rng();
+        if vk.delta_g1 != delta_before
+            || !same_ratio((G1Affine::one(), vk.delta_g1), (G2Affine::one(), vk.delta_g2))
+            || !same_ratio(merge_pairs(&initial.h, &self.params.h, rng), (vk.delta_g2, init_vk.delta_g2))
+            || !same_ratio(merge_pairs(&initial.l, &self.params.l, rng), (vk.delta_g2, init_vk.delta_g2))
+        {
+            return Err(Error::Mismatch("delta is not consistent with the contributions".to_string()));
+        }
+
+        Ok(hashes)
+    }
+
+    pub fn write<W: Write>(&self, mut out: W) -> Result<()> {
+        out.write_all(MPC_MAGIC)?;
+        out.write_u32::<BigEndian>(MPC_VERSION)?;
+        out.write_all(&self.cs_hash)?;
+        self.params.write(&mut out)?;
+        out.write_u32::<BigEndian>(self.contributions.len() as u32)?;
+        for contribution in &self.contributions {
+            contribution.write(&mut out)?;
+        }
+        Ok(())
+    }
+
+    pub fn read<R: Read>(mut input: R) -> Result<Self> {
+        let mut magic = [0u8; 4];
+        input.read_exact(&mut magic)?;
+        if &magic != MPC_MAGIC {
+            return Err(Error::BadFormat("not a ceremony parameters file".to_string()));
+        }
+        let version = input.read_u32::<BigEndian>()?;
+        if version != MPC_VERSION {
+            return Err(Error::BadFormat(format!("unsupported ceremony version {}", version)));
+        }
+        let mut cs_hash = vec![0u8; HASH_LEN];
+        input.read_exact(&mut cs_hash)?;
+        let params = Parameters::read(&mut input, true)?;
+        let count = input.read_u32::<BigEndian>()?;
+        let contributions = (0..count)
+            .map(|_| Contribution::read(&mut input))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(MpcParameters {
+            params,
+            cs_hash,
+            contributions,
+        })
+    }
+}
+
+/// hash of the circuit, the previous contributions and the new `s`, `s * delta`
+fn transcript(cs_hash: &[u8], previous: &[Contribution], s: &G1Affine, s_delta: &G1Affine) -> Result<Vec<u8>> {
+    let mut hasher = Blake2b::new(HASH_LEN);
+    hasher.update(cs_hash);
+    for contribution in previous {
+        let mut bytes = Vec::new();
+        contribution.write(&mut bytes)?;
+        hasher.update(&bytes);
+    }
+    hasher.update(s.into_uncompressed().as_ref());
+    hasher.update(s_delta.into_uncompressed().as_ref());
+    Ok(hasher.finalize().as_bytes().to_vec())
+}
+
+fn params_hash(params: &Parameters<Bn256>) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    params.write(&mut bytes)?;
+    let mut hasher = Blake2b::new(HASH_LEN);
+    hasher.update(&bytes);
+    Ok(hasher.finalize().as_bytes().to_vec())
+}
+
+/// a G2 point nobody knows the discrete log of, seeded by the transcript
+fn hash_to_g2(transcript: &[u8]) -> G2 {
+    let seed = transcript
+        .chunks(4)
+        .take(8)
+        .map(BigEndian::read_u32)
+        .collect::<Vec<_>>();
+    G2::rand(&mut ChaChaRng::from_seed(&seed))
+}
+
+fn read_point<P: CurveAffine, R: Read>(input: &mut R) -> Result<P> {
+    let mut encoded = P::Uncompressed::empty();
+    input.read_exact(encoded.as_mut())?;
+    encoded
+        .into_affine()
+        .map_err(|err| Error::BadFormat(format!("bad point in ceremony file: {}", err)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::groth16::phase1::domain_size;
+    use crate::groth16::format::read_input_and_proof;
+    use crate::groth16::prover::{generate_verified_proof, new_rng, verify, write_keys};
+    use circom2_compiler::algebra::Value;
+    use circom2_compiler::evaluator::{Evaluator, Mode, Scope};
+    use circom2_compiler::progress::NoProgress;
+    use circom2_compiler::storage::{Ram, StorageFactory};
+
+    const CIRCUIT: &str = "
+        template t() {
+            signal private input a;
+            signal private input b;
+            signal output c;
+            c <== a * b;
+        }
+        component main = t();
+    ";
+
+    #[test]
+    fn test_ceremony() {
+        let mut ram = Ram::default();
+        let mut eval = Evaluator::new(
+            Mode::GenConstraints,
+            ram.new_signals().unwrap(),
+            ram.new_constraints().unwrap(),
+        );
+        eval.eval_inline(&mut Scope::new(true, None, "root".to_string()), CIRCUIT).unwrap();
+
+        let (size, _) = domain_size(eval.constraints.len().unwrap() + 2).unwrap();
+        let phase1 = Phase1::insecure(size, &mut new_rng(Some(1)));
+
+        let mut mpc = MpcParameters::new(&phase1, &eval.signals, &eval.constraints).unwrap();
+        let first = mpc.contribute(&mut new_rng(Some(2))).unwrap();
+        let second = mpc.contribute(&mut new_rng(Some(3))).unwrap();
+
+        let mut bytes = Vec::new();
+        mpc.write(&mut bytes).unwrap();
+        let mpc = MpcParameters::read(&bytes[..]).unwrap();
+        assert_eq!(
+            vec![first, second],
+            mpc.verify(&phase1, &eval.signals, &eval.constraints).unwrap()
+        );
+
+        // the final parameters prove and verify
+        let (mut pk, mut sol, mut json) = (Vec::new(), Vec::new(), Vec::new());
+        write_keys(&eval.signals, &eval.constraints, Some(&eval.circuit_hash()), &mpc.params, &mut pk, Some(&mut sol), &mut json).unwrap();
+
+        let mut ram = Ram::default();
+        let mut witness = Evaluator::new(Mode::GenWitness, ram.new_signals().unwrap(), ram.new_constraints().unwrap());
+        witness.set_deferred_value("main.a".to_string(), Value::from(3));
+        witness.set_deferred_value("main.b".to_string(), Value::from(5));
+        witness.eval_inline(&mut Scope::new(true, None, "root".to_string()), CIRCUIT).unwrap();
+        let mut proof = Vec::new();
+        let hash = witness.circuit_hash();
+        generate_verified_proof::<Bn256, _, _, _, _>(witness.signals, Some(&hash), &pk[..], &mut proof, &mut new_rng(Some(4)), &mut NoProgress).unwrap();
+        let (inputs, proof) = read_input_and_proof(&proof[..]).unwrap();
+        assert!(verify(&mpc.params.vk, &proof, &inputs).unwrap());
+
+        // a contribution that does not match delta
+        let mut tampered = MpcParameters::read(&bytes[..]).unwrap();
+        tampered.contributions.pop();
+        assert!(tampered.verify(&phase1, &eval.signals, &eval.constraints).is_err());
+    }
+}
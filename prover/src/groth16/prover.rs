@@ -1,42 +1,42 @@
-use circom2_compiler::algebra::FS;
-use circom2_compiler::evaluator::{Evaluator,check_constrains_eval_zero};
+use circom2_compiler::algebra::{Witness, FS};
+use circom2_compiler::evaluator::check_constrains_eval_zero;
+use circom2_compiler::progress::{Counter, Phase, Progress};
 use circom2_compiler::storage;
-use circom2_compiler::storage::{Constraints, Signals,count_public_inputs,is_public_input};
+use circom2_compiler::storage::{Constraints, RamConstraints, Signals,count_public_inputs,is_public_input};
 
-use std::io::{Read, Write};
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
 use std::marker::PhantomData;
 
-use pairing::bn256::{Bn256, Fr};
-use pairing::Engine;
+use pairing::{CurveAffine, CurveProjective, Engine};
 
 use bellman::{Circuit, ConstraintSystem, SynthesisError};
 use bellman::groth16::{
-    create_random_proof, generate_random_parameters, prepare_verifying_key, verify_proof
+    create_random_proof, generate_random_parameters, prepare_verifying_key, verify_proof,
+    Parameters, Proof, VerifyingKey,
 };
 
-use ff::PrimeField;
+use ff::{Field, PrimeField};
 
-use rand::thread_rng;
+use rand::{thread_rng, ChaChaRng, Rand, Rng, SeedableRng};
 
-use super::error::Result;
+use super::curve::Curve;
+use super::error::{Error, Result};
 use super::format::*;
 use super::ethereum;
 use super::format;
+use super::phase1::Phase1;
+use super::phase2::MpcParameters;
 
-const BELLMAN_VERBOSE : &str = "BELLMAN_VERBOSE";
-
-pub fn bellman_verbose(verbose : bool) {
-    if verbose {
-            std::env::set_var(BELLMAN_VERBOSE, "1");
-    } else {
-            std::env::set_var(BELLMAN_VERBOSE, "0");
-    }
-}
-
+/// constraints between two progress updates
+pub(crate) const PROGRESS_EVERY: usize = 100_000;
 
 pub struct CircomCircuit<'a, E: Engine> {
     constraints: &'a Constraints,
     signals: &'a Signals,
+    // receives the synthesized constraints count
+    progress: &'a mut dyn Progress,
+    phase: Phase,
     phantom: PhantomData<E>,
 }
 
@@ -60,8 +60,9 @@ impl<'a, E: Engine> Circuit<E> for CircomCircuit<'a, E> {
         signals.push(CS::one());
 
         // register signals
-        for n in 1..map_storage_error(self.signals.len())? {
-            let s = map_storage_error(self.signals.get_by_id(n))?.unwrap();
+        let signals_len = map_storage_error(self.signals.len())?;
+        for s in self.signals.iter_range(1..signals_len) {
+            let s = map_storage_error(s)?;
             let signal = if is_public_input(&s) {
                 cs.alloc_input(
                     || (*s.full_name.0).clone(),
@@ -87,88 +88,352 @@ impl<'a, E: Engine> Circuit<E> for CircomCircuit<'a, E> {
         }
 
         // register constrains
-        for n in 0..map_storage_error(self.constraints.len())? {
-            let constraint = map_storage_error(self.constraints.get(n))?;
+        let field = self.signals.field();
+        let mut counter = Counter::new(self.phase, PROGRESS_EVERY);
+        for (n, constraint) in self.constraints.iter().enumerate() {
+            let constraint = map_storage_error(constraint)?;
             let name = format!("c{}", n);
             cs.enforce(
                 || name,
                 |lc| lc_to_bellman(lc, &signals, &constraint.a),
                 |lc| lc_to_bellman(lc, &signals, &constraint.b),
-                |lc| lc_to_bellman(lc, &signals, &-&constraint.c),
+                |lc| lc_to_bellman(lc, &signals, &constraint.c.neg(field)),
             );
+            counter.inc(self.progress);
         }
         Ok(())
     }
 }
 
-pub fn setup<S: Signals, C: Constraints, WP: Write, WV: Write>(
-    eval: &Evaluator<S, C>,
+/// randomness for setup and proving, a seed makes keys and proofs reproducible.
+///   INSECURE: anyone knowing the seed can forge proofs, use it only for tests
+pub fn new_rng(seed: Option<u64>) -> Box<dyn Rng> {
+    match seed {
+        Some(seed) => Box::new(ChaChaRng::from_seed(&[seed as u32, (seed >> 32) as u32])),
+        None => Box::new(thread_rng()),
+    }
+}
+
+/// generate the keys of the circuit, the solidity verifier is only written if
+///   `out_vk` is given. `source_hash` is the `circuit_hash` of the evaluator, when known
+#[allow(clippy::too_many_arguments)]
+pub fn setup<E: Curve, S: Signals, C: Constraints, WP: Write, WV: Write, WJ: Write, G: Rng>(
+    signals: &S,
+    constraints: &C,
+    source_hash: Option<&str>,
     out_pk: WP,
-    mut out_vk: WV,
+    out_vk: Option<WV>,
+    out_vk_json: WJ,
+    rng: &mut G,
+    progress: &mut dyn Progress,
 ) -> Result<()> {
-    let rng = &mut thread_rng();
-    let circuit = CircomCircuit::<Bn256> {
-        signals: &eval.signals,
-        constraints: &eval.constraints,
+    check_field::<E, _>(signals)?;
+    let n_constraints = constraints.len()?;
+    progress.start(Phase::Setup, Some(n_constraints));
+    let circuit = CircomCircuit::<E> {
+        signals,
+        constraints,
+        progress: &mut *progress,
+        phase: Phase::Setup,
         phantom: PhantomData,
     };
 
     // perform setup
     let params = generate_random_parameters(circuit, rng)?;
-    format::write_pk(out_pk, &eval.constraints, &params)?;
-    
-    let inputs_len = count_public_inputs(&eval.signals)?; 
-    ethereum::generate_solidity(&params.vk,inputs_len, &mut out_vk)?;
+    progress.finish(Phase::Setup, n_constraints);
 
+    progress.start(Phase::WriteKeys, Some(n_constraints));
+    write_keys(signals, constraints, source_hash, &params, out_pk, out_vk, out_vk_json)?;
+    progress.finish(Phase::WriteKeys, n_constraints);
     Ok(())
 }
 
-pub fn generate_verified_proof<S: Signals, R: Read, W: Write>(
+/// setup on top of a phase-1 powers of tau, delta is set by a single local contribution
+#[allow(clippy::too_many_arguments)]
+pub fn setup_phase1<S: Signals, C: Constraints, WP: Write, WV: Write, WJ: Write, G: Rng>(
+    phase1: &Phase1,
+    signals: &S,
+    constraints: &C,
+    source_hash: Option<&str>,
+    out_pk: WP,
+    out_vk: Option<WV>,
+    out_vk_json: WJ,
+    rng: &mut G,
+    progress: &mut dyn Progress,
+) -> Result<()> {
+    let n_constraints = constraints.len()?;
+    progress.start(Phase::Setup, Some(n_constraints));
+    let mut mpc = MpcParameters::new(phase1, signals, constraints)?;
+    mpc.contribute(rng)?;
+    progress.finish(Phase::Setup, n_constraints);
+
+    progress.start(Phase::WriteKeys, Some(n_constraints));
+    write_keys(signals, constraints, source_hash, &mpc.params, out_pk, out_vk, out_vk_json)?;
+    progress.finish(Phase::WriteKeys, n_constraints);
+    Ok(())
+}
+
+/// write the proving key, the solidity verifier and the snarkjs verification key
+pub fn write_keys<E: Curve, S: Signals, C: Constraints, WP: Write, WV: Write, WJ: Write>(
+    signals: &S,
+    constraints: &C,
+    source_hash: Option<&str>,
+    params: &Parameters<E>,
+    out_pk: WP,
+    out_vk: Option<WV>,
+    out_vk_json: WJ,
+) -> Result<()> {
+    // before writing anything, curves without solidity verifiers fail here
+    if let Some(mut out_vk) = out_vk {
+        let inputs_len = count_public_inputs(signals)?;
+        E::generate_solidity(&params.vk, inputs_len, &ethereum::SolidityOptions::default(), &mut out_vk)?;
+    }
+    format::write_pk(out_pk, signals, constraints, source_hash, params)?;
+    format::write_vk_json(&params.vk, out_vk_json)?;
+
+    Ok(())
+}
+
+/// prove the witness in `signals`, `source_hash` is the `circuit_hash` of the evaluator
+///   that computed it and must match the one of the proving key
+pub fn generate_verified_proof<E: Curve, S: Signals, R: Read, W: Write, G: Rng>(
+    signals: S,
+    source_hash: Option<&str>,
+    in_pk: R,
+    out_proof: &mut W,
+    rng: &mut G,
+    progress: &mut dyn Progress,
+) -> Result<Vec<(String,FS)>> {
+    let (constraints, circuit, params) = read_pk_progress::<E, _>(in_pk, progress)?;
+    check_circuit(&signals, source_hash, None::<(&RamConstraints, _)>, &circuit)?;
+    prove(&signals, &constraints, &params, out_proof, rng, progress)
+}
+
+/// prove with the constraints of an already compiled circuit, the copy stored in
+///   the proving key is skipped after checking that the key was created for them
+pub fn generate_verified_proof_with<E: Curve, S: Signals, C: Constraints, R: Read, W: Write, G: Rng>(
     signals: S,
+    source_hash: Option<&str>,
+    constraints: &C,
     in_pk: R,
-    out_proof: &mut W
+    out_proof: &mut W,
+    rng: &mut G,
+    progress: &mut dyn Progress,
 ) -> Result<Vec<(String,FS)>> {
+    progress.start(Phase::LoadKey, None);
+    let (header, circuit, params) = format::read_pk_skipping::<E, _>(in_pk)?;
+    progress.finish(Phase::LoadKey, constraints.len()?);
+    check_circuit(&signals, source_hash, Some((constraints, &header)), &circuit)?;
+    prove(&signals, constraints, &params, out_proof, rng, progress)
+}
+
+/// read a proving key, reporting it as the `LoadKey` phase
+pub(crate) fn read_pk_progress<E: Curve, R: Read>(
+    in_pk: R,
+    progress: &mut dyn Progress,
+) -> Result<(RamConstraints, CircuitInfo, Parameters<E>)> {
+    progress.start(Phase::LoadKey, None);
+    let pk = format::read_pk::<E, _>(in_pk)?;
+    progress.finish(Phase::LoadKey, pk.0.len()?);
+    Ok(pk)
+}
 
-    let rng = &mut thread_rng();
+/// check that the proving key was created for the circuit of `signals`, the source
+///   hashes are only compared when both are known, and the constraints of the
+///   circuit with the hash in the key header when they are given
+pub(crate) fn check_circuit<S: Signals, C: Constraints>(
+    signals: &S,
+    source_hash: Option<&str>,
+    constraints: Option<(&C, &PkHeader)>,
+    circuit: &CircuitInfo,
+) -> Result<()> {
+    if let (Some(hash), Some(key_hash)) = (source_hash, circuit.source_hash.as_ref()) {
+        if hash != key_hash {
+            return Err(Error::CircuitMismatch(format!(
+                "the proving key was created from circuit {}, but this one is {}",
+                key_hash, hash
+            )));
+        }
+    }
+    if let Some((constraints, header)) = constraints {
+        let (hash, _) = constraints_hash(constraints)?;
+        if hash != header.hash {
+            return Err(Error::CircuitMismatch(
+                "the proving key was not created for the constraints of this circuit".to_string(),
+            ));
+        }
+    }
+    if signals.len()? != circuit.wire_to_signal.len() {
+        return Err(Error::CircuitMismatch(format!(
+            "the proving key is for a circuit with {} signals, but this one has {}",
+            circuit.wire_to_signal.len(),
+            signals.len()?
+        )));
+    }
+    Ok(())
+}
 
-    let (constraints, params) = format::read_pk(in_pk)?;
+/// create a proof from a witness ordered by wire, as the one written by `circom2 witness`
+pub fn prove_witness<E: Curve, R: Read, W: Write, G: Rng>(
+    witness: &Witness,
+    in_pk: R,
+    out_proof: &mut W,
+    rng: &mut G,
+    progress: &mut dyn Progress,
+) -> Result<Vec<(String,FS)>> {
+    let (constraints, circuit, params) = read_pk_progress::<E, _>(in_pk, progress)?;
+    let signals = circuit.signals(E::FIELD, witness)?;
+    prove(&signals, &constraints, &params, out_proof, rng, progress)
+}
 
-    check_constrains_eval_zero(&constraints,&signals)
-        .expect("check_constrains_eval_zero failed");
+/// the circuit values are only meaningful in the scalar field of the curve
+pub(crate) fn check_field<E: Curve, S: Signals>(signals: &S) -> Result<()> {
+    if signals.field() != E::FIELD {
+        return Err(Error::Mismatch(format!(
+            "the circuit field is {:?}, but the {} curve needs {:?}",
+            signals.field(),
+            E::NAME,
+            E::FIELD
+        )));
+    }
+    Ok(())
+}
 
-    let circuit = CircomCircuit::<Bn256> {
-        signals: &signals,
-        constraints: &constraints,
+pub(crate) fn prove<E: Curve, S: Signals, C: Constraints, W: Write, G: Rng>(
+    signals: &S,
+    constraints: &C,
+    params: &Parameters<E>,
+    out_proof: &mut W,
+    rng: &mut G,
+    progress: &mut dyn Progress,
+) -> Result<Vec<(String,FS)>> {
+    check_field::<E, _>(signals)?;
+
+    check_constrains_eval_zero(constraints,signals).map_err(|err| Error::Mismatch(format!(
+        "the witness does not satisfy the constraints of the proving key: {:?}", err
+    )))?;
+
+    let n_constraints = constraints.len()?;
+    progress.start(Phase::Prove, Some(n_constraints));
+    let circuit = CircomCircuit::<E> {
+        signals,
+        constraints,
+        progress: &mut *progress,
+        phase: Phase::Prove,
         phantom: PhantomData,
     };
 
     // Create proof
-    let proof = create_random_proof(circuit, &params, rng).expect("cannot create proof");
+    let proof = create_random_proof(circuit, params, rng)?;
+    progress.finish(Phase::Prove, n_constraints);
 
     let mut public_inputs = Vec::new();
-    for i in 0..signals.len()? {
-        let signal = signals.get_by_id(i)?.unwrap();
+    for signal in signals.iter() {
+        let signal = signal?;
         if is_public_input(&signal) {
-            let fs = (&*signal).clone().value.unwrap().try_into_fs().unwrap();
+            let fs = signal.value.clone().and_then(|v| v.try_into_fs()).ok_or_else(|| {
+                Error::Mismatch(format!("signal {} has no value", signal.full_name.0))
+            })?;
             let name = signal.full_name.0.to_string();
             public_inputs.push((name,fs));
         }
     }
 
     // Self-verify
+    progress.start(Phase::Verify, None);
     let vk = prepare_verifying_key(&params.vk);
     let verify_public_inputs = public_inputs
         .iter()
-        .map(|(_,n)| Fr::from_str(&(n.0.to_string()))
-            .expect(&format!("cannot parse fe {}",&n.0.to_string())))
+        .map(|(_,n)| fe_to_bellman_fr::<E>(n))
         .collect::<Vec<_>>();
 
     verify_proof(&vk, &proof, &verify_public_inputs)?;
+    progress.finish(Phase::Verify, 1);
     format::write_input_and_proof(public_inputs.clone(), proof, out_proof)?;
 
     Ok(public_inputs)
 }
 
+/// verify a proof against the verifying key and the public inputs
+pub fn verify<E: Engine>(vk: &VerifyingKey<E>, proof: &Proof<E>, public_inputs: &[FS]) -> Result<bool> {
+    let public_inputs = public_frs(vk, public_inputs)?;
+    let pvk = prepare_verifying_key(vk);
+    Ok(verify_proof(&pvk, proof, &public_inputs)?)
+}
+
+/// verify many proofs of the same key with a single multi pairing over a random
+///   linear combination of their equations, fails if any of the proofs is not valid.
+///   An empty batch is an error, there is nothing that could be valid
+pub fn verify_batch<E: Engine, G: Rng>(
+    vk: &VerifyingKey<E>,
+    proofs: &[(Proof<E>, Vec<FS>)],
+    rng: &mut G,
+) -> Result<bool> {
+    if proofs.is_empty() {
+        return Err(Error::BadFormat("there are no proofs to verify".to_string()));
+    }
+    // sum r_i e(A_i, B_i) = e(sum r_i alpha, beta) + e(sum r_i vk_x_i, gamma) + e(sum r_i C_i, delta)
+    let (mut sum_r, mut sum_vk_x, mut sum_c) = (E::Fr::zero(), E::G1::zero(), E::G1::zero());
+    let mut pairs = Vec::with_capacity(proofs.len() + 3);
+    for (proof, public_inputs) in proofs {
+        let r = E::Fr::rand(rng);
+        let mut vk_x = vk.ic[0].into_projective();
+        for (input, ic) in public_frs(vk, public_inputs)?.iter().zip(vk.ic[1..].iter()) {
+            vk_x.add_assign(&ic.mul(input.into_repr()));
+        }
+        vk_x.mul_assign(r.into_repr());
+        sum_vk_x.add_assign(&vk_x);
+        sum_c.add_assign(&proof.c.mul(r.into_repr()));
+        sum_r.add_assign(&r);
+        pairs.push((proof.a.mul(r.into_repr()).into_affine().prepare(), proof.b.prepare()));
+    }
+    let mut sum_alpha = vk.alpha_g1.mul(sum_r.into_repr());
+    sum_alpha.negate();
+    sum_vk_x.negate();
+    sum_c.negate();
+    pairs.push((sum_alpha.into_affine().prepare(), vk.beta_g2.prepare()));
+    pairs.push((sum_vk_x.into_affine().prepare(), vk.gamma_g2.prepare()));
+    pairs.push((sum_c.into_affine().prepare(), vk.delta_g2.prepare()));
+
+    let pairs = pairs.iter().map(|(g1, g2)| (g1, g2)).collect::<Vec<_>>();
+    let result = E::final_exponentiation(&E::miller_loop(pairs.iter()))
+        .ok_or_else(|| Error::Unexpected("final exponentiation failed".to_string()))?;
+    Ok(result == E::Fqk::one())
+}
+
+fn public_frs<E: Engine>(vk: &VerifyingKey<E>, public_inputs: &[FS]) -> Result<Vec<E::Fr>> {
+    if vk.ic.len() != public_inputs.len() + 1 {
+        return Err(Error::BadFormat(format!(
+            "verifying key expects {} public inputs, got {}",
+            vk.ic.len() - 1,
+            public_inputs.len()
+        )));
+    }
+    Ok(public_inputs
+        .iter()
+        .map(|fs| fe_to_bellman_fr::<E>(fs))
+        .collect())
+}
+
+/// verification key from a snarkjs `.json` file or a proving key
+pub fn read_vk_file<E: Curve>(vk_path: &str) -> Result<VerifyingKey<E>> {
+    let vk_file = BufReader::new(File::open(vk_path)?);
+    if vk_path.ends_with(".json") {
+        read_vk_json::<E, _>(vk_file)
+    } else {
+        read_pk_vk::<E, _>(vk_file)
+    }
+}
+
+/// verify a proof.json, the verifying key is read from a snarkjs `.json` or from a
+///   proving key, public inputs are the ones in the proof file unless given
+pub fn verify_files<E: Curve>(vk_path: &str, proof_path: &str, public_inputs: Option<Vec<FS>>) -> Result<bool> {
+    let vk = read_vk_file::<E>(vk_path)?;
+    let (proof_inputs, proof) = read_input_and_proof(BufReader::new(File::open(proof_path)?))?;
+
+    verify(&vk, &proof, &public_inputs.unwrap_or(proof_inputs))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -178,7 +443,9 @@ mod test {
     };
     use circom2_compiler::algebra::Value;
     use circom2_compiler::evaluator::{Evaluator, Mode, Scope};
-    use circom2_compiler::storage::Ram;
+    use circom2_compiler::format::WireMap;
+    use circom2_compiler::progress::NoProgress;
+    use circom2_compiler::storage::{build_witness, Ram};
     use circom2_compiler::storage::StorageFactory;
     use pairing::bn256::{Bn256, Fr};
     use rand::thread_rng;
@@ -213,11 +480,12 @@ mod test {
         let rng = &mut thread_rng();
 
         // Create parameters for our circuit
-        println!("Run setup ---------------------------------");
         let params = {
             let circuit = CircomCircuit::<Bn256> {
                 signals: &ev_r1cs.signals,
                 constraints: &ev_r1cs.constraints,
+                progress: &mut NoProgress,
+                phase: Phase::Setup,
                 phantom: PhantomData,
             };
 
@@ -246,10 +514,11 @@ mod test {
         check_constrains_eval_zero(&ev_r1cs.constraints,&ev_witness.signals)
             .expect("cannot check all constraints = 0");
 
-        println!("Creating proofs --------------------------------- ");
         let circuit = CircomCircuit::<Bn256> {
             signals: &ev_witness.signals,
             constraints: &ev_r1cs.constraints,
+            progress: &mut NoProgress,
+            phase: Phase::Prove,
             phantom: PhantomData,
         };
 
@@ -296,11 +565,20 @@ mod test {
 
         // setup -----------------------------------------------------
 
-        let (pk, vk) = (
+        let (pk, vk, vk_json) = (
             File::create("/tmp/pk").unwrap(),
             File::create("/tmp/ver.sol").unwrap(),
+            File::create("/tmp/verification_key.json").unwrap(),
         );
-        setup(&ev_r1cs, pk, vk).expect("cannot setup");
+        setup::<Bn256, _, _, _, _, _, _>(&ev_r1cs.signals, &ev_r1cs.constraints, Some(&ev_r1cs.circuit_hash()), pk, Some(vk), vk_json, &mut thread_rng(), &mut NoProgress).expect("cannot setup");
+
+        // the verifying key is readable from the proving key and the json
+        let vk = read_pk_vk::<Bn256, _>(File::open("/tmp/pk").unwrap()).unwrap();
+        let vk_json = read_vk_json::<Bn256, _>(File::open("/tmp/verification_key.json").unwrap()).unwrap();
+        assert_eq!(2, vk.ic.len());
+        assert_eq!(vk.ic, vk_json.ic);
+        assert_eq!(vk.alpha_g1, vk_json.alpha_g1);
+        assert_eq!(vk.delta_g2, vk_json.delta_g2);
 
         // Compute witness -------------------------------------------
         let mut ram = Ram::default();
@@ -322,9 +600,206 @@ mod test {
         // Create and verify proof
         let mut proof_out = Vec::new();
         let pk = File::open("/tmp/pk").unwrap();
-        let public_input = generate_verified_proof(ev_witness.signals, pk, &mut proof_out).unwrap();
+        let hash = ev_witness.circuit_hash();
+        let public_input = generate_verified_proof::<Bn256, _, _, _, _>(ev_witness.signals, Some(&hash), pk, &mut proof_out, &mut thread_rng(), &mut NoProgress).unwrap();
         assert_eq!("[(\"main.c\", 21)]", format!("{:?}", public_input));
 
+        // verify the written proof
+        File::create("/tmp/proof.json").unwrap().write_all(&proof_out).unwrap();
+        assert!(verify_files::<Bn256>("/tmp/verification_key.json", "/tmp/proof.json", None).unwrap());
+        assert!(verify_files::<Bn256>("/tmp/pk", "/tmp/proof.json", None).unwrap());
+        assert!(!verify_files::<Bn256>("/tmp/pk", "/tmp/proof.json", Some(vec![FS::from(22)])).unwrap());
+
+        // prove from a witness ordered by wire
+        let (ev_witness, wires) = {
+            let mut ram = Ram::default();
+            let mut ev_witness = Evaluator::new(
+                Mode::GenWitness,
+                ram.new_signals().unwrap(),
+                ram.new_constraints().unwrap(),
+            );
+            ev_witness.set_deferred_value("main.a".to_string(), Value::from(5));
+            ev_witness.set_deferred_value("main.b".to_string(), Value::from(3));
+            ev_witness
+                .eval_inline(&mut Scope::new(true, None, "root".to_string()), circuit)
+                .unwrap();
+            let wires = WireMap::new(&ev_witness.signals).unwrap();
+            (ev_witness, wires)
+        };
+        let witness = wires.to_wires(&build_witness(&ev_witness.signals).unwrap());
+        let mut proof_out = Vec::new();
+        let pk = File::open("/tmp/pk").unwrap();
+        let public_input = prove_witness::<Bn256, _, _, _>(&witness, pk, &mut proof_out, &mut thread_rng(), &mut NoProgress).unwrap();
+        assert_eq!("[(\"main.c\", 15)]", format!("{:?}", public_input));
+
+        // refuse keys of other circuits
+        let mut ram = Ram::default();
+        let mut ev_other = Evaluator::new(
+            Mode::GenWitness,
+            ram.new_signals().unwrap(),
+            ram.new_constraints().unwrap(),
+        );
+        ev_other
+            .eval_inline(&mut Scope::new(true, None, "root".to_string()), "
+                template t() {
+                    signal input a;
+                    signal output c;
+                    c <== a * a;
+                }
+                component main = t();
+            ")
+            .unwrap();
+        let pk = File::open("/tmp/pk").unwrap();
+        let hash = ev_other.circuit_hash();
+        match generate_verified_proof::<Bn256, _, _, _, _>(ev_other.signals, Some(&hash), pk, &mut Vec::new(), &mut thread_rng(), &mut NoProgress) {
+            Err(Error::CircuitMismatch(_)) => {}
+            _ => panic!("expected a circuit mismatch"),
+        }
+
+        // with the same signals but other constraints
+        let edited = "
+            template t() {
+                signal private input a;
+                signal private input b;
+                signal output c;
+                c <== a * b + 1;
+            }
+            component main = t();
+        ";
+        let mut ram = Ram::default();
+        let mut ev_edited = Evaluator::new(
+            Mode::GenWitness,
+            ram.new_signals().unwrap(),
+            ram.new_constraints().unwrap(),
+        );
+        ev_edited.set_deferred_value("main.a".to_string(), Value::from(7));
+        ev_edited.set_deferred_value("main.b".to_string(), Value::from(3));
+        ev_edited
+            .eval_inline(&mut Scope::new(true, None, "root".to_string()), edited)
+            .unwrap();
+        let hash = ev_edited.circuit_hash();
+        let pk = File::open("/tmp/pk").unwrap();
+        match generate_verified_proof::<Bn256, _, _, _, _>(ev_edited.signals, Some(&hash), pk, &mut Vec::new(), &mut thread_rng(), &mut NoProgress) {
+            Err(Error::CircuitMismatch(_)) => {}
+            _ => panic!("expected a circuit mismatch"),
+        }
+
+        // the constraints are compared when given, without a source hash
+        let mut ram = Ram::default();
+        let mut ev_edited = Evaluator::new(
+            Mode::GenConstraints,
+            ram.new_signals().unwrap(),
+            ram.new_constraints().unwrap(),
+        );
+        ev_edited
+            .eval_inline(&mut Scope::new(true, None, "root".to_string()), edited)
+            .unwrap();
+        let pk = File::open("/tmp/pk").unwrap();
+        match generate_verified_proof_with::<Bn256, _, _, _, _, _>(ev_edited.signals, None, &ev_edited.constraints, pk, &mut Vec::new(), &mut thread_rng(), &mut NoProgress) {
+            Err(Error::CircuitMismatch(_)) => {}
+            _ => panic!("expected a circuit mismatch"),
+        }
+        match generate_verified_proof::<Bn256, _, _, _, _>(ev_witness.signals, None, &b"not a key"[..], &mut Vec::new(), &mut thread_rng(), &mut NoProgress) {
+            Err(Error::BadFormat(_)) => {}
+            _ => panic!("expected a bad format"),
+        }
+
     }
 
+    #[test]
+    fn test_seeded_setup_and_proof() {
+        let circuit = "
+            template t() {
+                signal private input a;
+                signal output c;
+                c <== a * a;
+            }
+            component main = t();
+        ";
+
+        let eval = |mode, a: Option<u64>| {
+            let mut ram = Ram::default();
+            let mut ev = Evaluator::new(mode, ram.new_signals().unwrap(), ram.new_constraints().unwrap());
+            if let Some(a) = a {
+                ev.set_deferred_value("main.a".to_string(), Value::from(a));
+            }
+            ev.eval_inline(&mut Scope::new(true, None, "root".to_string()), circuit)
+                .unwrap();
+            ev
+        };
+
+        let ev_r1cs = eval(Mode::GenConstraints, None);
+        let run = || {
+            let (mut pk, mut sol, mut json, mut proof) = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+            setup::<Bn256, _, _, _, _, _, _>(&ev_r1cs.signals, &ev_r1cs.constraints, None, &mut pk, Some(&mut sol), &mut json, &mut new_rng(Some(42)), &mut NoProgress)
+                .expect("cannot setup");
+            let ev_witness = eval(Mode::GenWitness, Some(3));
+            generate_verified_proof::<Bn256, _, _, _, _>(ev_witness.signals, None, &pk[..], &mut proof, &mut new_rng(Some(42)), &mut NoProgress)
+                .expect("cannot prove");
+            (pk, sol, json, proof)
+        };
+
+        assert!(run() == run());
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let circuit = "
+            template t() {
+                signal private input a;
+                signal output c;
+                c <== a * a;
+            }
+            component main = t();
+        ";
+
+        let eval = |mode, a: Option<u64>| {
+            let mut ram = Ram::default();
+            let mut ev = Evaluator::new(mode, ram.new_signals().unwrap(), ram.new_constraints().unwrap());
+            if let Some(a) = a {
+                ev.set_deferred_value("main.a".to_string(), Value::from(a));
+            }
+            ev.eval_inline(&mut Scope::new(true, None, "root".to_string()), circuit)
+                .unwrap();
+            ev
+        };
+
+        let ev_r1cs = eval(Mode::GenConstraints, None);
+        let (mut pk, mut sol, mut json) = (Vec::new(), Vec::new(), Vec::new());
+        setup::<Bn256, _, _, _, _, _, _>(&ev_r1cs.signals, &ev_r1cs.constraints, None, &mut pk, Some(&mut sol), &mut json, &mut thread_rng(), &mut NoProgress)
+            .expect("cannot setup");
+        let vk = read_pk_vk::<Bn256, _>(&pk[..]).unwrap();
+
+        let proofs = (2..5)
+            .map(|a| {
+                let mut proof = Vec::new();
+                generate_verified_proof::<Bn256, _, _, _, _>(eval(Mode::GenWitness, Some(a)).signals, None, &pk[..], &mut proof, &mut thread_rng(), &mut NoProgress)
+                    .expect("cannot prove");
+                let (inputs, proof) = read_input_and_proof(&proof[..]).unwrap();
+                (proof, inputs)
+            })
+            .collect::<Vec<_>>();
+        assert!(verify_batch(&vk, &proofs, &mut thread_rng()).unwrap());
+
+        // one proof with a wrong public input
+        let mut bad = proofs.clone();
+        bad[1].1 = vec![FS::from(10)];
+        assert!(!verify_batch(&vk, &bad, &mut thread_rng()).unwrap());
+
+        // one proof of other public inputs
+        let mut bad = proofs.clone();
+        bad[2].0 = proofs[0].0.clone();
+        assert!(!verify_batch(&vk, &bad, &mut thread_rng()).unwrap());
+
+        // one proof with a tampered point
+        let mut bad = proofs.clone();
+        bad[0].0.c = proofs[1].0.c;
+        assert!(!verify_batch(&vk, &bad, &mut thread_rng()).unwrap());
+
+        // nothing to verify
+        match verify_batch(&vk, &[], &mut thread_rng()) {
+            Err(Error::BadFormat(_)) => {}
+            _ => panic!("expected an empty batch to fail"),
+        }
+    }
 }
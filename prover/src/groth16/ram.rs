@@ -1,56 +1,88 @@
 use circom2_compiler::{
     evaluator::print_info,
     evaluator::{Evaluator,Mode},
-    algebra::{Value,FS}
+    algebra::{Field,Value,FS},
+    progress::{NoProgress, Progress},
 };
 
 use std::fs::File;
+use std::io::BufReader;
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use bellman::groth16::Parameters;
+use rand::Rng;
+
+use super::curve::Curve;
 use super::error::{Error,Result};
+use super::format::{self, CircuitInfo};
+use super::mapped::MappedKey;
+use super::prover::{check_circuit, prove, read_pk_progress};
+use super::stream::prove_mapped;
 
 use circom2_compiler::storage::{Constraints, Signals};
-use circom2_compiler::storage::{Ram, StorageFactory};
+use circom2_compiler::storage::{Ram, RamConstraints, RamSignals, StorageFactory};
 use circom2_compiler::tester::dump_error;
 
 
-pub fn setup_ram(circuit_path: &str, proving_key_path: &str, verificator_key_path: &str) -> Result<()> {
+/// compile the circuit and write its keys, the solidity verifier is skipped if
+///   `verificator_key_path` is `None`
+pub fn setup_ram<E: Curve>(
+    circuit_path: &str,
+    proving_key_path: &str,
+    verificator_key_path: Option<&str>,
+    verification_key_json_path: &str,
+    seed: Option<u64>,
+    progress: &mut dyn Progress,
+) -> Result<()> {
 
-    let mut storage = Ram::default();
+    let mut storage = Ram::new(E::FIELD);
 
-    let mut eval = Evaluator::new(
-        Mode::GenConstraints,
-        storage.new_signals()?,
-        storage.new_constraints()?,
-    );
-    info!("Compiling circuit...");
+    // the evaluator reports the constraints to the progress until it is dropped
+    let (signals, constraints, source_hash) = {
+        let mut eval = Evaluator::new(
+            Mode::GenConstraints,
+            storage.new_signals()?,
+            storage.new_constraints()?,
+        );
+        eval.progress = Box::new(&mut *progress);
+        info!("Compiling circuit...");
 
-    if let Err(err) = eval.eval_file(".", &circuit_path) {
-        dump_error(&eval, &format!("{:?}", err));
-        return Err(Error::from(err));
-    }
+        if let Err(err) = eval.eval_file(".", &circuit_path) {
+            dump_error(&eval, &format!("{:?}", err));
+            return Err(Error::from(err));
+        }
 
-    print_info(&eval,false);
+        print_info(&eval,false);
+        let source_hash = eval.circuit_hash();
+        (eval.signals, eval.constraints, source_hash)
+    };
     info!("Running setup");
 
-    let (pk,vk) = (
+    let (pk,vk,vk_json) = (
         File::create(proving_key_path)?,
-        File::create(verificator_key_path)?
+        verificator_key_path.map(File::create).transpose()?,
+        File::create(verification_key_json_path)?
     );
 
-    super::setup(&eval, pk, vk)?;
+    super::setup::<E, _, _, _, _, _, _>(&signals, &constraints, Some(&source_hash), pk, vk, vk_json, &mut super::new_rng(seed), progress)?;
 
     Ok(())
 }
 
-pub fn prove_ram(circuit_path: &str,proving_key_path: &str, inputs: Vec<(String,FS)>) -> Result<String> {
+/// compute the witness of the circuit in `field`, with the `circuit_hash` of its source
+pub fn witness_ram(circuit_path: &str, field: Field, inputs: Vec<(String,FS)>, progress: &mut dyn Progress) -> Result<(RamSignals, String)> {
 
     info!("Generating witness...");
 
-    let mut ram = Ram::default();
+    let mut ram = Ram::new(field);
     let mut ev_witness = Evaluator::new(
         Mode::GenWitness,
         ram.new_signals()?,
         ram.new_constraints()?,
     );
+    ev_witness.progress = Box::new(progress);
 
     info!("Checking constraints...");
 
@@ -60,8 +92,8 @@ pub fn prove_ram(circuit_path: &str,proving_key_path: &str, inputs: Vec<(String,
 
     info!("Checking signals...");
 
-    for n in 1..ev_witness.signals.len()? {
-        let signal = &*ev_witness.signals.get_by_id(n).unwrap().unwrap();
+    for signal in ev_witness.signals.iter_range(1..ev_witness.signals.len()?) {
+        let signal = signal?;
         if signal.value.is_none() {
             return Err(Error::Unexpected(format!("signal '{}' value is not defined",signal.full_name.0)));
         }  
@@ -73,20 +105,186 @@ pub fn prove_ram(circuit_path: &str,proving_key_path: &str, inputs: Vec<(String,
 
     ev_witness.eval_file(".", &circuit_path)?;
 
+    let source_hash = ev_witness.circuit_hash();
+    Ok((ev_witness.signals, source_hash))
+}
+
+pub fn prove_ram<E: Curve>(
+    circuit_path: &str,
+    proving_key_path: &str,
+    inputs: Vec<(String,FS)>,
+    seed: Option<u64>,
+    progress: &mut dyn Progress,
+) -> Result<String> {
+
+    let prover = Prover::<E>::open(circuit_path, proving_key_path, progress)?;
+
     // Create proof
     info!("Creating and self-verifying proof...");
 
-    let pk = File::open(proving_key_path)?;
+    let proof = prover.prove(inputs, &mut super::new_rng(seed), progress)?;
 
-    let mut proof = Vec::new();
+    info!("Proof generated and self-verified");
 
-    let _ = super::generate_verified_proof(
-        ev_witness.signals,
-        pk,
-        &mut proof
-    )?;
+    Ok(proof)
+}
 
-    info!("Proof generated and self-verified");
+/// proving key of a `Prover`
+enum Key<E: Curve> {
+    /// read into memory, proofs are created by the bellman prover
+    Loaded {
+        constraints: RamConstraints,
+        circuit: CircuitInfo,
+        params: Parameters<E>,
+    },
+    /// memory mapped, streamed while proving
+    Mapped(MappedKey<E>),
+}
+
+/// proving key loaded once, to create many proofs of the same circuit
+pub struct Prover<E: Curve> {
+    circuit_path: String,
+    key: Key<E>,
+}
+
+impl<E: Curve> Prover<E> {
+    pub fn open(circuit_path: &str, proving_key_path: &str, progress: &mut dyn Progress) -> Result<Self> {
+        let pk = BufReader::new(File::open(proving_key_path)?);
+        let (constraints, circuit, params) = read_pk_progress(pk, progress)?;
+        Ok(Prover {
+            circuit_path: circuit_path.to_string(),
+            key: Key::Loaded { constraints, circuit, params },
+        })
+    }
+
+    /// map the proving key instead of reading it, for keys that do not fit in
+    ///   memory. Proving is slower than with `open`
+    pub fn open_mapped(circuit_path: &str, proving_key_path: &str, progress: &mut dyn Progress) -> Result<Self> {
+        Ok(Prover {
+            circuit_path: circuit_path.to_string(),
+            key: Key::Mapped(MappedKey::open(proving_key_path, progress)?),
+        })
+    }
+
+    /// proof.json of the inputs
+    pub fn prove<G: Rng>(&self, inputs: Vec<(String,FS)>, rng: &mut G, progress: &mut dyn Progress) -> Result<String> {
+        let (signals, source_hash) = witness_ram(&self.circuit_path, E::FIELD, inputs, progress)?;
+
+        let mut proof = Vec::new();
+        match &self.key {
+            Key::Loaded { constraints, circuit, params } => {
+                check_circuit(&signals, Some(&source_hash), None::<(&RamConstraints, _)>, circuit)?;
+                prove(&signals, constraints, params, &mut proof, rng, progress)?;
+            }
+            Key::Mapped(key) => {
+                prove_mapped(&signals, Some(&source_hash), key, &mut proof, rng, progress)?;
+            }
+        }
+        Ok(String::from_utf8_lossy(&proof).to_string())
+    }
+
+    /// proof.json of an inputs json object
+    pub fn prove_json<G: Rng>(&self, inputs_json: &str, rng: &mut G, progress: &mut dyn Progress) -> Result<String> {
+        self.prove(format::flatten_json("main", inputs_json)?, rng, progress)
+    }
+
+    /// prove a stream of inputs json objects one after another, or across `threads`
+    ///   threads, `done` is called with the position of each input and its proof
+    ///   as they are finished. A seeded prover uses `seed + position` for each one,
+    ///   the progress of each proof is not reported
+    pub fn prove_stream<I, F>(self: Arc<Self>, inputs: I, threads: usize, seed: Option<u64>, mut done: F) -> Result<()>
+    where
+        I: Iterator<Item = String> + Send + 'static,
+        F: FnMut(usize, Result<String>),
+    {
+        let rng = move |n: usize| super::new_rng(seed.map(|seed| seed.wrapping_add(n as u64)));
+
+        if threads <= 1 {
+            for (n, inputs_json) in inputs.enumerate() {
+                done(n, self.prove_json(&inputs_json, &mut rng(n), &mut NoProgress));
+            }
+            return Ok(());
+        }
+
+        let inputs = Arc::new(Mutex::new(inputs.enumerate()));
+        let (tx, rx) = mpsc::channel();
+        let workers = (0..threads)
+            .map(|_| {
+                let (prover, inputs, tx) = (self.clone(), inputs.clone(), tx.clone());
+                thread::spawn(move || loop {
+                    let next = match inputs.lock() {
+                        Ok(mut inputs) => inputs.next(),
+                        Err(_) => break,
+                    };
+                    match next {
+                        Some((n, inputs_json)) => {
+                            if tx.send((n, prover.prove_json(&inputs_json, &mut rng(n), &mut NoProgress))).is_err() {
+                                break;
+                            }
+                        }
+                        None => break,
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        drop(tx);
 
-    Ok(String::from_utf8_lossy(&proof).to_string())
+        for (n, proof) in rx {
+            done(n, proof);
+        }
+        for worker in workers {
+            worker
+                .join()
+                .map_err(|_| Error::Unexpected("prover thread panicked".to_string()))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pairing::bn256::Bn256;
+    use std::io::Write;
+
+    #[test]
+    fn test_prove_stream() {
+        File::create("/tmp/prove_stream.circom").unwrap().write_all(b"
+            template t() {
+                signal private input a;
+                signal output c;
+                c <== a * a;
+            }
+            component main = t();
+        ").unwrap();
+        setup_ram::<Bn256>(
+            "/tmp/prove_stream.circom",
+            "/tmp/prove_stream.pk",
+            Some("/tmp/prove_stream.sol"),
+            "/tmp/prove_stream.json",
+            Some(1),
+            &mut NoProgress,
+        ).unwrap();
+
+        let prover = Arc::new(Prover::<Bn256>::open("/tmp/prove_stream.circom", "/tmp/prove_stream.pk", &mut NoProgress).unwrap());
+        let inputs = vec!["{\"a\":2}", "{\"a\":3}", "not json", "{\"a\":4}"];
+        let run = |prover: &Arc<Prover<Bn256>>, threads| {
+            let mut proofs = vec![None; inputs.len()];
+            let stream = inputs.clone().into_iter().map(|s| s.to_string());
+            prover.clone().prove_stream(stream, threads, Some(7), |n, proof| {
+                proofs[n] = Some(proof.ok());
+            }).unwrap();
+            proofs.into_iter().map(|proof| proof.unwrap()).collect::<Vec<_>>()
+        };
+
+        let sequential = run(&prover, 1);
+        assert!(sequential[0].is_some() && sequential[1].is_some() && sequential[3].is_some());
+        assert!(sequential[2].is_none());
+        assert!(sequential[1].as_ref().unwrap().contains("\"9\""));
+        assert_eq!(sequential, run(&prover, 3));
+
+        // the same proofs streaming the mapped key
+        let mapped = Arc::new(Prover::<Bn256>::open_mapped("/tmp/prove_stream.circom", "/tmp/prove_stream.pk", &mut NoProgress).unwrap());
+        assert_eq!(sequential, run(&mapped, 2));
+    }
 }
@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+use std::io::Write;
+
+use bellman::groth16::create_random_proof;
+use bellman::{Circuit, ConstraintSystem, SynthesisError};
+use ff::Field;
+use rand::Rng;
+
+use circom2_compiler::algebra::{Witness, FS, LC, SIGNAL_ONE};
+use circom2_compiler::progress::{Counter, Phase, Progress};
+use circom2_compiler::storage::{is_public_input, RamConstraints, Signals};
+
+use super::curve::Curve;
+use super::error::{Error, Result};
+use super::format::{self, fe_to_bellman_fr, lc_to_bellman, CircuitInfo};
+use super::mapped::MappedKey;
+use super::prover::{check_circuit, check_field, verify, PROGRESS_EVERY};
+
+/// values of the signals by id, the public inputs are the inputs of the
+///   bellman circuit and the other signals its aux variables
+struct Assignment<E: Curve> {
+    values: Vec<E::Fr>,
+    inputs: Vec<bool>,
+    public: Vec<(String, FS)>,
+}
+
+impl<E: Curve> Assignment<E> {
+    fn with_capacity(n_signals: usize) -> Self {
+        let mut values = Vec::with_capacity(n_signals);
+        values.push(E::Fr::one());
+        let mut inputs = Vec::with_capacity(n_signals);
+        inputs.push(true);
+        Assignment {
+            values,
+            inputs,
+            public: Vec::new(),
+        }
+    }
+
+    /// signals are pushed in id order, from the one after ONE
+    fn push(&mut self, value: &FS, public: Option<String>) {
+        self.values.push(fe_to_bellman_fr::<E>(value));
+        self.inputs.push(public.is_some());
+        if let Some(name) = public {
+            self.public.push((name, value.clone()));
+        }
+    }
+
+    fn from_signals<S: Signals>(signals: &S) -> Result<Self> {
+        let mut assignment = Self::with_capacity(signals.len()?);
+        for signal in signals.iter_range(SIGNAL_ONE + 1..signals.len()?) {
+            let signal = signal?;
+            let value = signal
+                .value
+                .clone()
+                .and_then(|v| v.try_into_fs())
+                .ok_or_else(|| Error::Mismatch(format!("signal {} has no value", signal.full_name.0)))?;
+            let public = if is_public_input(&signal) {
+                Some(signal.full_name.0.to_string())
+            } else {
+                None
+            };
+            assignment.push(&value, public);
+        }
+        Ok(assignment)
+    }
+
+    fn from_witness(witness: &Witness, circuit: &CircuitInfo) -> Result<Self> {
+        let n_signals = circuit.wire_to_signal.len();
+        if witness.len() != n_signals {
+            return Err(Error::Mismatch(format!(
+                "witness has {} values but the circuit has {} wires",
+                witness.len(),
+                n_signals
+            )));
+        }
+        let mut signal_to_wire = vec![0; n_signals];
+        for (wire, signal) in circuit.wire_to_signal.iter().enumerate() {
+            signal_to_wire[*signal] = wire;
+        }
+        let mut public = circuit.public.iter().cloned().collect::<HashMap<_, _>>();
+
+        let mut assignment = Self::with_capacity(n_signals);
+        for (signal, wire) in signal_to_wire.iter().enumerate().skip(SIGNAL_ONE + 1) {
+            assignment.push(&witness.0[*wire], public.remove(&signal));
+        }
+        Ok(assignment)
+    }
+
+    fn eval(&self, lc: &LC) -> Result<E::Fr> {
+        let mut acc = E::Fr::zero();
+        for (signal, coeff) in &lc.0 {
+            let mut value = *self
+                .values
+                .get(*signal)
+                .ok_or_else(|| Error::Mismatch(format!("the proving key uses the unknown signal {}", signal)))?;
+            value.mul_assign(&fe_to_bellman_fr::<E>(coeff));
+            acc.add_assign(&value);
+        }
+        Ok(acc)
+    }
+
+    /// check that the values satisfy every a * b + c = 0 of the key
+    fn check(&self, key: &MappedKey<E>) -> Result<()> {
+        key.for_each_constraint(|n, qeq| {
+            let mut sum = self.eval(&qeq.a)?;
+            sum.mul_assign(&self.eval(&qeq.b)?);
+            sum.add_assign(&self.eval(&qeq.c)?);
+            if !sum.is_zero() {
+                return Err(Error::Mismatch(format!(
+                    "the witness does not satisfy the constraint {} of the proving key",
+                    n
+                )));
+            }
+            Ok(())
+        })
+    }
+}
+
+/// the circuit of a mapped key, its constraints are read from the key while
+///   they are synthesized
+struct MappedCircuit<'a, E: Curve> {
+    key: &'a MappedKey<E>,
+    assignment: &'a Assignment<E>,
+    // receives the synthesized constraints count
+    progress: &'a mut dyn Progress,
+}
+
+impl<'a, E: Curve> Circuit<E> for MappedCircuit<'a, E> {
+    fn synthesize<CS: ConstraintSystem<E>>(self, cs: &mut CS) -> std::result::Result<(), SynthesisError> {
+        let assignment = self.assignment;
+        let mut variables = Vec::with_capacity(assignment.values.len());
+        variables.push(CS::one());
+        for id in SIGNAL_ONE + 1..assignment.values.len() {
+            let value = assignment.values[id];
+            let variable = if assignment.inputs[id] {
+                cs.alloc_input(|| format!("s{}", id), || Ok(value))?
+            } else {
+                cs.alloc(|| format!("s{}", id), || Ok(value))?
+            };
+            variables.push(variable);
+        }
+
+        let field = E::FIELD;
+        let progress = self.progress;
+        let mut counter = Counter::new(Phase::Prove, PROGRESS_EVERY);
+        self.key
+            .for_each_constraint(|n, qeq| {
+                cs.enforce(
+                    || format!("c{}", n),
+                    |lc| lc_to_bellman(lc, &variables, &qeq.a),
+                    |lc| lc_to_bellman(lc, &variables, &qeq.b),
+                    |lc| lc_to_bellman(lc, &variables, &qeq.c.neg(field)),
+                );
+                counter.inc(&mut *progress);
+                Ok(())
+            })
+            .map_err(|_| SynthesisError::Unsatisfiable)
+    }
+}
+
+/// create a proof of the circuit of `signals` with a memory mapped proving key,
+///   the constraints are checked before proving
+pub fn prove_mapped<E: Curve, S: Signals, W: Write, G: Rng>(
+    signals: &S,
+    source_hash: Option<&str>,
+    key: &MappedKey<E>,
+    out_proof: &mut W,
+    rng: &mut G,
+    progress: &mut dyn Progress,
+) -> Result<Vec<(String, FS)>> {
+    check_field::<E, _>(signals)?;
+    check_circuit(signals, source_hash, None::<(&RamConstraints, _)>, &key.circuit)?;
+    let assignment = Assignment::from_signals(signals)?;
+    prove_assignment(key, assignment, out_proof, rng, progress)
+}
+
+/// create a proof from a witness ordered by wire with a memory mapped proving key,
+///   without the circuit source
+pub fn prove_witness_mapped<E: Curve, W: Write, G: Rng>(
+    witness: &Witness,
+    key: &MappedKey<E>,
+    out_proof: &mut W,
+    rng: &mut G,
+    progress: &mut dyn Progress,
+) -> Result<Vec<(String, FS)>> {
+    let assignment = Assignment::from_witness(witness, &key.circuit)?;
+    prove_assignment(key, assignment, out_proof, rng, progress)
+}
+
+/// the bellman prover, with the constraints and the points of the parameters
+///   read from the mapped key as they are needed
+fn prove_assignment<E: Curve, W: Write, G: Rng>(
+    key: &MappedKey<E>,
+    assignment: Assignment<E>,
+    out_proof: &mut W,
+    rng: &mut G,
+    progress: &mut dyn Progress,
+) -> Result<Vec<(String, FS)>> {
+    if assignment.public.len() + 1 != key.vk.ic.len() {
+        return Err(Error::Mismatch(format!(
+            "the proving key is for {} public inputs, but the circuit has {}",
+            key.vk.ic.len() - 1,
+            assignment.public.len()
+        )));
+    }
+    assignment.check(key)?;
+
+    let n_constraints = key.n_constraints();
+    progress.start(Phase::Prove, Some(n_constraints));
+    let circuit = MappedCircuit {
+        key,
+        assignment: &assignment,
+        progress: &mut *progress,
+    };
+    let proof = create_random_proof(circuit, key, rng)?;
+    progress.finish(Phase::Prove, n_constraints);
+
+    // Self-verify
+    progress.start(Phase::Verify, None);
+    let public_inputs = assignment.public.iter().map(|(_, v)| v.clone()).collect::<Vec<_>>();
+    if !verify(&key.vk, &proof, &public_inputs)? {
+        return Err(Error::BadFormat(
+            "the created proof is not valid, the proving key is corrupted".to_string(),
+        ));
+    }
+    progress.finish(Phase::Verify, 1);
+    format::write_input_and_proof(assignment.public.clone(), proof, out_proof)?;
+
+    Ok(assignment.public)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::groth16::format::read_input_and_proof;
+    use crate::groth16::phase1::{domain_size, Phase1};
+    use crate::groth16::prover::{new_rng, setup_phase1};
+    use circom2_compiler::algebra::Value;
+    use circom2_compiler::evaluator::{Evaluator, Mode, Scope};
+    use circom2_compiler::format::WireMap;
+    use circom2_compiler::progress::NoProgress;
+    use circom2_compiler::storage::{build_witness, Constraints, Ram, StorageFactory};
+    use pairing::bn256::Bn256;
+    use std::fs::File;
+
+    const CIRCUIT: &str = "
+        template t() {
+            signal private input a;
+            signal private input b;
+            signal input k;
+            signal output c;
+            signal s;
+
+            s <== a * b;
+            c <== (s + k) * (a + 1);
+        }
+        component main = t();
+    ";
+
+    #[test]
+    fn test_prove_mapped() {
+        let eval = |mode: Mode| {
+            let witness = mode == Mode::GenWitness;
+            let mut ram = Ram::default();
+            let mut ev = Evaluator::new(mode, ram.new_signals().unwrap(), ram.new_constraints().unwrap());
+            if witness {
+                ev.set_deferred_value("main.a".to_string(), Value::from(3));
+                ev.set_deferred_value("main.b".to_string(), Value::from(4));
+                ev.set_deferred_value("main.k".to_string(), Value::from(5));
+            }
+            ev.eval_inline(&mut Scope::new(true, None, "root".to_string()), CIRCUIT)
+                .unwrap();
+            ev
+        };
+        let (ev_r1cs, ev_witness) = (eval(Mode::GenConstraints), eval(Mode::GenWitness));
+
+        let (size, _) = domain_size(ev_r1cs.constraints.len().unwrap() + 3).unwrap();
+        let phase1 = Phase1::insecure(size, &mut new_rng(Some(1)));
+        let mut pk = Vec::new();
+        setup_phase1(&phase1, &ev_r1cs.signals, &ev_r1cs.constraints, Some(&ev_r1cs.circuit_hash()), &mut pk, None::<Vec<u8>>, Vec::new(), &mut new_rng(Some(2)), &mut NoProgress)
+            .unwrap();
+        File::create("/tmp/prove_mapped.pk").unwrap().write_all(&pk).unwrap();
+
+        let key = MappedKey::<Bn256>::open("/tmp/prove_mapped.pk", &mut NoProgress).unwrap();
+        assert_eq!(ev_r1cs.constraints.len().unwrap(), key.n_constraints());
+
+        // c = (12 + 5) * 4
+        let mut proof = Vec::new();
+        let hash = ev_witness.circuit_hash();
+        let public = prove_mapped(&ev_witness.signals, Some(&hash), &key, &mut proof, &mut new_rng(Some(3)), &mut NoProgress).unwrap();
+        assert_eq!("[(\"main.c\", 68), (\"main.k\", 5)]", format!("{:?}", public));
+        let (inputs, proof) = read_input_and_proof::<Bn256, _>(&proof[..]).unwrap();
+        assert!(verify(&key.vk, &proof, &inputs).unwrap());
+
+        // the same proof from a witness ordered by wire
+        let wires = WireMap::new(&ev_witness.signals).unwrap();
+        let mut witness = wires.to_wires(&build_witness(&ev_witness.signals).unwrap());
+        let mut from_witness = Vec::new();
+        prove_witness_mapped(&witness, &key, &mut from_witness, &mut new_rng(Some(3)), &mut NoProgress).unwrap();
+        assert_eq!(inputs, read_input_and_proof::<Bn256, _>(&from_witness[..]).unwrap().0);
+
+        // a witness that does not satisfy the constraints
+        let last = witness.len() - 1;
+        witness.0[last] = FS::from(1);
+        match prove_witness_mapped(&witness, &key, &mut Vec::new(), &mut new_rng(Some(3)), &mut NoProgress) {
+            Err(Error::Mismatch(_)) => {}
+            _ => panic!("expected a mismatch"),
+        }
+
+        // the witness of another circuit
+        match prove_mapped(&ev_witness.signals, Some("edited"), &key, &mut Vec::new(), &mut new_rng(Some(3)), &mut NoProgress) {
+            Err(Error::CircuitMismatch(_)) => {}
+            _ => panic!("expected a circuit mismatch"),
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use ff::{Field, PrimeField};
+use pairing::bn256::{Fr, G1, G2};
+use pairing::{CurveAffine, CurveProjective};
+use rayon::prelude::*;
+
+/// values an fft runs over, the scalars of BN254 or its points
+pub(crate) trait Group: Copy + Send + Sync {
+    fn add(&mut self, other: &Self);
+    fn sub(&mut self, other: &Self);
+    fn scale(&mut self, k: &Fr);
+}
+
+impl Group for Fr {
+    fn add(&mut self, other: &Self) {
+        self.add_assign(other);
+    }
+
+    fn sub(&mut self, other: &Self) {
+        self.sub_assign(other);
+    }
+
+    fn scale(&mut self, k: &Fr) {
+        self.mul_assign(k);
+    }
+}
+
+impl Group for G1 {
+    fn add(&mut self, other: &Self) {
+        self.add_assign(other);
+    }
+
+    fn sub(&mut self, other: &Self) {
+        let mut neg = *other;
+        neg.negate();
+        self.add_assign(&neg);
+    }
+
+    fn scale(&mut self, k: &Fr) {
+        self.mul_assign(k.into_repr());
+    }
+}
+
+impl Group for G2 {
+    fn add(&mut self, other: &Self) {
+        self.add_assign(other);
+    }
+
+    fn sub(&mut self, other: &Self) {
+        let mut neg = *other;
+        neg.negate();
+        self.add_assign(&neg);
+    }
+
+    fn scale(&mut self, k: &Fr) {
+        self.mul_assign(k.into_repr());
+    }
+}
+
+/// evaluations at the powers of `omega` of the polynomial with coefficients `a`,
+///   in place. `omega` is a root of unity of order 2^log_n, the length of `a`
+pub(crate) fn fft<G: Group>(a: &mut [G], omega: &Fr, log_n: u32) {
+    fn bitreverse(mut n: usize, l: u32) -> usize {
+        let mut r = 0;
+        for _ in 0..l {
+            r = (r << 1) | (n & 1);
+            n >>= 1;
+        }
+        r
+    }
+
+    let n = a.len();
+    for k in 0..n {
+        let rk = bitreverse(k, log_n);
+        if k < rk {
+            a.swap(rk, k);
+        }
+    }
+
+    // the butterflies of each block of 2m values are independent
+    let mut m = 1;
+    for _ in 0..log_n {
+        let w_m = omega.pow(&[(n / (2 * m)) as u64]);
+        a.par_chunks_mut(2 * m).for_each(|block| {
+            let (low, high) = block.split_at_mut(m);
+            let mut w = Fr::one();
+            for (low, high) in low.iter_mut().zip(high.iter_mut()) {
+                let mut t = *high;
+                t.scale(&w);
+                let mut tmp = *low;
+                tmp.sub(&t);
+                *high = tmp;
+                low.add(&t);
+                w.mul_assign(&w_m);
+            }
+        });
+        m *= 2;
+    }
+}
+
+/// sum of `bases` times `scalars`, with buckets for windows of c bits from
+///   the most significant one
+pub(crate) fn multiexp<G: CurveAffine>(bases: &[G], scalars: &[G::Scalar]) -> G::Projective {
+    let scalars = scalars.iter().map(|s| s.into_repr()).collect::<Vec<_>>();
+    let c = if scalars.len() < 32 {
+        3
+    } else {
+        (scalars.len() as f64).ln().ceil() as u32
+    };
+    let mut result = G::Projective::zero();
+    for window in (0..(<G::Scalar as PrimeField>::NUM_BITS + c - 1) / c).rev() {
+        for _ in 0..c {
+            result.double();
+        }
+        let mut buckets = vec![G::Projective::zero(); (1 << c) - 1];
+        for (base, scalar) in bases.iter().zip(scalars.iter()) {
+            let digit = bits(scalar.as_ref(), window * c, c);
+            if digit != 0 {
+                buckets[digit - 1].add_assign_mixed(base);
+            }
+        }
+        // sum_i i bucket_i as a sum of running sums
+        let mut running = G::Projective::zero();
+        for bucket in buckets.iter().rev() {
+            running.add_assign(bucket);
+            result.add_assign(&running);
+        }
+    }
+    result
+}
+
+fn bits(limbs: &[u64], offset: u32, count: u32) -> usize {
+    (0..count)
+        .map(|i| offset + i)
+        .filter(|bit| (*bit as usize) < limbs.len() * 64)
+        .fold(0, |acc, bit| {
+            let set = (limbs[bit as usize / 64] >> (bit % 64)) & 1;
+            acc | ((set as usize) << (bit - offset))
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pairing::bn256::G1Affine;
+    use rand::{Rand, SeedableRng, XorShiftRng};
+
+    #[test]
+    fn test_fft_points() {
+        let rng = &mut XorShiftRng::from_seed([1, 2, 3, 4]);
+        let mut omega = Fr::root_of_unity();
+        for _ in 3..Fr::S {
+            omega.square();
+        }
+        let mut scalars = (0..8).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+        let g = G1::rand(rng);
+        let mut points = scalars
+            .iter()
+            .map(|s| {
+                let mut p = g;
+                p.scale(s);
+                p
+            })
+            .collect::<Vec<_>>();
+
+        // the fft in the exponent is the fft of the exponents
+        fft(&mut scalars, &omega, 3);
+        fft(&mut points, &omega, 3);
+        for (s, p) in scalars.iter().zip(points.iter()) {
+            let mut expected = g;
+            expected.scale(s);
+            assert_eq!(expected, *p);
+        }
+    }
+
+    #[test]
+    fn test_multiexp() {
+        let rng = &mut XorShiftRng::from_seed([1, 2, 3, 4]);
+        for len in &[1, 5, 40] {
+            let bases = (0..*len).map(|_| G1::rand(rng).into_affine()).collect::<Vec<G1Affine>>();
+            let scalars = (0..*len).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+            let mut expected = G1::zero();
+            for (base, scalar) in bases.iter().zip(scalars.iter()) {
+                expected.add_assign(&base.mul(scalar.into_repr()));
+            }
+            assert_eq!(expected, multiexp(&bases, &scalars));
+        }
+    }
+}
@@ -3,7 +3,6 @@ extern crate pairing;
 extern crate rand;
 #[macro_use]
 extern crate lazy_static;
-extern crate regex;
 #[macro_use]
 extern crate log;
 #[macro_use]
@@ -11,3 +10,5 @@ extern crate serde_derive;
 
 pub mod groth16;
 pub mod cuda;
+pub mod plonk;
+mod group;
@@ -0,0 +1,260 @@
+use ff::Field;
+use pairing::bn256::{Bn256, Fr};
+
+use circom2_compiler::algebra::{FS, LC, QEQ, SIGNAL_ONE};
+use circom2_compiler::storage::{is_public_input, Constraints, Signals};
+
+use crate::groth16::{fe_to_bellman_fr, Error, Result};
+
+/// one row of the circuit, it holds if
+///   q_m a b + q_l a + q_r b + q_o c + q_c + PI = 0
+///   where a, b, c are the values of the `wires` variables
+#[derive(Clone, Debug)]
+pub(crate) struct Gate {
+    pub q_m: Fr,
+    pub q_l: Fr,
+    pub q_r: Fr,
+    pub q_o: Fr,
+    pub q_c: Fr,
+    pub wires: [usize; 3],
+}
+
+impl Gate {
+    fn new(q_m: Fr, q_l: Fr, q_r: Fr, q_o: Fr, q_c: Fr, wires: [usize; 3]) -> Self {
+        Gate { q_m, q_l, q_r, q_o, q_c, wires }
+    }
+}
+
+/// the R1CS converted to gates, the variables are the signals in id order followed
+///   by the intermediate sums of the linear combinations. The first rows bind the
+///   public inputs, then the ONE signal and then the constraints
+pub(crate) struct Gates {
+    pub gates: Vec<Gate>,
+    pub n_public: usize,
+    pub n_vars: usize,
+    /// value of each variable, only when built from a witness
+    pub values: Option<Vec<Fr>>,
+}
+
+/// k * variable + constant
+struct Term {
+    k: Fr,
+    var: usize,
+    constant: Fr,
+}
+
+impl Gates {
+    /// gates of the circuit, with the values of the variables if `witness`
+    pub fn new<S: Signals, C: Constraints>(signals: &S, constraints: &C, witness: bool) -> Result<Self> {
+        let n_signals = signals.len()?;
+        let mut values = if witness {
+            Some(vec![Fr::zero(); n_signals])
+        } else {
+            None
+        };
+        let mut public = Vec::new();
+        for signal in signals.iter_range(SIGNAL_ONE + 1..n_signals) {
+            let signal = signal?;
+            if is_public_input(&signal) {
+                public.push(signal.id);
+            }
+            if let Some(values) = values.as_mut() {
+                let fs = signal
+                    .value
+                    .clone()
+                    .and_then(|v| v.try_into_fs())
+                    .ok_or_else(|| Error::Mismatch(format!("signal {} has no value", signal.full_name.0)))?;
+                values[signal.id] = fe_to_bellman_fr::<Bn256>(&fs);
+            }
+        }
+        if let Some(values) = values.as_mut() {
+            values[SIGNAL_ONE] = Fr::one();
+        }
+
+        let (zero, one) = (Fr::zero(), Fr::one());
+        let mut minus_one = one;
+        minus_one.negate();
+
+        let mut gates = Gates {
+            gates: Vec::new(),
+            n_public: public.len(),
+            n_vars: n_signals,
+            values,
+        };
+
+        // a = public input, the PI polynomial adds -input in these rows
+        for id in public {
+            gates.gates.push(Gate::new(zero, one, zero, zero, zero, [id, SIGNAL_ONE, SIGNAL_ONE]));
+        }
+        // ONE = 1, every unused wire is ONE so it has to be bound
+        gates
+            .gates
+            .push(Gate::new(zero, one, zero, zero, minus_one, [SIGNAL_ONE, SIGNAL_ONE, SIGNAL_ONE]));
+
+        for qeq in constraints.iter() {
+            gates.push_qeq(&qeq?);
+        }
+        Ok(gates)
+    }
+
+    /// a * b + c = 0, with a = ka x + ca, b = kb y + cb and c = kc z + cc
+    ///   ka kb x y + ka cb x + kb ca y + kc z + ca cb + cc = 0
+    fn push_qeq(&mut self, qeq: &QEQ) {
+        let a = self.reduce(&qeq.a);
+        let b = self.reduce(&qeq.b);
+        let c = self.reduce(&qeq.c);
+
+        let mul = |x: &Fr, y: &Fr| {
+            let mut r = *x;
+            r.mul_assign(y);
+            r
+        };
+        let mut q_c = mul(&a.constant, &b.constant);
+        q_c.add_assign(&c.constant);
+        self.gates.push(Gate::new(
+            mul(&a.k, &b.k),
+            mul(&a.k, &b.constant),
+            mul(&b.k, &a.constant),
+            c.k,
+            q_c,
+            [a.var, b.var, c.var],
+        ));
+    }
+
+    /// a linear combination as a single variable, adding a gate for each extra term
+    fn reduce(&mut self, lc: &LC) -> Term {
+        let mut constant = Fr::zero();
+        let mut terms = Vec::new();
+        for (signal, value) in &lc.0 {
+            let k = fe_to_bellman_fr::<Bn256>(value);
+            if *signal == SIGNAL_ONE {
+                constant.add_assign(&k);
+            } else if !k.is_zero() {
+                terms.push((k, *signal));
+            }
+        }
+
+        let mut terms = terms.into_iter();
+        let (mut k, mut var) = match terms.next() {
+            Some(first) => first,
+            None => (Fr::zero(), SIGNAL_ONE),
+        };
+        let (one, zero) = (Fr::one(), Fr::zero());
+        let mut minus_one = one;
+        minus_one.negate();
+        for (k_next, var_next) in terms {
+            // k var + k_next var_next - sum = 0
+            let sum = self.n_vars;
+            self.n_vars += 1;
+            if let Some(values) = self.values.as_mut() {
+                let mut value = values[var];
+                value.mul_assign(&k);
+                let mut next = values[var_next];
+                next.mul_assign(&k_next);
+                value.add_assign(&next);
+                values.push(value);
+            }
+            self.gates
+                .push(Gate::new(zero, k, k_next, minus_one, zero, [var, var_next, sum]));
+            k = one;
+            var = sum;
+        }
+        Term { k, var, constant }
+    }
+
+    /// value of the public inputs, in the order of their rows
+    pub fn public_inputs(&self) -> Vec<Fr> {
+        match &self.values {
+            Some(values) => self.gates[..self.n_public].iter().map(|g| values[g.wires[0]]).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// check that every gate holds with the witness values
+    pub fn check(&self) -> Result<()> {
+        let values = self
+            .values
+            .as_ref()
+            .ok_or_else(|| Error::Unexpected("gates without witness".to_string()))?;
+        for (row, gate) in self.gates.iter().enumerate().skip(self.n_public) {
+            let [a, b, c] = [values[gate.wires[0]], values[gate.wires[1]], values[gate.wires[2]]];
+            let mut sum = gate.q_c;
+            for (q, v) in &[(gate.q_l, a), (gate.q_r, b), (gate.q_o, c)] {
+                let mut term = *q;
+                term.mul_assign(v);
+                sum.add_assign(&term);
+            }
+            let mut ab = gate.q_m;
+            ab.mul_assign(&a);
+            ab.mul_assign(&b);
+            sum.add_assign(&ab);
+            if !sum.is_zero() {
+                return Err(Error::Mismatch(format!(
+                    "the witness does not satisfy the gate {} of the circuit",
+                    row
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// the public inputs of a proof as field elements
+pub(crate) fn public_frs(public_inputs: &[FS]) -> Vec<Fr> {
+    public_inputs.iter().map(fe_to_bellman_fr::<Bn256>).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use circom2_compiler::algebra::Value;
+    use circom2_compiler::evaluator::{Evaluator, Mode, Scope};
+    use circom2_compiler::storage::{Ram, StorageFactory};
+
+    #[test]
+    fn test_gates_from_r1cs() {
+        let circuit = "
+            template t() {
+                signal private input a;
+                signal private input b;
+                signal input k;
+                signal output c;
+                signal s;
+
+                s <== a + b + k;
+                c <== (a + 2) * (s - 1) + 3 * b;
+            }
+            component main = t();
+        ";
+        let eval = |mode: Mode| {
+            let witness = mode == Mode::GenWitness;
+            let mut ram = Ram::default();
+            let mut ev = Evaluator::new(mode, ram.new_signals().unwrap(), ram.new_constraints().unwrap());
+            if witness {
+                ev.set_deferred_value("main.a".to_string(), Value::from(3));
+                ev.set_deferred_value("main.b".to_string(), Value::from(4));
+                ev.set_deferred_value("main.k".to_string(), Value::from(5));
+            }
+            ev.eval_inline(&mut Scope::new(true, None, "root".to_string()), circuit)
+                .unwrap();
+            ev
+        };
+        let (ev_r1cs, ev_witness) = (eval(Mode::GenConstraints), eval(Mode::GenWitness));
+
+        let mut gates = Gates::new(&ev_witness.signals, &ev_r1cs.constraints, true).unwrap();
+        assert_eq!(2, gates.n_public);
+        assert!(gates.n_vars > ev_witness.signals.len().unwrap());
+        gates.check().unwrap();
+
+        // c = 5 * 11 + 12 = 67, k = 5
+        let mut public = gates.public_inputs();
+        public.sort_by_key(|v| format!("{}", v));
+        let mut expected = vec![fe_to_bellman_fr::<Bn256>(&FS::from(67)), fe_to_bellman_fr::<Bn256>(&FS::from(5))];
+        expected.sort_by_key(|v| format!("{}", v));
+        assert_eq!(expected, public);
+
+        let last = gates.values.as_ref().unwrap().len() - 1;
+        gates.values.as_mut().unwrap()[last].add_assign(&Fr::one());
+        assert!(gates.check().is_err());
+    }
+}
@@ -0,0 +1,291 @@
+use std::io::{Read, Write};
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use ff::{PrimeField, PrimeFieldRepr};
+use num_bigint::BigUint;
+use pairing::bn256::{Bn256, Fr, G1Affine, G2Affine};
+use pairing::{CurveAffine, EncodedPoint};
+
+use circom2_compiler::algebra::FS;
+use circom2_compiler::storage::{Constraints, RamConstraints, Signals};
+
+use crate::groth16::{
+    g1_from_str, g2_from_str, hex_to_dec, parse_g1, parse_g2, read_pk_circuit, skip_pk_circuit,
+    write_pk_circuit, CircuitInfo, Error, Result,
+};
+
+use super::prover::{cosets, Proof, ProvingKey, VerifyingKey};
+
+// proving key layout
+//
+//   header, constraints and circuit as in the groth16 proving key, with magic "c2pl"
+//   verifying key : json prefixed with its size : u32
+//   powers of tau : count u32, tau^i * G1 uncompressed
+//
+const PK_MAGIC: &[u8; 4] = b"c2pl";
+const PK_VERSION: u32 = 1;
+const PROTOCOL: &str = "plonk";
+
+/// verification key, with the names that snarkjs uses for plonk
+#[derive(Serialize, Deserialize)]
+struct JsonVerifyingKey {
+    protocol: String,
+    curve: String,
+    #[serde(rename = "nPublic")]
+    n_public: usize,
+    power: u32,
+    k1: String,
+    k2: String,
+    #[serde(rename = "Qm")]
+    q_m: [String; 3],
+    #[serde(rename = "Ql")]
+    q_l: [String; 3],
+    #[serde(rename = "Qr")]
+    q_r: [String; 3],
+    #[serde(rename = "Qo")]
+    q_o: [String; 3],
+    #[serde(rename = "Qc")]
+    q_c: [String; 3],
+    #[serde(rename = "S1")]
+    s_1: [String; 3],
+    #[serde(rename = "S2")]
+    s_2: [String; 3],
+    #[serde(rename = "S3")]
+    s_3: [String; 3],
+    #[serde(rename = "X_2")]
+    x_2: [[String; 2]; 3],
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonProof {
+    protocol: String,
+    #[serde(rename = "A")]
+    a: [String; 3],
+    #[serde(rename = "B")]
+    b: [String; 3],
+    #[serde(rename = "C")]
+    c: [String; 3],
+    #[serde(rename = "Z")]
+    z: [String; 3],
+    #[serde(rename = "T1")]
+    t_lo: [String; 3],
+    #[serde(rename = "T2")]
+    t_mid: [String; 3],
+    #[serde(rename = "T3")]
+    t_hi: [String; 3],
+    #[serde(rename = "Wxi")]
+    w_zeta: [String; 3],
+    #[serde(rename = "Wxiw")]
+    w_zeta_omega: [String; 3],
+    eval_a: String,
+    eval_b: String,
+    eval_c: String,
+    eval_s1: String,
+    eval_s2: String,
+    eval_zw: String,
+    eval_r: String,
+    public: Vec<String>,
+}
+
+pub fn write_pk<W: Write, S: Signals, C: Constraints>(
+    mut out: W,
+    signals: &S,
+    constraints: &C,
+    source_hash: Option<&str>,
+    pk: &ProvingKey,
+) -> Result<()> {
+    write_pk_circuit::<Bn256, _, _, _>(&mut out, PK_MAGIC, PK_VERSION, signals, constraints, source_hash)?;
+
+    let mut vk = Vec::new();
+    write_vk_json(&pk.vk, &mut vk)?;
+    out.write_u32::<BigEndian>(vk.len() as u32)?;
+    out.write_all(&vk)?;
+
+    out.write_u32::<BigEndian>(pk.powers.len() as u32)?;
+    for p in &pk.powers {
+        out.write_all(p.into_uncompressed().as_ref())?;
+    }
+    Ok(())
+}
+
+pub fn read_pk<R: Read>(mut input: R) -> Result<(RamConstraints, CircuitInfo, ProvingKey)> {
+    let (constraints, circuit) = read_pk_circuit::<Bn256, _>(&mut input, PK_MAGIC, PK_VERSION)?;
+    let vk = read_vk_section(&mut input)?;
+
+    let count = input.read_u32::<BigEndian>()? as usize;
+    if count != vk.n + 6 {
+        return Err(Error::BadFormat("proving key powers of tau are corrupted".to_string()));
+    }
+    let mut powers = Vec::with_capacity(count);
+    for _ in 0..count {
+        let mut encoded = <G1Affine as CurveAffine>::Uncompressed::empty();
+        input.read_exact(encoded.as_mut())?;
+        powers.push(
+            encoded
+                .into_affine()
+                .map_err(|err| Error::BadFormat(format!("bad G1 point: {}", err)))?,
+        );
+    }
+
+    Ok((constraints, circuit, ProvingKey { vk, powers }))
+}
+
+/// read only the verifying key stored in a proving key file
+pub fn read_pk_vk<R: Read>(mut input: R) -> Result<VerifyingKey> {
+    skip_pk_circuit::<Bn256, _>(&mut input, PK_MAGIC, PK_VERSION)?;
+    read_vk_section(&mut input)
+}
+
+fn read_vk_section<R: Read>(input: &mut R) -> Result<VerifyingKey> {
+    let len = input.read_u32::<BigEndian>()? as usize;
+    let mut vk = vec![0u8; len];
+    input.read_exact(&mut vk)?;
+    read_vk_json(&vk[..])
+}
+
+pub fn write_vk_json<W: Write>(vk: &VerifyingKey, out: W) -> Result<()> {
+    let (k1, k2) = cosets();
+    let json = JsonVerifyingKey {
+        protocol: PROTOCOL.to_string(),
+        curve: "bn128".to_string(),
+        n_public: vk.n_public,
+        power: vk.n.trailing_zeros(),
+        k1: fr_to_dec(&k1),
+        k2: fr_to_dec(&k2),
+        q_m: g1_json(&vk.q_m),
+        q_l: g1_json(&vk.q_l),
+        q_r: g1_json(&vk.q_r),
+        q_o: g1_json(&vk.q_o),
+        q_c: g1_json(&vk.q_c),
+        s_1: g1_json(&vk.s_1),
+        s_2: g1_json(&vk.s_2),
+        s_3: g1_json(&vk.s_3),
+        x_2: g2_json(&vk.x_2),
+    };
+    serde_json::to_writer_pretty(out, &json)?;
+    Ok(())
+}
+
+/// read a verification key written by `write_vk_json`, keys of other plonk
+///   implementations are refused since their proofs are not compatible
+pub fn read_vk_json<R: Read>(input: R) -> Result<VerifyingKey> {
+    let json: JsonVerifyingKey = serde_json::from_reader(input)?;
+    if json.protocol != PROTOCOL {
+        return Err(Error::BadFormat(format!("unsupported protocol {}", json.protocol)));
+    }
+    if json.curve.to_lowercase() != "bn128" {
+        return Err(Error::Mismatch(format!("the verification key is for the {} curve, not bn128", json.curve)));
+    }
+    let (k1, k2) = cosets();
+    if fr_from_dec(&json.k1)? != k1 || fr_from_dec(&json.k2)? != k2 {
+        return Err(Error::Mismatch(
+            "the verification key uses other permutation cosets, it was not created by circom2".to_string(),
+        ));
+    }
+    if json.power >= 64 {
+        return Err(Error::BadFormat(format!("bad verification key power {}", json.power)));
+    }
+    Ok(VerifyingKey {
+        n: 1 << json.power,
+        n_public: json.n_public,
+        q_m: g1_from_str(&json.q_m)?,
+        q_l: g1_from_str(&json.q_l)?,
+        q_r: g1_from_str(&json.q_r)?,
+        q_o: g1_from_str(&json.q_o)?,
+        q_c: g1_from_str(&json.q_c)?,
+        s_1: g1_from_str(&json.s_1)?,
+        s_2: g1_from_str(&json.s_2)?,
+        s_3: g1_from_str(&json.s_3)?,
+        x_2: g2_from_str(&json.x_2)?,
+    })
+}
+
+pub fn write_proof<W: Write>(public_input: &[(String, FS)], proof: &Proof, out: &mut W) -> Result<()> {
+    let json = JsonProof {
+        protocol: PROTOCOL.to_string(),
+        a: g1_json(&proof.a),
+        b: g1_json(&proof.b),
+        c: g1_json(&proof.c),
+        z: g1_json(&proof.z),
+        t_lo: g1_json(&proof.t_lo),
+        t_mid: g1_json(&proof.t_mid),
+        t_hi: g1_json(&proof.t_hi),
+        w_zeta: g1_json(&proof.w_zeta),
+        w_zeta_omega: g1_json(&proof.w_zeta_omega),
+        eval_a: fr_to_dec(&proof.eval_a),
+        eval_b: fr_to_dec(&proof.eval_b),
+        eval_c: fr_to_dec(&proof.eval_c),
+        eval_s1: fr_to_dec(&proof.eval_s1),
+        eval_s2: fr_to_dec(&proof.eval_s2),
+        eval_zw: fr_to_dec(&proof.eval_zw),
+        eval_r: fr_to_dec(&proof.eval_r),
+        public: public_input.iter().map(|(_, v)| v.0.to_string()).collect(),
+    };
+    serde_json::to_writer_pretty(out, &json)?;
+    Ok(())
+}
+
+/// read a proof written by `write_proof`, returns the public inputs and the proof
+pub fn read_proof<R: Read>(input: R) -> Result<(Vec<FS>, Proof)> {
+    let json: JsonProof = serde_json::from_reader(input)?;
+    if json.protocol != PROTOCOL {
+        return Err(Error::BadFormat(format!("unsupported protocol {}", json.protocol)));
+    }
+    let proof = Proof {
+        a: g1_from_str(&json.a)?,
+        b: g1_from_str(&json.b)?,
+        c: g1_from_str(&json.c)?,
+        z: g1_from_str(&json.z)?,
+        t_lo: g1_from_str(&json.t_lo)?,
+        t_mid: g1_from_str(&json.t_mid)?,
+        t_hi: g1_from_str(&json.t_hi)?,
+        w_zeta: g1_from_str(&json.w_zeta)?,
+        w_zeta_omega: g1_from_str(&json.w_zeta_omega)?,
+        eval_a: fr_from_dec(&json.eval_a)?,
+        eval_b: fr_from_dec(&json.eval_b)?,
+        eval_c: fr_from_dec(&json.eval_c)?,
+        eval_s1: fr_from_dec(&json.eval_s1)?,
+        eval_s2: fr_from_dec(&json.eval_s2)?,
+        eval_zw: fr_from_dec(&json.eval_zw)?,
+        eval_r: fr_from_dec(&json.eval_r)?,
+    };
+    let public_input = json
+        .public
+        .iter()
+        .map(|v| FS::parse(v))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok((public_input, proof))
+}
+
+fn g1_json(p: &G1Affine) -> [String; 3] {
+    let (x, y) = parse_g1(p);
+    [hex_to_dec(&x), hex_to_dec(&y), "1".to_string()]
+}
+
+fn g2_json(p: &G2Affine) -> [[String; 2]; 3] {
+    let (x1, x0, y1, y0) = parse_g2(p);
+    [
+        [hex_to_dec(&x0), hex_to_dec(&x1)],
+        [hex_to_dec(&y0), hex_to_dec(&y1)],
+        ["1".to_string(), "0".to_string()],
+    ]
+}
+
+fn fr_to_dec(fr: &Fr) -> String {
+    let mut be = Vec::new();
+    fr.into_repr().write_be(&mut be).expect("cannot write to memory");
+    BigUint::from_bytes_be(&be).to_str_radix(10)
+}
+
+/// fails for values that are not in the scalar field
+fn fr_from_dec(dec: &str) -> Result<Fr> {
+    let bad = || Error::BadFormat(format!("{} is not in the scalar field", dec));
+    let value = BigUint::parse_bytes(dec.as_bytes(), 10).ok_or_else(bad)?;
+    let mut modulus = Vec::new();
+    Fr::char().write_be(&mut modulus).expect("cannot write to memory");
+    if value >= BigUint::from_bytes_be(&modulus) {
+        return Err(bad());
+    }
+    Fr::from_str(&value.to_str_radix(10)).ok_or_else(bad)
+}
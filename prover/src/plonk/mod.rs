@@ -0,0 +1,10 @@
+mod circuit;
+mod format;
+mod poly;
+mod prover;
+
+pub use format::{read_pk_vk, read_proof, read_vk_json, write_proof, write_vk_json};
+pub use prover::{
+    circuit_size, generate_verified_proof, prove_witness, read_vk_file, setup, verify, verify_files, Proof,
+    ProvingKey, VerifyingKey,
+};
@@ -0,0 +1,263 @@
+use ff::{Field, PrimeField};
+use pairing::bn256::{Fr, G1Affine};
+use pairing::CurveProjective;
+
+use crate::group::{fft, multiexp};
+use crate::groth16::{Error, Result};
+
+/// multiplicative subgroup of 2^log_n roots of unity
+pub(crate) struct Domain {
+    pub n: usize,
+    pub log_n: u32,
+    pub omega: Fr,
+    omega_inv: Fr,
+    n_inv: Fr,
+}
+
+impl Domain {
+    /// smallest domain with at least `size` elements
+    pub fn new(size: usize) -> Result<Self> {
+        let (mut n, mut log_n) = (1, 0);
+        while n < size {
+            n *= 2;
+            log_n += 1;
+        }
+        Self::with_log(log_n)
+    }
+
+    pub fn with_log(log_n: u32) -> Result<Self> {
+        if log_n > Fr::S {
+            return Err(Error::BadFormat(format!(
+                "circuit too large, it needs 2^{} gates but the field supports 2^{}",
+                log_n,
+                Fr::S
+            )));
+        }
+        let mut omega = Fr::root_of_unity();
+        for _ in log_n..Fr::S {
+            omega.square();
+        }
+        let n = 1usize << log_n;
+        Ok(Domain {
+            n,
+            log_n,
+            omega,
+            omega_inv: omega.inverse().expect("roots of unity are not zero"),
+            n_inv: fr(n as u64).inverse().expect("domain size is not zero"),
+        })
+    }
+
+    /// evaluations at the domain of a polynomial with at most `n` coefficients
+    pub fn fft(&self, coeffs: &[Fr]) -> Vec<Fr> {
+        let mut values = coeffs.to_vec();
+        values.resize(self.n, Fr::zero());
+        fft(&mut values, &self.omega, self.log_n);
+        values
+    }
+
+    /// coefficients of the polynomial with `values` at the domain
+    pub fn ifft(&self, values: &[Fr]) -> Vec<Fr> {
+        let mut coeffs = values.to_vec();
+        coeffs.resize(self.n, Fr::zero());
+        fft(&mut coeffs, &self.omega_inv, self.log_n);
+        for c in coeffs.iter_mut() {
+            c.mul_assign(&self.n_inv);
+        }
+        coeffs
+    }
+
+    /// evaluations at `g` times the domain, `g` the multiplicative generator
+    pub fn coset_fft(&self, coeffs: &[Fr]) -> Vec<Fr> {
+        let mut shifted = coeffs.to_vec();
+        scale(&mut shifted, &Fr::multiplicative_generator());
+        self.fft(&shifted)
+    }
+
+    /// coefficients of the polynomial with `values` at `g` times the domain
+    pub fn coset_ifft(&self, values: &[Fr]) -> Vec<Fr> {
+        let mut coeffs = self.ifft(values);
+        let g_inv = Fr::multiplicative_generator().inverse().expect("generator is not zero");
+        scale(&mut coeffs, &g_inv);
+        coeffs
+    }
+
+    /// omega^i for each i of the domain
+    pub fn elements(&self) -> Vec<Fr> {
+        powers(&self.omega, self.n)
+    }
+
+    /// Z_H(z) = z^n - 1
+    pub fn vanishing(&self, z: &Fr) -> Fr {
+        let mut zh = z.pow(&[self.n as u64]);
+        zh.sub_assign(&Fr::one());
+        zh
+    }
+
+    /// L_i(z) for the first `count` lagrange polynomials of the domain,
+    ///   fails if `z` is in the domain
+    pub fn lagrange(&self, z: &Fr, count: usize) -> Result<Vec<Fr>> {
+        // L_i(z) = omega^i (z^n - 1) / (n (z - omega^i))
+        let zh = self.vanishing(z);
+        let mut omega_i = Fr::one();
+        let mut result = Vec::with_capacity(count);
+        for _ in 0..count {
+            let mut den = *z;
+            den.sub_assign(&omega_i);
+            den.mul_assign(&fr(self.n as u64));
+            let mut l = den
+                .inverse()
+                .ok_or_else(|| Error::Unexpected("evaluation point in the domain".to_string()))?;
+            l.mul_assign(&omega_i);
+            l.mul_assign(&zh);
+            result.push(l);
+            omega_i.mul_assign(&self.omega);
+        }
+        Ok(result)
+    }
+}
+
+pub(crate) fn fr(v: u64) -> Fr {
+    Fr::from_repr(<Fr as PrimeField>::Repr::from(v)).expect("small values are in the field")
+}
+
+/// 1, x, x^2, ..., x^(n-1)
+pub(crate) fn powers(x: &Fr, n: usize) -> Vec<Fr> {
+    let mut current = Fr::one();
+    (0..n)
+        .map(|_| {
+            let power = current;
+            current.mul_assign(x);
+            power
+        })
+        .collect()
+}
+
+/// multiply the i-th coefficient by x^i, that is p(x X)
+pub(crate) fn scale(coeffs: &mut [Fr], x: &Fr) {
+    let mut power = Fr::one();
+    for c in coeffs.iter_mut() {
+        c.mul_assign(&power);
+        power.mul_assign(x);
+    }
+}
+
+pub(crate) fn evaluate(coeffs: &[Fr], z: &Fr) -> Fr {
+    let mut result = Fr::zero();
+    for c in coeffs.iter().rev() {
+        result.mul_assign(z);
+        result.add_assign(c);
+    }
+    result
+}
+
+/// p += k q
+pub(crate) fn add_scaled(p: &mut Vec<Fr>, k: &Fr, q: &[Fr]) {
+    if p.len() < q.len() {
+        p.resize(q.len(), Fr::zero());
+    }
+    for (p, q) in p.iter_mut().zip(q.iter()) {
+        let mut term = *q;
+        term.mul_assign(k);
+        p.add_assign(&term);
+    }
+}
+
+/// (p(X) - p(z)) / (X - z)
+pub(crate) fn divide_by_linear(coeffs: &[Fr], z: &Fr) -> Vec<Fr> {
+    let mut quotient = vec![Fr::zero(); coeffs.len().saturating_sub(1)];
+    let mut carry = Fr::zero();
+    for i in (1..coeffs.len()).rev() {
+        carry.mul_assign(z);
+        carry.add_assign(&coeffs[i]);
+        quotient[i - 1] = carry;
+    }
+    quotient
+}
+
+/// p(X) + (sum b_i X^i) Z_H(X), the same evaluations at the domain
+pub(crate) fn blind(coeffs: &mut Vec<Fr>, n: usize, blinding: &[Fr]) {
+    if coeffs.len() < n + blinding.len() {
+        coeffs.resize(n + blinding.len(), Fr::zero());
+    }
+    for (i, b) in blinding.iter().enumerate() {
+        coeffs[n + i].add_assign(b);
+        coeffs[i].sub_assign(b);
+    }
+}
+
+/// inverse of each value, all of them have to be non zero
+pub(crate) fn batch_inverse(values: &mut [Fr]) -> Result<()> {
+    let mut acc = Fr::one();
+    let prefix = values
+        .iter()
+        .map(|v| {
+            let current = acc;
+            acc.mul_assign(v);
+            current
+        })
+        .collect::<Vec<_>>();
+    let mut inv = acc
+        .inverse()
+        .ok_or_else(|| Error::Unexpected("cannot invert zero".to_string()))?;
+    for (v, prefix) in values.iter_mut().zip(prefix.iter()).rev() {
+        let mut result = inv;
+        result.mul_assign(prefix);
+        inv.mul_assign(v);
+        *v = result;
+    }
+    Ok(())
+}
+
+/// commitment of a polynomial to the powers of tau, sum c_i [tau^i]
+pub(crate) fn commit(srs: &[G1Affine], coeffs: &[Fr]) -> Result<G1Affine> {
+    if coeffs.len() > srs.len() {
+        return Err(Error::BadFormat(format!(
+            "polynomial of degree {} but the proving key has {} powers of tau",
+            coeffs.len() - 1,
+            srs.len()
+        )));
+    }
+    Ok(multiexp(&srs[..coeffs.len()], coeffs).into_affine())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::{Rand, SeedableRng, XorShiftRng};
+
+    #[test]
+    fn test_fft_roundtrip_and_division() {
+        let rng = &mut XorShiftRng::from_seed([1, 2, 3, 4]);
+        let domain = Domain::new(5).unwrap();
+        assert_eq!(8, domain.n);
+
+        let coeffs = (0..8).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+        let values = domain.fft(&coeffs);
+        for (x, v) in domain.elements().iter().zip(values.iter()) {
+            assert_eq!(evaluate(&coeffs, x), *v);
+        }
+        assert_eq!(coeffs, domain.ifft(&values));
+        assert_eq!(coeffs, domain.coset_ifft(&domain.coset_fft(&coeffs)));
+
+        let z = Fr::rand(rng);
+        let quotient = divide_by_linear(&coeffs, &z);
+        let x = Fr::rand(rng);
+        let (mut lhs, mut rhs) = (evaluate(&quotient, &x), evaluate(&coeffs, &x));
+        let mut x_minus_z = x;
+        x_minus_z.sub_assign(&z);
+        lhs.mul_assign(&x_minus_z);
+        rhs.sub_assign(&evaluate(&coeffs, &z));
+        assert_eq!(lhs, rhs);
+
+        let mut blinded = coeffs.clone();
+        blind(&mut blinded, domain.n, &[Fr::rand(rng), Fr::rand(rng)]);
+        for (x, v) in domain.elements().iter().zip(values.iter()) {
+            assert_eq!(evaluate(&blinded, x), *v);
+        }
+
+        let lagrange = domain.lagrange(&x, 2).unwrap();
+        let mut l1 = vec![Fr::zero(); 8];
+        l1[1] = Fr::one();
+        assert_eq!(evaluate(&domain.ifft(&l1), &x), lagrange[1]);
+    }
+}
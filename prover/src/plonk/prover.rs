@@ -0,0 +1,867 @@
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
+
+use blake2_rfc::blake2b::Blake2b;
+use ff::{Field, PrimeField, PrimeFieldRepr};
+use pairing::bn256::{Bn256, Fr, FrRepr, G1Affine, G2Affine};
+use pairing::{CurveAffine, CurveProjective, Engine};
+use rand::{Rand, Rng};
+
+use circom2_compiler::algebra::{Witness, FS, SIGNAL_ONE};
+use circom2_compiler::progress::{Phase, Progress};
+use circom2_compiler::storage::{is_public_input, Constraints, RamConstraints, Signals};
+
+use crate::group::multiexp;
+use crate::groth16::{check_circuit, check_field, Curve, Error, Phase1, Result};
+
+use super::circuit::{public_frs, Gate, Gates};
+use super::format;
+use super::poly::{self, Domain};
+
+/// the smallest domain, so the 2n - 1 powers of a phase-1 of size n cover
+///   the n + 6 coefficients of the blinded polynomials
+const MIN_DOMAIN: usize = 8;
+
+/// commitments to the circuit polynomials, the same for every proof
+#[derive(Clone, Debug, PartialEq)]
+pub struct VerifyingKey {
+    /// number of rows of the circuit, a power of two
+    pub n: usize,
+    pub n_public: usize,
+    pub q_m: G1Affine,
+    pub q_l: G1Affine,
+    pub q_r: G1Affine,
+    pub q_o: G1Affine,
+    pub q_c: G1Affine,
+    pub s_1: G1Affine,
+    pub s_2: G1Affine,
+    pub s_3: G1Affine,
+    /// tau * G2
+    pub x_2: G2Affine,
+}
+
+/// the verifying key and the powers of tau used to commit, the circuit
+///   polynomials are computed again from the constraints
+pub struct ProvingKey {
+    pub vk: VerifyingKey,
+    pub powers: Vec<G1Affine>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Proof {
+    pub a: G1Affine,
+    pub b: G1Affine,
+    pub c: G1Affine,
+    pub z: G1Affine,
+    pub t_lo: G1Affine,
+    pub t_mid: G1Affine,
+    pub t_hi: G1Affine,
+    pub w_zeta: G1Affine,
+    pub w_zeta_omega: G1Affine,
+    pub eval_a: Fr,
+    pub eval_b: Fr,
+    pub eval_c: Fr,
+    pub eval_s1: Fr,
+    pub eval_s2: Fr,
+    pub eval_zw: Fr,
+    pub eval_r: Fr,
+}
+
+/// the b and c wires are labelled with k1 omega^i and k2 omega^i, in
+///   cosets of the domain different from it and from each other
+pub(crate) fn cosets() -> (Fr, Fr) {
+    let k1 = Fr::multiplicative_generator();
+    let mut k2 = k1;
+    k2.square();
+    (k1, k2)
+}
+
+/// selectors and permutation of the gates, as polynomials on their domain
+struct Preprocessed {
+    domain: Domain,
+    /// q_m, q_l, q_r, q_o, q_c
+    selectors: [Vec<Fr>; 5],
+    /// s_1, s_2, s_3
+    sigmas: [Vec<Fr>; 3],
+}
+
+fn preprocess(gates: &Gates) -> Result<Preprocessed> {
+    let domain = Domain::new(std::cmp::max(gates.gates.len(), MIN_DOMAIN))?;
+    let n = domain.n;
+
+    let column = |f: &dyn Fn(usize) -> Fr| (0..n).map(f).collect::<Vec<_>>();
+    let selector = |f: &dyn Fn(&Gate) -> Fr| {
+        domain.ifft(&column(&|i| gates.gates.get(i).map_or(Fr::zero(), f)))
+    };
+    let selectors = [
+        selector(&|g| g.q_m),
+        selector(&|g| g.q_l),
+        selector(&|g| g.q_r),
+        selector(&|g| g.q_o),
+        selector(&|g| g.q_c),
+    ];
+
+    // each wire points to the next wire of the same variable, padding rows are ONE
+    let wire = |position: usize| {
+        gates
+            .gates
+            .get(position % n)
+            .map_or(SIGNAL_ONE, |g| g.wires[position / n])
+    };
+    let mut sigma = (0..3 * n).collect::<Vec<_>>();
+    let (mut first, mut last) = (vec![None; gates.n_vars], vec![None; gates.n_vars]);
+    for position in 0..3 * n {
+        let var = wire(position);
+        match last[var] {
+            Some(previous) => sigma[previous] = position,
+            None => first[var] = Some(position),
+        }
+        last[var] = Some(position);
+    }
+    for (first, last) in first.iter().zip(last.iter()) {
+        if let (Some(first), Some(last)) = (first, last) {
+            sigma[*last] = *first;
+        }
+    }
+
+    let (k1, k2) = cosets();
+    let elements = domain.elements();
+    let label = |position: usize| {
+        let mut label = elements[position % n];
+        match position / n {
+            1 => label.mul_assign(&k1),
+            2 => label.mul_assign(&k2),
+            _ => {}
+        }
+        label
+    };
+    let sigmas = [
+        domain.ifft(&column(&|i| label(sigma[i]))),
+        domain.ifft(&column(&|i| label(sigma[n + i]))),
+        domain.ifft(&column(&|i| label(sigma[2 * n + i]))),
+    ];
+
+    Ok(Preprocessed { domain, selectors, sigmas })
+}
+
+/// powers of tau needed by the circuit, to read them with `Phase1::open`
+pub fn circuit_size<S: Signals, C: Constraints>(signals: &S, constraints: &C) -> Result<usize> {
+    let gates = Gates::new(signals, constraints, false)?;
+    Ok(Domain::new(std::cmp::max(gates.gates.len(), MIN_DOMAIN))?.n)
+}
+
+/// generate the keys of the circuit from a universal powers of tau, the same
+///   powers work for any circuit of up to `phase1.size()` rows
+pub fn setup<S: Signals, C: Constraints, WP: Write, WJ: Write>(
+    phase1: &Phase1,
+    signals: &S,
+    constraints: &C,
+    source_hash: Option<&str>,
+    out_pk: WP,
+    out_vk_json: WJ,
+    progress: &mut dyn Progress,
+) -> Result<()> {
+    check_field::<Bn256, _>(signals)?;
+    let gates = Gates::new(signals, constraints, false)?;
+    let rows = gates.gates.len();
+    progress.start(Phase::Setup, Some(rows));
+    let circuit = preprocess(&gates)?;
+    let n = circuit.domain.n;
+    if phase1.size() < n || phase1.tau_g1.len() < n + 6 {
+        return Err(Error::BadFormat(format!(
+            "powers of tau has {} powers, circuit needs {}",
+            phase1.size(),
+            n
+        )));
+    }
+
+    let powers = phase1.tau_g1[..n + 6].to_vec();
+    let commit = |coeffs: &[Fr]| poly::commit(&powers, coeffs);
+    let [q_m, q_l, q_r, q_o, q_c] = &circuit.selectors;
+    let [s_1, s_2, s_3] = &circuit.sigmas;
+    let vk = VerifyingKey {
+        n,
+        n_public: gates.n_public,
+        q_m: commit(q_m)?,
+        q_l: commit(q_l)?,
+        q_r: commit(q_r)?,
+        q_o: commit(q_o)?,
+        q_c: commit(q_c)?,
+        s_1: commit(s_1)?,
+        s_2: commit(s_2)?,
+        s_3: commit(s_3)?,
+        x_2: phase1.tau_g2[1],
+    };
+
+    progress.finish(Phase::Setup, rows);
+
+    progress.start(Phase::WriteKeys, Some(rows));
+    let pk = ProvingKey { vk, powers };
+    format::write_pk(out_pk, signals, constraints, source_hash, &pk)?;
+    format::write_vk_json(&pk.vk, out_vk_json)?;
+    progress.finish(Phase::WriteKeys, rows);
+    Ok(())
+}
+
+/// prove the witness in `signals`, `source_hash` is the `circuit_hash` of the evaluator
+///   that computed it and must match the one of the proving key
+pub fn generate_verified_proof<S: Signals, R: Read, W: Write, G: Rng>(
+    signals: S,
+    source_hash: Option<&str>,
+    in_pk: R,
+    out_proof: &mut W,
+    rng: &mut G,
+    progress: &mut dyn Progress,
+) -> Result<Vec<(String, FS)>> {
+    progress.start(Phase::LoadKey, None);
+    let (constraints, circuit, pk) = format::read_pk(in_pk)?;
+    progress.finish(Phase::LoadKey, constraints.len()?);
+    check_circuit(&signals, source_hash, None::<(&RamConstraints, _)>, &circuit)?;
+    prove(&signals, &constraints, &pk, out_proof, rng, progress)
+}
+
+/// create a proof from a witness ordered by wire, as the one written by `circom2 witness`
+pub fn prove_witness<R: Read, W: Write, G: Rng>(
+    witness: &Witness,
+    in_pk: R,
+    out_proof: &mut W,
+    rng: &mut G,
+    progress: &mut dyn Progress,
+) -> Result<Vec<(String, FS)>> {
+    progress.start(Phase::LoadKey, None);
+    let (constraints, circuit, pk) = format::read_pk(in_pk)?;
+    progress.finish(Phase::LoadKey, constraints.len()?);
+    let signals = circuit.signals(Bn256::FIELD, witness)?;
+    prove(&signals, &constraints, &pk, out_proof, rng, progress)
+}
+
+pub(crate) fn prove<S: Signals, C: Constraints, W: Write, G: Rng>(
+    signals: &S,
+    constraints: &C,
+    pk: &ProvingKey,
+    out_proof: &mut W,
+    rng: &mut G,
+    progress: &mut dyn Progress,
+) -> Result<Vec<(String, FS)>> {
+    check_field::<Bn256, _>(signals)?;
+
+    let gates = Gates::new(signals, constraints, true)?;
+    gates.check()?;
+    let rows = gates.gates.len();
+    progress.start(Phase::Prove, Some(rows));
+    let proof = create_proof(&gates, pk, rng)?;
+    progress.finish(Phase::Prove, rows);
+
+    let mut public_inputs = Vec::new();
+    for signal in signals.iter() {
+        let signal = signal?;
+        if is_public_input(&signal) {
+            let fs = signal.value.clone().and_then(|v| v.try_into_fs()).ok_or_else(|| {
+                Error::Mismatch(format!("signal {} has no value", signal.full_name.0))
+            })?;
+            public_inputs.push((signal.full_name.0.to_string(), fs));
+        }
+    }
+
+    // self-verify
+    progress.start(Phase::Verify, None);
+    let inputs = public_inputs.iter().map(|(_, fs)| fs.clone()).collect::<Vec<_>>();
+    if !verify(&pk.vk, &proof, &inputs)? {
+        return Err(Error::Unexpected("the created proof is not valid".to_string()));
+    }
+    progress.finish(Phase::Verify, 1);
+    format::write_proof(&public_inputs, &proof, out_proof)?;
+
+    Ok(public_inputs)
+}
+
+fn create_proof<G: Rng>(gates: &Gates, pk: &ProvingKey, rng: &mut G) -> Result<Proof> {
+    let circuit = preprocess(gates)?;
+    let (domain, vk) = (&circuit.domain, &pk.vk);
+    let n = domain.n;
+    if n != vk.n || gates.n_public != vk.n_public {
+        return Err(Error::Mismatch(format!(
+            "the proving key is for a circuit with {} rows and {} public inputs, but this one has {} and {}",
+            vk.n, vk.n_public, n, gates.n_public
+        )));
+    }
+    let values = gates
+        .values
+        .as_ref()
+        .ok_or_else(|| Error::Unexpected("gates without witness".to_string()))?;
+    let commit = |coeffs: &[Fr]| poly::commit(&pk.powers, coeffs);
+    let (k1, k2) = cosets();
+    let [q_m, q_l, q_r, q_o, q_c] = &circuit.selectors;
+    let [s_1, s_2, s_3] = &circuit.sigmas;
+
+    let public = gates.public_inputs();
+    let mut transcript = Transcript::new(vk, &public);
+
+    // round 1, wire polynomials
+    let wires = (0..3)
+        .map(|j| {
+            (0..n)
+                .map(|i| gates.gates.get(i).map_or(Fr::one(), |g| values[g.wires[j]]))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let mut blinded = |values: &[Fr], count: usize| {
+        let mut coeffs = domain.ifft(values);
+        let blinding = (0..count).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+        poly::blind(&mut coeffs, n, &blinding);
+        coeffs
+    };
+    let a = blinded(&wires[0], 2);
+    let b = blinded(&wires[1], 2);
+    let c = blinded(&wires[2], 2);
+    let (a_commit, b_commit, c_commit) = (commit(&a)?, commit(&b)?, commit(&c)?);
+    transcript.point(&a_commit);
+    transcript.point(&b_commit);
+    transcript.point(&c_commit);
+    let beta = transcript.challenge();
+    let gamma = transcript.challenge();
+
+    // round 2, permutation accumulator
+    let sigma_values = [domain.fft(s_1), domain.fft(s_2), domain.fft(s_3)];
+    let elements = domain.elements();
+    let (mut num, mut den) = (vec![Fr::one(); n], vec![Fr::one(); n]);
+    for i in 0..n {
+        for (j, k) in [Fr::one(), k1, k2].iter().enumerate() {
+            let mut id = elements[i];
+            id.mul_assign(k);
+            num[i].mul_assign(&permutation_term(&wires[j][i], &beta, &id, &gamma));
+            den[i].mul_assign(&permutation_term(&wires[j][i], &beta, &sigma_values[j][i], &gamma));
+        }
+    }
+    poly::batch_inverse(&mut den)?;
+    let mut z_values = vec![Fr::one(); n];
+    for i in 0..n - 1 {
+        let mut next = z_values[i];
+        next.mul_assign(&num[i]);
+        next.mul_assign(&den[i]);
+        z_values[i + 1] = next;
+    }
+    let z = blinded(&z_values, 3);
+    let z_commit = commit(&z)?;
+    transcript.point(&z_commit);
+    let alpha = transcript.challenge();
+
+    // round 3, quotient evaluated on a coset of a domain 8 times larger
+    let ext = Domain::with_log(domain.log_n + 3)?;
+    let mut z_omega = z.clone();
+    poly::scale(&mut z_omega, &domain.omega);
+    let mut pi = vec![Fr::zero(); n];
+    for (pi, x) in pi.iter_mut().zip(public.iter()) {
+        *pi = *x;
+        pi.negate();
+    }
+    let mut l1 = vec![Fr::zero(); n];
+    l1[0] = Fr::one();
+    let coset = |coeffs: &[Fr]| ext.coset_fft(coeffs);
+    let (ea, eb, ec, ez, ezw) = (coset(&a), coset(&b), coset(&c), coset(&z), coset(&z_omega));
+    let (eqm, eql, eqr, eqo, eqc) = (coset(q_m), coset(q_l), coset(q_r), coset(q_o), coset(q_c));
+    let (es1, es2, es3) = (coset(s_1), coset(s_2), coset(s_3));
+    let epi = coset(&domain.ifft(&pi));
+    let el1 = coset(&domain.ifft(&l1));
+
+    // Z_H(g w^j) = g^n w^(j n) - 1 only takes 8 values
+    let g = Fr::multiplicative_generator();
+    let mut zh_inv = poly::powers(&ext.omega.pow(&[n as u64]), 8);
+    for zh in zh_inv.iter_mut() {
+        zh.mul_assign(&g.pow(&[n as u64]));
+        zh.sub_assign(&Fr::one());
+    }
+    poly::batch_inverse(&mut zh_inv)?;
+
+    let mut alpha2 = alpha;
+    alpha2.square();
+    let mut x = g;
+    let mut t_values = Vec::with_capacity(ext.n);
+    for j in 0..ext.n {
+        let mul = |x: &Fr, y: &Fr| {
+            let mut r = *x;
+            r.mul_assign(y);
+            r
+        };
+        let mut gate = mul(&mul(&eqm[j], &ea[j]), &eb[j]);
+        gate.add_assign(&mul(&eql[j], &ea[j]));
+        gate.add_assign(&mul(&eqr[j], &eb[j]));
+        gate.add_assign(&mul(&eqo[j], &ec[j]));
+        gate.add_assign(&eqc[j]);
+        gate.add_assign(&epi[j]);
+
+        let mut perm = ez[j];
+        perm.mul_assign(&permutation_term(&ea[j], &beta, &x, &gamma));
+        perm.mul_assign(&permutation_term(&eb[j], &beta, &mul(&k1, &x), &gamma));
+        perm.mul_assign(&permutation_term(&ec[j], &beta, &mul(&k2, &x), &gamma));
+        let mut copy = ezw[j];
+        copy.mul_assign(&permutation_term(&ea[j], &beta, &es1[j], &gamma));
+        copy.mul_assign(&permutation_term(&eb[j], &beta, &es2[j], &gamma));
+        copy.mul_assign(&permutation_term(&ec[j], &beta, &es3[j], &gamma));
+        perm.sub_assign(&copy);
+        perm.mul_assign(&alpha);
+
+        let mut first = ez[j];
+        first.sub_assign(&Fr::one());
+        first.mul_assign(&el1[j]);
+        first.mul_assign(&alpha2);
+
+        gate.add_assign(&perm);
+        gate.add_assign(&first);
+        gate.mul_assign(&zh_inv[j % 8]);
+        t_values.push(gate);
+        x.mul_assign(&ext.omega);
+    }
+    let t = ext.coset_ifft(&t_values);
+    if t[3 * n + 6..].iter().any(|c| !c.is_zero()) {
+        return Err(Error::Unexpected("the quotient polynomial has a wrong degree".to_string()));
+    }
+    // t_lo + b X^n, t_mid - b + b' X^n and t_hi - b', so the commitments of
+    //   the chunks are hiding and t = t_lo + X^n t_mid + X^2n t_hi still holds
+    let (b_lo, b_mid) = (Fr::rand(rng), Fr::rand(rng));
+    let mut t_lo = t[..n].to_vec();
+    t_lo.push(b_lo);
+    let mut t_mid = t[n..2 * n].to_vec();
+    t_mid[0].sub_assign(&b_lo);
+    t_mid.push(b_mid);
+    let mut t_hi = t[2 * n..3 * n + 6].to_vec();
+    t_hi[0].sub_assign(&b_mid);
+    let (t_lo_commit, t_mid_commit, t_hi_commit) = (commit(&t_lo)?, commit(&t_mid)?, commit(&t_hi)?);
+    transcript.point(&t_lo_commit);
+    transcript.point(&t_mid_commit);
+    transcript.point(&t_hi_commit);
+    let zeta = transcript.challenge();
+
+    // round 4, evaluations at zeta
+    let mut zeta_omega = zeta;
+    zeta_omega.mul_assign(&domain.omega);
+    let (eval_a, eval_b, eval_c) = (poly::evaluate(&a, &zeta), poly::evaluate(&b, &zeta), poly::evaluate(&c, &zeta));
+    let (eval_s1, eval_s2) = (poly::evaluate(s_1, &zeta), poly::evaluate(s_2, &zeta));
+    let eval_zw = poly::evaluate(&z, &zeta_omega);
+    for eval in &[eval_a, eval_b, eval_c, eval_s1, eval_s2, eval_zw] {
+        transcript.scalar(eval);
+    }
+
+    let l1_zeta = domain.lagrange(&zeta, 1)?[0];
+    let k = Linearization::new(&Evaluations {
+        a: eval_a,
+        b: eval_b,
+        c: eval_c,
+        s1: eval_s1,
+        s2: eval_s2,
+        zw: eval_zw,
+    }, &Challenges { beta, gamma, alpha, zeta }, &l1_zeta);
+    let mut r = Vec::new();
+    for (scalar, coeffs) in k.scalars.iter().zip([q_m, q_l, q_r, q_o, q_c, &z, s_3].iter()) {
+        poly::add_scaled(&mut r, scalar, coeffs);
+    }
+    let eval_r = poly::evaluate(&r, &zeta);
+    transcript.scalar(&eval_r);
+    let v = transcript.challenge();
+
+    // round 5, opening proofs at zeta and zeta omega
+    let zeta_n = zeta.pow(&[n as u64]);
+    let mut zeta_2n = zeta_n;
+    zeta_2n.square();
+    let mut opened = t_lo;
+    poly::add_scaled(&mut opened, &zeta_n, &t_mid);
+    poly::add_scaled(&mut opened, &zeta_2n, &t_hi);
+    let v_powers = poly::powers(&v, 7);
+    for (vi, coeffs) in v_powers[1..].iter().zip([&r, &a, &b, &c, s_1, s_2].iter()) {
+        poly::add_scaled(&mut opened, vi, coeffs);
+    }
+    let w_zeta = commit(&poly::divide_by_linear(&opened, &zeta))?;
+    let w_zeta_omega = commit(&poly::divide_by_linear(&z, &zeta_omega))?;
+
+    Ok(Proof {
+        a: a_commit,
+        b: b_commit,
+        c: c_commit,
+        z: z_commit,
+        t_lo: t_lo_commit,
+        t_mid: t_mid_commit,
+        t_hi: t_hi_commit,
+        w_zeta,
+        w_zeta_omega,
+        eval_a,
+        eval_b,
+        eval_c,
+        eval_s1,
+        eval_s2,
+        eval_zw,
+        eval_r,
+    })
+}
+
+/// verify a proof against the verifying key and the public inputs
+pub fn verify(vk: &VerifyingKey, proof: &Proof, public_inputs: &[FS]) -> Result<bool> {
+    if public_inputs.len() != vk.n_public {
+        return Err(Error::BadFormat(format!(
+            "verifying key expects {} public inputs, got {}",
+            vk.n_public,
+            public_inputs.len()
+        )));
+    }
+    let domain = Domain::new(vk.n)?;
+    if domain.n != vk.n || vk.n < MIN_DOMAIN {
+        return Err(Error::BadFormat(format!("bad verifying key domain size {}", vk.n)));
+    }
+    let public = public_frs(public_inputs);
+
+    let mut transcript = Transcript::new(vk, &public);
+    transcript.point(&proof.a);
+    transcript.point(&proof.b);
+    transcript.point(&proof.c);
+    let beta = transcript.challenge();
+    let gamma = transcript.challenge();
+    transcript.point(&proof.z);
+    let alpha = transcript.challenge();
+    transcript.point(&proof.t_lo);
+    transcript.point(&proof.t_mid);
+    transcript.point(&proof.t_hi);
+    let zeta = transcript.challenge();
+    for eval in &[proof.eval_a, proof.eval_b, proof.eval_c, proof.eval_s1, proof.eval_s2, proof.eval_zw] {
+        transcript.scalar(eval);
+    }
+    transcript.scalar(&proof.eval_r);
+    let v = transcript.challenge();
+    transcript.point(&proof.w_zeta);
+    transcript.point(&proof.w_zeta_omega);
+    let u = transcript.challenge();
+
+    let lagrange = domain.lagrange(&zeta, std::cmp::max(1, public.len()))?;
+    let zh = domain.vanishing(&zeta);
+    let mut alpha2 = alpha;
+    alpha2.square();
+
+    // t(zeta) from the linearization evaluation
+    let mut t = proof.eval_r;
+    for (x, l) in public.iter().zip(lagrange.iter()) {
+        let mut pi = *x;
+        pi.mul_assign(l);
+        t.sub_assign(&pi);
+    }
+    let mut copy = proof.eval_zw;
+    copy.mul_assign(&alpha);
+    copy.mul_assign(&permutation_term(&proof.eval_a, &beta, &proof.eval_s1, &gamma));
+    copy.mul_assign(&permutation_term(&proof.eval_b, &beta, &proof.eval_s2, &gamma));
+    let mut c_gamma = proof.eval_c;
+    c_gamma.add_assign(&gamma);
+    copy.mul_assign(&c_gamma);
+    t.sub_assign(&copy);
+    let mut first = lagrange[0];
+    first.mul_assign(&alpha2);
+    t.sub_assign(&first);
+    t.mul_assign(&zh.inverse().ok_or_else(|| Error::Unexpected("evaluation point in the domain".to_string()))?);
+
+    // [F] = [t_lo] + zeta^n [t_mid] + zeta^2n [t_hi] + v [r] + v^2 [a] + v^3 [b] + v^4 [c]
+    //   + v^5 [s_1] + v^6 [s_2] + u [z], with [r] from the verifying key and [z]
+    let k = Linearization::new(&Evaluations {
+        a: proof.eval_a,
+        b: proof.eval_b,
+        c: proof.eval_c,
+        s1: proof.eval_s1,
+        s2: proof.eval_s2,
+        zw: proof.eval_zw,
+    }, &Challenges { beta, gamma, alpha, zeta }, &lagrange[0]);
+    let zeta_n = zeta.pow(&[vk.n as u64]);
+    let mut zeta_2n = zeta_n;
+    zeta_2n.square();
+    let v_powers = poly::powers(&v, 7);
+    let mut bases = vec![proof.t_lo, proof.t_mid, proof.t_hi];
+    let mut scalars = vec![Fr::one(), zeta_n, zeta_2n];
+    for (base, k) in [vk.q_m, vk.q_l, vk.q_r, vk.q_o, vk.q_c, proof.z, vk.s_3].iter().zip(k.scalars.iter()) {
+        let mut scalar = *k;
+        scalar.mul_assign(&v);
+        bases.push(*base);
+        scalars.push(scalar);
+    }
+    bases.extend_from_slice(&[proof.a, proof.b, proof.c, vk.s_1, vk.s_2, proof.z]);
+    scalars.extend_from_slice(&v_powers[2..]);
+    scalars.push(u);
+
+    // E = t + v r + v^2 a + v^3 b + v^4 c + v^5 s_1 + v^6 s_2 + u zw
+    let mut e = t;
+    for (vi, eval) in v_powers[1..].iter().zip(
+        [proof.eval_r, proof.eval_a, proof.eval_b, proof.eval_c, proof.eval_s1, proof.eval_s2].iter(),
+    ) {
+        let mut term = *eval;
+        term.mul_assign(vi);
+        e.add_assign(&term);
+    }
+    let mut zw = proof.eval_zw;
+    zw.mul_assign(&u);
+    e.add_assign(&zw);
+
+    // e([W_zeta] + u [W_zeta_omega], [x]_2) = e(zeta [W_zeta] + u zeta omega [W_zeta_omega] + [F] - E [1], [1]_2)
+    let mut zeta_omega_u = zeta;
+    zeta_omega_u.mul_assign(&domain.omega);
+    zeta_omega_u.mul_assign(&u);
+    e.negate();
+    bases.extend_from_slice(&[proof.w_zeta, proof.w_zeta_omega, G1Affine::one()]);
+    scalars.extend_from_slice(&[zeta, zeta_omega_u, e]);
+    let right = multiexp(&bases, &scalars);
+    let mut left = proof.w_zeta_omega.mul(u.into_repr());
+    left.add_assign_mixed(&proof.w_zeta);
+    left.negate();
+
+    let (left, right) = (left.into_affine().prepare(), right.into_affine().prepare());
+    let (x_2, one) = (vk.x_2.prepare(), G2Affine::one().prepare());
+    let result = Bn256::final_exponentiation(&Bn256::miller_loop(&[(&left, &x_2), (&right, &one)]))
+        .ok_or_else(|| Error::Unexpected("final exponentiation failed".to_string()))?;
+    Ok(result == <Bn256 as Engine>::Fqk::one())
+}
+
+/// verification key from a `.json` file or a proving key
+pub fn read_vk_file(vk_path: &str) -> Result<VerifyingKey> {
+    let vk_file = BufReader::new(File::open(vk_path)?);
+    if vk_path.ends_with(".json") {
+        format::read_vk_json(vk_file)
+    } else {
+        format::read_pk_vk(vk_file)
+    }
+}
+
+/// verify a proof.json, the verifying key is read from a `.json` or from a
+///   proving key, public inputs are the ones in the proof file unless given
+pub fn verify_files(vk_path: &str, proof_path: &str, public_inputs: Option<Vec<FS>>) -> Result<bool> {
+    let vk = read_vk_file(vk_path)?;
+    let (proof_inputs, proof) = format::read_proof(BufReader::new(File::open(proof_path)?))?;
+
+    verify(&vk, &proof, &public_inputs.unwrap_or(proof_inputs))
+}
+
+/// w + beta label + gamma
+fn permutation_term(w: &Fr, beta: &Fr, label: &Fr, gamma: &Fr) -> Fr {
+    let mut term = *beta;
+    term.mul_assign(label);
+    term.add_assign(w);
+    term.add_assign(gamma);
+    term
+}
+
+struct Evaluations {
+    a: Fr,
+    b: Fr,
+    c: Fr,
+    s1: Fr,
+    s2: Fr,
+    zw: Fr,
+}
+
+struct Challenges {
+    beta: Fr,
+    gamma: Fr,
+    alpha: Fr,
+    zeta: Fr,
+}
+
+/// r(X) as a combination of q_m, q_l, q_r, q_o, q_c, z and s_3, the
+///   part of the quotient equation that is not already evaluated
+struct Linearization {
+    scalars: [Fr; 7],
+}
+
+impl Linearization {
+    fn new(e: &Evaluations, ch: &Challenges, l1_zeta: &Fr) -> Self {
+        let (k1, k2) = cosets();
+        let mul = |x: &Fr, y: &Fr| {
+            let mut r = *x;
+            r.mul_assign(y);
+            r
+        };
+        // alpha (a + beta zeta + gamma)(b + beta k1 zeta + gamma)(c + beta k2 zeta + gamma) + alpha^2 L1(zeta)
+        let mut z = ch.alpha;
+        z.mul_assign(&permutation_term(&e.a, &ch.beta, &ch.zeta, &ch.gamma));
+        z.mul_assign(&permutation_term(&e.b, &ch.beta, &mul(&k1, &ch.zeta), &ch.gamma));
+        z.mul_assign(&permutation_term(&e.c, &ch.beta, &mul(&k2, &ch.zeta), &ch.gamma));
+        let mut first = mul(&ch.alpha, &ch.alpha);
+        first.mul_assign(l1_zeta);
+        z.add_assign(&first);
+
+        // -alpha beta zw (a + beta s1 + gamma)(b + beta s2 + gamma)
+        let mut s_3 = mul(&ch.alpha, &ch.beta);
+        s_3.mul_assign(&e.zw);
+        s_3.mul_assign(&permutation_term(&e.a, &ch.beta, &e.s1, &ch.gamma));
+        s_3.mul_assign(&permutation_term(&e.b, &ch.beta, &e.s2, &ch.gamma));
+        s_3.negate();
+
+        Linearization {
+            scalars: [mul(&e.a, &e.b), e.a, e.b, e.c, Fr::one(), z, s_3],
+        }
+    }
+}
+
+/// Fiat-Shamir challenges, blake2b of everything sent so far
+struct Transcript(Vec<u8>);
+
+impl Transcript {
+    fn new(vk: &VerifyingKey, public: &[Fr]) -> Self {
+        let mut transcript = Transcript(Vec::new());
+        transcript.0.extend_from_slice(&(vk.n as u64).to_be_bytes());
+        for p in &[vk.q_m, vk.q_l, vk.q_r, vk.q_o, vk.q_c, vk.s_1, vk.s_2, vk.s_3] {
+            transcript.point(p);
+        }
+        for x in public {
+            transcript.scalar(x);
+        }
+        transcript
+    }
+
+    fn point(&mut self, p: &G1Affine) {
+        self.0.extend_from_slice(p.into_uncompressed().as_ref());
+    }
+
+    fn scalar(&mut self, x: &Fr) {
+        x.into_repr().write_be(&mut self.0).expect("cannot write to memory");
+    }
+
+    fn challenge(&mut self) -> Fr {
+        let mut hasher = Blake2b::new(64);
+        hasher.update(&self.0);
+        let hash = hasher.finalize();
+        self.0.extend_from_slice(hash.as_bytes());
+
+        // the first 32 bytes, masked to the bits that are always in the field
+        let mut repr = FrRepr::default();
+        repr.read_be(&hash.as_bytes()[..32]).expect("cannot read from memory");
+        repr.as_mut()[3] &= u64::max_value() >> (256 - Fr::CAPACITY);
+        Fr::from_repr(repr).expect("masked to the field capacity")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::groth16::new_rng;
+    use circom2_compiler::algebra::Value;
+    use circom2_compiler::evaluator::{Evaluator, Mode, Scope};
+    use circom2_compiler::progress::NoProgress;
+    use circom2_compiler::storage::{Ram, RamConstraints, RamSignals, StorageFactory};
+
+    const CIRCUIT: &str = "
+        template t() {
+            signal private input a;
+            signal private input b;
+            signal input k;
+            signal output c;
+
+            c <== (a + b + 1) * (a - k) + 7;
+        }
+        component main = t();
+    ";
+
+    /// the finished phases
+    #[derive(Default)]
+    struct Phases(Vec<Phase>);
+
+    impl Progress for Phases {
+        fn finish(&mut self, phase: Phase, _done: usize) {
+            self.0.push(phase);
+        }
+    }
+
+    fn eval(mode: Mode, circuit: &str, inputs: &[(&str, u64)]) -> Evaluator<'static, RamSignals, RamConstraints> {
+        let mut ram = Ram::default();
+        let mut ev = Evaluator::new(mode, ram.new_signals().unwrap(), ram.new_constraints().unwrap());
+        for (name, value) in inputs {
+            ev.set_deferred_value(format!("main.{}", name), Value::from(*value));
+        }
+        ev.eval_inline(&mut Scope::new(true, None, "root".to_string()), circuit)
+            .unwrap();
+        ev
+    }
+
+    #[test]
+    fn test_setup_prove_verify() {
+        let ev_r1cs = eval(Mode::GenConstraints, CIRCUIT, &[]);
+        let size = circuit_size(&ev_r1cs.signals, &ev_r1cs.constraints).unwrap();
+        let phase1 = Phase1::insecure(size, &mut new_rng(Some(1)));
+
+        let (mut pk, mut vk_json) = (Vec::new(), Vec::new());
+        setup(&phase1, &ev_r1cs.signals, &ev_r1cs.constraints, Some(&ev_r1cs.circuit_hash()), &mut pk, &mut vk_json, &mut NoProgress).unwrap();
+        let vk = format::read_vk_json(&vk_json[..]).unwrap();
+        assert_eq!(vk, format::read_pk_vk(&pk[..]).unwrap());
+
+        // c = (5 + 2 + 1) * (5 - 3) + 7 = 23
+        let ev_witness = eval(Mode::GenWitness, CIRCUIT, &[("a", 5), ("b", 2), ("k", 3)]);
+        let mut proof_json = Vec::new();
+        let mut phases = Phases::default();
+        let hash = ev_witness.circuit_hash();
+        let public = generate_verified_proof(ev_witness.signals, Some(&hash), &pk[..], &mut proof_json, &mut new_rng(Some(2)), &mut phases)
+            .unwrap();
+        assert_eq!(vec![Phase::LoadKey, Phase::Prove, Phase::Verify], phases.0);
+        let public = public.into_iter().map(|(_, v)| v).collect::<Vec<_>>();
+        let mut expected = vec![FS::from(23), FS::from(3)];
+        expected.sort_by_key(|v| v.0.clone());
+        let mut sorted = public.clone();
+        sorted.sort_by_key(|v| v.0.clone());
+        assert_eq!(expected, sorted);
+
+        let (inputs, proof) = format::read_proof(&proof_json[..]).unwrap();
+        assert_eq!(public, inputs);
+        assert!(verify(&vk, &proof, &inputs).unwrap());
+
+        // other public inputs, or a tampered proof, are refused
+        let wrong = inputs.iter().map(|v| FS::from(1).add(v, Bn256::FIELD)).collect::<Vec<_>>();
+        assert!(!verify(&vk, &proof, &wrong).unwrap());
+        let mut tampered = proof.clone();
+        tampered.eval_r.add_assign(&Fr::one());
+        assert!(!verify(&vk, &tampered, &inputs).unwrap());
+        assert!(verify(&vk, &proof, &inputs[1..]).is_err());
+
+        // a witness that does not satisfy the constraints
+        let mut bad = eval(Mode::GenWitness, CIRCUIT, &[("a", 5), ("b", 2), ("k", 3)]);
+        let c = bad.signals.get_by_name("main.c").unwrap().unwrap().id;
+        bad.signals.update(c, Value::from(24)).unwrap();
+        match generate_verified_proof(bad.signals, Some(&hash), &pk[..], &mut Vec::new(), &mut new_rng(Some(3)), &mut NoProgress) {
+            Err(Error::Mismatch(_)) => {}
+            _ => panic!("expected an unsatisfied gate"),
+        }
+
+        // the witness of another circuit
+        let other = eval(Mode::GenWitness, &CIRCUIT.replace("+ 7", "+ 8"), &[("a", 5), ("b", 2), ("k", 3)]);
+        let other_hash = other.circuit_hash();
+        match generate_verified_proof(other.signals, Some(&other_hash), &pk[..], &mut Vec::new(), &mut new_rng(Some(3)), &mut NoProgress) {
+            Err(Error::CircuitMismatch(_)) => {}
+            _ => panic!("expected a circuit mismatch"),
+        }
+    }
+
+    #[test]
+    fn test_universal_srs() {
+        // the same powers of tau work for circuits of different sizes
+        let other = "
+            template t() {
+                signal private input a;
+                signal output c;
+                signal x[4];
+
+                x[0] <== a * a;
+                x[1] <== x[0] * a + a;
+                x[2] <== x[1] * x[0];
+                x[3] <== x[2] + x[1] + x[0] + a;
+                c <== x[3] * 2;
+            }
+            component main = t();
+        ";
+        let phase1 = Phase1::insecure(32, &mut new_rng(Some(1)));
+        for (circuit, inputs) in &[(CIRCUIT, vec![("a", 1), ("b", 1), ("k", 1)]), (other, vec![("a", 3)])] {
+            let ev_r1cs = eval(Mode::GenConstraints, circuit, &[]);
+            let (mut pk, mut vk_json) = (Vec::new(), Vec::new());
+            setup(&phase1, &ev_r1cs.signals, &ev_r1cs.constraints, None, &mut pk, &mut vk_json, &mut NoProgress).unwrap();
+
+            let ev_witness = eval(Mode::GenWitness, circuit, inputs);
+            let (mut proof, rng) = (Vec::new(), &mut new_rng(Some(2)));
+            let public = generate_verified_proof(ev_witness.signals, None, &pk[..], &mut proof, rng, &mut NoProgress).unwrap();
+            let (_, proof) = format::read_proof(&proof[..]).unwrap();
+            let vk = format::read_vk_json(&vk_json[..]).unwrap();
+            let public = public.into_iter().map(|(_, v)| v).collect::<Vec<_>>();
+            assert!(verify(&vk, &proof, &public).unwrap());
+        }
+
+        let too_small = Phase1::insecure(4, &mut new_rng(Some(1)));
+        let ev_r1cs = eval(Mode::GenConstraints, CIRCUIT, &[]);
+        assert!(setup(&too_small, &ev_r1cs.signals, &ev_r1cs.constraints, None, Vec::new(), Vec::new(), &mut NoProgress).is_err());
+    }
+}
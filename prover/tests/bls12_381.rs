@@ -0,0 +1,116 @@
+use circom2_compiler::algebra::{Field, Value, FS};
+use circom2_compiler::evaluator::{Evaluator, Mode, Scope};
+use circom2_compiler::progress::NoProgress;
+use circom2_compiler::storage::{Ram, RamConstraints, RamSignals, StorageFactory};
+use circom2_prover::groth16::{self, Bls12, Bn256, Error};
+
+use std::fs::File;
+use std::io::Write;
+
+const CIRCUIT: &str = "
+    template t() {
+        signal private input a;
+        signal output c;
+        c <== a * a;
+    }
+    component main = t();
+";
+
+fn eval(mode: Mode, a: Option<FS>) -> Evaluator<'static, RamSignals, RamConstraints> {
+    let mut ram = Ram::new(Field::Bls12_381);
+    let mut ev = Evaluator::new(mode, ram.new_signals().unwrap(), ram.new_constraints().unwrap());
+    if let Some(a) = a {
+        ev.set_deferred_value("main.a".to_string(), Value::from(a));
+    }
+    ev.eval_inline(&mut Scope::new(true, None, "root".to_string()), CIRCUIT)
+        .unwrap();
+    ev
+}
+
+#[test]
+fn test_bls12_381() {
+    let ev_r1cs = eval(Mode::GenConstraints, None);
+    let (mut pk, mut json) = (Vec::new(), Vec::new());
+    groth16::setup::<Bls12, _, _, _, _, _, _>(
+        &ev_r1cs.signals,
+        &ev_r1cs.constraints,
+        None,
+        &mut pk,
+        None::<Vec<u8>>,
+        &mut json,
+        &mut groth16::new_rng(Some(1)),
+        &mut NoProgress,
+    )
+    .expect("cannot setup");
+    assert!(String::from_utf8_lossy(&json).contains("\"bls12381\""));
+
+    // -1 in the bls12-381 scalar field, it does not fit in the bn254 one
+    let minus_one = FS::parse(
+        "52435875175126190479447740508185965837690552500527637822603658699938581184512",
+    )
+    .unwrap();
+    let ev_witness = eval(Mode::GenWitness, Some(minus_one));
+    let mut proof = Vec::new();
+    let public_input = groth16::generate_verified_proof::<Bls12, _, _, _, _>(
+        ev_witness.signals,
+        None,
+        &pk[..],
+        &mut proof,
+        &mut groth16::new_rng(Some(2)),
+        &mut NoProgress,
+    )
+    .expect("cannot prove");
+    assert_eq!("[(\"main.c\", 1)]", format!("{:?}", public_input));
+
+    File::create("/tmp/bls12_381_vk.json").unwrap().write_all(&json).unwrap();
+    File::create("/tmp/bls12_381_pk").unwrap().write_all(&pk).unwrap();
+    File::create("/tmp/bls12_381_proof.json").unwrap().write_all(&proof).unwrap();
+    assert!(groth16::verify_files::<Bls12>("/tmp/bls12_381_vk.json", "/tmp/bls12_381_proof.json", None).unwrap());
+    assert!(groth16::verify_files::<Bls12>("/tmp/bls12_381_pk", "/tmp/bls12_381_proof.json", None).unwrap());
+    assert!(!groth16::verify_files::<Bls12>(
+        "/tmp/bls12_381_pk",
+        "/tmp/bls12_381_proof.json",
+        Some(vec![FS::from(2)])
+    )
+    .unwrap());
+
+    // keys of one curve are refused by the other
+    match groth16::read_vk_json::<Bn256, _>(&json[..]) {
+        Err(Error::Mismatch(_)) => {}
+        _ => panic!("expected a curve mismatch"),
+    }
+    match groth16::read_pk_vk::<Bn256, _>(&pk[..]) {
+        Err(Error::Mismatch(_)) => {}
+        _ => panic!("expected a curve mismatch"),
+    }
+
+    // no solidity verifier for bls12-381
+    match groth16::setup::<Bls12, _, _, _, _, _, _>(
+        &ev_r1cs.signals,
+        &ev_r1cs.constraints,
+        None,
+        Vec::new(),
+        Some(Vec::new()),
+        Vec::new(),
+        &mut groth16::new_rng(Some(1)),
+        &mut NoProgress,
+    ) {
+        Err(Error::Unsupported(_)) => {}
+        _ => panic!("expected solidity to be unsupported"),
+    }
+
+    // and no bn254 keys for a circuit in the bls12-381 field
+    match groth16::setup::<Bn256, _, _, _, _, _, _>(
+        &ev_r1cs.signals,
+        &ev_r1cs.constraints,
+        None,
+        Vec::new(),
+        None::<Vec<u8>>,
+        Vec::new(),
+        &mut groth16::new_rng(Some(1)),
+        &mut NoProgress,
+    ) {
+        Err(Error::Mismatch(_)) => {}
+        _ => panic!("expected a field mismatch"),
+    }
+}